@@ -0,0 +1,504 @@
+// batch.rs
+
+use super::{
+    body::{Body, EventCode},
+    errors::*,
+    message::Message,
+    message_stream::MessageStream,
+};
+use std::io::Read;
+
+/// Columns of the `Add Order` and `Add Order with MPID Attribution` messages.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct AddOrderColumns {
+    pub tag: Vec<u8>,
+    pub timestamp: Vec<u64>,
+    pub stock_locate: Vec<u16>,
+    pub order_id: Vec<u64>,
+    pub is_bid: Vec<bool>,
+    pub shares: Vec<u32>,
+    pub stock: Vec<u64>,
+    pub price: Vec<u32>,
+}
+
+/// Columns of the `Order Delete` messages.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct DeleteOrderColumns {
+    pub tag: Vec<u8>,
+    pub timestamp: Vec<u64>,
+    pub stock_locate: Vec<u16>,
+    pub order_id: Vec<u64>,
+}
+
+/// Columns of the `Order Cancel` messages.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct OrderCancelledColumns {
+    pub tag: Vec<u8>,
+    pub timestamp: Vec<u64>,
+    pub stock_locate: Vec<u16>,
+    pub order_id: Vec<u64>,
+    pub shares: Vec<u32>,
+}
+
+/// Columns of the `Order Executed` messages.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct OrderExecutedColumns {
+    pub tag: Vec<u8>,
+    pub timestamp: Vec<u64>,
+    pub stock_locate: Vec<u16>,
+    pub order_id: Vec<u64>,
+    pub shares: Vec<u32>,
+    pub match_number: Vec<u64>,
+}
+
+/// Columns of the `Order Executed with Price` messages.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct OrderExecutedWithPriceColumns {
+    pub tag: Vec<u8>,
+    pub timestamp: Vec<u64>,
+    pub stock_locate: Vec<u16>,
+    pub order_id: Vec<u64>,
+    pub shares: Vec<u32>,
+    pub match_number: Vec<u64>,
+    pub printable: Vec<bool>,
+    pub price: Vec<u32>,
+}
+
+/// Columns of the `Order Replace` messages.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct ReplaceOrderColumns {
+    pub tag: Vec<u8>,
+    pub timestamp: Vec<u64>,
+    pub stock_locate: Vec<u16>,
+    pub old_order_id: Vec<u64>,
+    pub new_order_id: Vec<u64>,
+    pub shares: Vec<u32>,
+    pub price: Vec<u32>,
+}
+
+/// Columns of the `System Event` messages.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct SystemEventColumns {
+    pub tag: Vec<u8>,
+    pub timestamp: Vec<u64>,
+    pub stock_locate: Vec<u16>,
+    pub event: Vec<EventCode>,
+}
+
+/// Columns of the `Stock Directory` messages.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct StockDirectoryColumns {
+    pub tag: Vec<u8>,
+    pub timestamp: Vec<u64>,
+    pub stock_locate: Vec<u16>,
+    pub stock: Vec<u64>,
+}
+
+/// Header columns of every message type that the parser skips.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct PassColumns {
+    pub tag: Vec<u8>,
+    pub timestamp: Vec<u64>,
+    pub stock_locate: Vec<u16>,
+}
+
+/// A struct-of-arrays view of a batch of messages, with one set of columns per message family.
+/// The rows of every family keep the order in which the messages appeared in the stream.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct MessageBatch {
+    pub add_orders: AddOrderColumns,
+    pub delete_orders: DeleteOrderColumns,
+    pub orders_cancelled: OrderCancelledColumns,
+    pub orders_executed: OrderExecutedColumns,
+    pub orders_executed_with_price: OrderExecutedWithPriceColumns,
+    pub replace_orders: ReplaceOrderColumns,
+    pub system_events: SystemEventColumns,
+    pub stock_directories: StockDirectoryColumns,
+    pub passes: PassColumns,
+}
+
+impl MessageBatch {
+    /// Creates a new, empty `MessageBatch`.
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the total number of messages in the batch.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.add_orders.len()
+            + self.delete_orders.len()
+            + self.orders_cancelled.len()
+            + self.orders_executed.len()
+            + self.orders_executed_with_price.len()
+            + self.replace_orders.len()
+            + self.system_events.len()
+            + self.stock_directories.len()
+            + self.passes.len()
+    }
+
+    /// Returns true if the batch holds no messages.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Clears every column while keeping the allocated capacity.
+    pub fn clear(&mut self) {
+        self.add_orders.clear();
+        self.delete_orders.clear();
+        self.orders_cancelled.clear();
+        self.orders_executed.clear();
+        self.orders_executed_with_price.clear();
+        self.replace_orders.clear();
+        self.system_events.clear();
+        self.stock_directories.clear();
+        self.passes.clear();
+    }
+
+    /// Appends a message to the columns of its family.
+    #[inline]
+    pub fn push(&mut self, msg: &Message) {
+        match msg.body {
+            Body::AddOrder {
+                order_id,
+                is_bid,
+                shares,
+                stock,
+                price,
+            } => {
+                let cols = &mut self.add_orders;
+                cols.tag.push(msg.tag);
+                cols.timestamp.push(msg.timestamp);
+                cols.stock_locate.push(msg.stock_locate);
+                cols.order_id.push(order_id);
+                cols.is_bid.push(is_bid);
+                cols.shares.push(shares);
+                cols.stock.push(stock);
+                cols.price.push(price);
+            }
+            Body::DeleteOrder { order_id } => {
+                let cols = &mut self.delete_orders;
+                cols.tag.push(msg.tag);
+                cols.timestamp.push(msg.timestamp);
+                cols.stock_locate.push(msg.stock_locate);
+                cols.order_id.push(order_id);
+            }
+            Body::OrderCancelled { order_id, shares } => {
+                let cols = &mut self.orders_cancelled;
+                cols.tag.push(msg.tag);
+                cols.timestamp.push(msg.timestamp);
+                cols.stock_locate.push(msg.stock_locate);
+                cols.order_id.push(order_id);
+                cols.shares.push(shares);
+            }
+            Body::OrderExecuted {
+                order_id,
+                shares,
+                match_number,
+            } => {
+                let cols = &mut self.orders_executed;
+                cols.tag.push(msg.tag);
+                cols.timestamp.push(msg.timestamp);
+                cols.stock_locate.push(msg.stock_locate);
+                cols.order_id.push(order_id);
+                cols.shares.push(shares);
+                cols.match_number.push(match_number);
+            }
+            Body::OrderExecutedWithPrice {
+                order_id,
+                shares,
+                match_number,
+                printable,
+                price,
+            } => {
+                let cols = &mut self.orders_executed_with_price;
+                cols.tag.push(msg.tag);
+                cols.timestamp.push(msg.timestamp);
+                cols.stock_locate.push(msg.stock_locate);
+                cols.order_id.push(order_id);
+                cols.shares.push(shares);
+                cols.match_number.push(match_number);
+                cols.printable.push(printable);
+                cols.price.push(price);
+            }
+            Body::ReplaceOrder {
+                old_order_id,
+                new_order_id,
+                shares,
+                price,
+            } => {
+                let cols = &mut self.replace_orders;
+                cols.tag.push(msg.tag);
+                cols.timestamp.push(msg.timestamp);
+                cols.stock_locate.push(msg.stock_locate);
+                cols.old_order_id.push(old_order_id);
+                cols.new_order_id.push(new_order_id);
+                cols.shares.push(shares);
+                cols.price.push(price);
+            }
+            Body::SystemEvent { event } => {
+                let cols = &mut self.system_events;
+                cols.tag.push(msg.tag);
+                cols.timestamp.push(msg.timestamp);
+                cols.stock_locate.push(msg.stock_locate);
+                cols.event.push(event);
+            }
+            Body::StockDirectory { stock } => {
+                let cols = &mut self.stock_directories;
+                cols.tag.push(msg.tag);
+                cols.timestamp.push(msg.timestamp);
+                cols.stock_locate.push(msg.stock_locate);
+                cols.stock.push(stock);
+            }
+            Body::Pass(()) => {
+                let cols = &mut self.passes;
+                cols.tag.push(msg.tag);
+                cols.timestamp.push(msg.timestamp);
+                cols.stock_locate.push(msg.stock_locate);
+            }
+        }
+    }
+}
+
+impl AddOrderColumns {
+    /// Returns the number of rows.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.tag.len()
+    }
+
+    /// Returns true if there are no rows.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.tag.is_empty()
+    }
+
+    /// Clears every column while keeping the allocated capacity.
+    pub fn clear(&mut self) {
+        self.tag.clear();
+        self.timestamp.clear();
+        self.stock_locate.clear();
+        self.order_id.clear();
+        self.is_bid.clear();
+        self.shares.clear();
+        self.stock.clear();
+        self.price.clear();
+    }
+}
+
+impl DeleteOrderColumns {
+    /// Returns the number of rows.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.tag.len()
+    }
+
+    /// Returns true if there are no rows.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.tag.is_empty()
+    }
+
+    /// Clears every column while keeping the allocated capacity.
+    pub fn clear(&mut self) {
+        self.tag.clear();
+        self.timestamp.clear();
+        self.stock_locate.clear();
+        self.order_id.clear();
+    }
+}
+
+impl OrderCancelledColumns {
+    /// Returns the number of rows.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.tag.len()
+    }
+
+    /// Returns true if there are no rows.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.tag.is_empty()
+    }
+
+    /// Clears every column while keeping the allocated capacity.
+    pub fn clear(&mut self) {
+        self.tag.clear();
+        self.timestamp.clear();
+        self.stock_locate.clear();
+        self.order_id.clear();
+        self.shares.clear();
+    }
+}
+
+impl OrderExecutedColumns {
+    /// Returns the number of rows.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.tag.len()
+    }
+
+    /// Returns true if there are no rows.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.tag.is_empty()
+    }
+
+    /// Clears every column while keeping the allocated capacity.
+    pub fn clear(&mut self) {
+        self.tag.clear();
+        self.timestamp.clear();
+        self.stock_locate.clear();
+        self.order_id.clear();
+        self.shares.clear();
+        self.match_number.clear();
+    }
+}
+
+impl OrderExecutedWithPriceColumns {
+    /// Returns the number of rows.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.tag.len()
+    }
+
+    /// Returns true if there are no rows.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.tag.is_empty()
+    }
+
+    /// Clears every column while keeping the allocated capacity.
+    pub fn clear(&mut self) {
+        self.tag.clear();
+        self.timestamp.clear();
+        self.stock_locate.clear();
+        self.order_id.clear();
+        self.shares.clear();
+        self.match_number.clear();
+        self.printable.clear();
+        self.price.clear();
+    }
+}
+
+impl ReplaceOrderColumns {
+    /// Returns the number of rows.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.tag.len()
+    }
+
+    /// Returns true if there are no rows.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.tag.is_empty()
+    }
+
+    /// Clears every column while keeping the allocated capacity.
+    pub fn clear(&mut self) {
+        self.tag.clear();
+        self.timestamp.clear();
+        self.stock_locate.clear();
+        self.old_order_id.clear();
+        self.new_order_id.clear();
+        self.shares.clear();
+        self.price.clear();
+    }
+}
+
+impl SystemEventColumns {
+    /// Returns the number of rows.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.tag.len()
+    }
+
+    /// Returns true if there are no rows.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.tag.is_empty()
+    }
+
+    /// Clears every column while keeping the allocated capacity.
+    pub fn clear(&mut self) {
+        self.tag.clear();
+        self.timestamp.clear();
+        self.stock_locate.clear();
+        self.event.clear();
+    }
+}
+
+impl StockDirectoryColumns {
+    /// Returns the number of rows.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.tag.len()
+    }
+
+    /// Returns true if there are no rows.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.tag.is_empty()
+    }
+
+    /// Clears every column while keeping the allocated capacity.
+    pub fn clear(&mut self) {
+        self.tag.clear();
+        self.timestamp.clear();
+        self.stock_locate.clear();
+        self.stock.clear();
+    }
+}
+
+impl PassColumns {
+    /// Returns the number of rows.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.tag.len()
+    }
+
+    /// Returns true if there are no rows.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.tag.is_empty()
+    }
+
+    /// Clears every column while keeping the allocated capacity.
+    pub fn clear(&mut self) {
+        self.tag.clear();
+        self.timestamp.clear();
+        self.stock_locate.clear();
+    }
+}
+
+impl<R: Read> MessageStream<R> {
+    /// Decodes up to `n` messages into a new columnar `MessageBatch`.
+    /// An empty batch means that the stream is exhausted.
+    #[inline]
+    pub fn next_batch(&mut self, n: usize) -> Result<MessageBatch> {
+        let mut batch = MessageBatch::new();
+        self.next_batch_into(n, &mut batch)?;
+        Ok(batch)
+    }
+
+    /// Decodes up to `n` messages into `batch`, reusing its allocations.
+    /// The batch is cleared first.
+    ///
+    /// # Returns
+    /// Returns the number of decoded messages, 0 once the stream is exhausted.
+    #[inline]
+    pub fn next_batch_into(&mut self, n: usize, batch: &mut MessageBatch) -> Result<usize> {
+        batch.clear();
+        let mut decoded = 0;
+        while decoded < n {
+            match self.next() {
+                Some(msg) => batch.push(&msg?),
+                None => break,
+            }
+            decoded += 1;
+        }
+        Ok(decoded)
+    }
+}
@@ -0,0 +1,60 @@
+// itch-subset.rs
+
+extern crate itch_parser;
+
+use itch_parser::{extract_subset, parse_time_of_day, MessageStream, MessageWriter, SubsetFilter};
+use std::{env, process};
+
+const USAGE: &str = "Usage: itch-subset <INPUT> <OUTPUT> [--symbols AAPL,MSFT] \
+                     [--start HH:MM:SS[.fraction]] [--end HH:MM:SS[.fraction]]";
+
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    if args.len() < 2 {
+        eprintln!("{}", USAGE);
+        process::exit(1);
+    }
+
+    let mut filter = SubsetFilter::new();
+    let (mut start, mut end) = (None, None);
+    let mut options = args[2..].iter();
+    while let Some(option) = options.next() {
+        let value = options.next().unwrap_or_else(|| {
+            eprintln!("Missing value for {}\n{}", option, USAGE);
+            process::exit(1);
+        });
+        let time = || {
+            parse_time_of_day(value).unwrap_or_else(|| {
+                eprintln!("Invalid time {}\n{}", value, USAGE);
+                process::exit(1);
+            })
+        };
+        match option.as_str() {
+            "--symbols" => {
+                let symbols: Vec<&str> = value.split(',').collect();
+                filter = filter.with_symbols(&symbols);
+            }
+            "--start" => start = Some(time()),
+            "--end" => end = Some(time()),
+            _ => {
+                eprintln!("Unknown option {}\n{}", option, USAGE);
+                process::exit(1);
+            }
+        }
+    }
+    let filter = filter.with_window(start, end);
+
+    let mut stream = MessageStream::from_file(&args[0]).unwrap_or_else(|e| {
+        eprintln!("Cannot open {}: {}", args[0], e);
+        process::exit(1);
+    });
+    let mut writer = MessageWriter::create(&args[1]).unwrap_or_else(|e| {
+        eprintln!("Cannot create {}: {}", args[1], e);
+        process::exit(1);
+    });
+    let messages = extract_subset(&mut stream, &mut writer, &filter).unwrap_or_else(|e| {
+        eprintln!("Cannot extract the subset of {}: {}", args[0], e);
+        process::exit(1);
+    });
+    println!("Wrote {} messages into {}", messages, args[1]);
+}
@@ -4,6 +4,7 @@ use nom::IResult;
 
 /// The message body. It just uses the important variants and their fields.
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Body {
     AddOrder {
         order_id: u64,
@@ -40,12 +41,16 @@ pub enum Body {
     SystemEvent {
         event: EventCode,
     },
+    StockDirectory {
+        stock: u64,
+    },
     // Enum variant representing a placeholder "Pass" message with no data.
     Pass(()),
 }
 
 // Enum representing different event codes for SystemEvent messages.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum EventCode {
     StartOfMessages,
     StartOfSystemHours,
@@ -55,6 +60,21 @@ pub enum EventCode {
     EndOfMessages,
 }
 
+impl EventCode {
+    /// Returns the ITCH character of the event code.
+    #[inline]
+    pub fn code(&self) -> u8 {
+        match self {
+            EventCode::StartOfMessages => b'O',
+            EventCode::StartOfSystemHours => b'S',
+            EventCode::StartOfMarketHours => b'Q',
+            EventCode::EndOfMarketHours => b'M',
+            EventCode::EndOfSystemHours => b'E',
+            EventCode::EndOfMessages => b'C',
+        }
+    }
+}
+
 // Parse a SystemEvent message from input bytes.
 #[inline]
 pub fn parse_system_event(input: &[u8]) -> IResult<&[u8], EventCode> {
@@ -6,5 +6,8 @@ error_chain! {
     foreign_links {
         Io(::std::io::Error);
         Nom(::nom::Err<u8>);
+        Csv(::csv::Error) #[cfg(feature = "csv")];
+        Json(::serde_json::Error) #[cfg(feature = "json")];
+        Parquet(::parquet::errors::ParquetError) #[cfg(feature = "parquet")];
     }
 }
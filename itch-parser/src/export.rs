@@ -0,0 +1,256 @@
+// export.rs
+
+use super::{
+    errors::*, message::Message, message_stream::MessageStream, symbol_directory::SymbolDirectory,
+};
+use std::io::Read;
+
+#[cfg(any(feature = "csv", feature = "json"))]
+use {
+    super::body::Body,
+    super::utils::{format_price, message_type_name, stock_symbol},
+    serde::Serialize,
+    std::io::Write,
+};
+
+/// Selects which messages are exported.
+/// An unset filter lets every message through.
+#[derive(Debug, Default, Clone)]
+pub struct ExportFilter {
+    tags: Option<Vec<u8>>,
+    symbols: Option<Vec<String>>,
+}
+
+impl ExportFilter {
+    /// Creates a filter that lets every message through.
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Only exports the messages with one of the given tags (e.g. `b"AFD"`).
+    pub fn with_tags(mut self, tags: &[u8]) -> Self {
+        self.tags = Some(tags.to_vec());
+        self
+    }
+
+    /// Only exports the messages whose stock locate maps to one of the given symbols.
+    /// Messages that are not tied to a known symbol, like system events, are dropped.
+    pub fn with_symbols<S: AsRef<str>>(mut self, symbols: &[S]) -> Self {
+        self.symbols = Some(symbols.iter().map(|s| s.as_ref().to_string()).collect());
+        self
+    }
+
+    /// Returns true if the message passes the filter.
+    #[inline]
+    pub fn matches(&self, msg: &Message, symbol: Option<&str>) -> bool {
+        if let Some(tags) = &self.tags {
+            if !tags.contains(&msg.tag) {
+                return false;
+            }
+        }
+        match (&self.symbols, symbol) {
+            (None, _) => true,
+            (Some(symbols), Some(symbol)) => symbols.iter().any(|s| s == symbol),
+            (Some(_), None) => false,
+        }
+    }
+}
+
+/// Calls `f` with every message passing the filter and the symbol of its stock locate.
+///
+/// # Returns
+/// Returns the number of messages passed to `f`.
+pub(crate) fn for_each_filtered<R, F>(
+    stream: &mut MessageStream<R>,
+    filter: &ExportFilter,
+    mut f: F,
+) -> Result<u64>
+where
+    R: Read,
+    F: FnMut(&Message, Option<&str>) -> Result<()>,
+{
+    let mut directory = SymbolDirectory::new();
+    let mut exported = 0;
+    for msg in stream {
+        let msg = msg?;
+        directory.update(&msg);
+        let symbol = directory.symbol(msg.stock_locate);
+        if filter.matches(&msg, symbol) {
+            f(&msg, symbol)?;
+            exported += 1;
+        }
+    }
+    Ok(exported)
+}
+
+/// A flat, row-oriented view of a message used by the CSV and NDJSON exporters.
+/// Fields that do not exist for a message type are left empty.
+#[cfg(any(feature = "csv", feature = "json"))]
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ExportRecord {
+    pub message_type: &'static str,
+    pub tag: char,
+    pub stock_locate: u16,
+    pub tracking_number: u16,
+    pub timestamp: u64,
+    pub symbol: Option<String>,
+    pub order_id: Option<u64>,
+    pub new_order_id: Option<u64>,
+    pub side: Option<char>,
+    pub shares: Option<u32>,
+    pub stock: Option<String>,
+    pub price: Option<String>,
+    pub match_number: Option<u64>,
+    pub printable: Option<bool>,
+    pub event: Option<String>,
+}
+
+#[cfg(any(feature = "csv", feature = "json"))]
+impl ExportRecord {
+    /// Names of the fields, in the order they are written.
+    pub const COLUMNS: [&'static str; 15] = [
+        "message_type",
+        "tag",
+        "stock_locate",
+        "tracking_number",
+        "timestamp",
+        "symbol",
+        "order_id",
+        "new_order_id",
+        "side",
+        "shares",
+        "stock",
+        "price",
+        "match_number",
+        "printable",
+        "event",
+    ];
+
+    /// Creates the record of a message, with its symbol and formatted prices.
+    pub fn new(msg: &Message, symbol: Option<&str>) -> Self {
+        let mut record = Self {
+            message_type: message_type_name(msg.tag),
+            tag: msg.tag as char,
+            stock_locate: msg.stock_locate,
+            tracking_number: msg.tracking_number,
+            timestamp: msg.timestamp,
+            symbol: symbol.map(str::to_string),
+            order_id: None,
+            new_order_id: None,
+            side: None,
+            shares: None,
+            stock: None,
+            price: None,
+            match_number: None,
+            printable: None,
+            event: None,
+        };
+        match msg.body {
+            Body::AddOrder {
+                order_id,
+                is_bid,
+                shares,
+                stock,
+                price,
+            } => {
+                record.order_id = Some(order_id);
+                record.side = Some(if is_bid { 'B' } else { 'S' });
+                record.shares = Some(shares);
+                record.stock = Some(stock_symbol(stock));
+                record.price = Some(format_price(price));
+            }
+            Body::DeleteOrder { order_id } => {
+                record.order_id = Some(order_id);
+            }
+            Body::OrderCancelled { order_id, shares } => {
+                record.order_id = Some(order_id);
+                record.shares = Some(shares);
+            }
+            Body::OrderExecuted {
+                order_id,
+                shares,
+                match_number,
+            } => {
+                record.order_id = Some(order_id);
+                record.shares = Some(shares);
+                record.match_number = Some(match_number);
+            }
+            Body::OrderExecutedWithPrice {
+                order_id,
+                shares,
+                match_number,
+                printable,
+                price,
+            } => {
+                record.order_id = Some(order_id);
+                record.shares = Some(shares);
+                record.match_number = Some(match_number);
+                record.printable = Some(printable);
+                record.price = Some(format_price(price));
+            }
+            Body::ReplaceOrder {
+                old_order_id,
+                new_order_id,
+                shares,
+                price,
+            } => {
+                record.order_id = Some(old_order_id);
+                record.new_order_id = Some(new_order_id);
+                record.shares = Some(shares);
+                record.price = Some(format_price(price));
+            }
+            Body::SystemEvent { event } => {
+                record.event = Some(format!("{:?}", event));
+            }
+            Body::StockDirectory { stock } => {
+                record.stock = Some(stock_symbol(stock));
+            }
+            Body::Pass(()) => {}
+        }
+        record
+    }
+}
+
+/// Writes every message passing the filter as a CSV row, preceded by a header row.
+/// The header row is written even when no message passes the filter.
+///
+/// # Returns
+/// Returns the number of exported messages.
+#[cfg(feature = "csv")]
+pub fn export_csv<R: Read, W: Write>(
+    stream: &mut MessageStream<R>,
+    writer: W,
+    filter: &ExportFilter,
+) -> Result<u64> {
+    let mut writer = csv::WriterBuilder::new()
+        .has_headers(false)
+        .from_writer(writer);
+    writer.write_record(ExportRecord::COLUMNS)?;
+    let exported = for_each_filtered(stream, filter, |msg, symbol| {
+        writer.serialize(ExportRecord::new(msg, symbol))?;
+        Ok(())
+    })?;
+    writer.flush()?;
+    Ok(exported)
+}
+
+/// Writes every message passing the filter as a JSON object, one per line.
+///
+/// # Returns
+/// Returns the number of exported messages.
+#[cfg(feature = "json")]
+pub fn export_ndjson<R: Read, W: Write>(
+    stream: &mut MessageStream<R>,
+    writer: W,
+    filter: &ExportFilter,
+) -> Result<u64> {
+    let mut writer = std::io::BufWriter::new(writer);
+    let exported = for_each_filtered(stream, filter, |msg, symbol| {
+        serde_json::to_writer(&mut writer, &ExportRecord::new(msg, symbol))?;
+        writer.write_all(b"\n")?;
+        Ok(())
+    })?;
+    writer.flush()?;
+    Ok(exported)
+}
@@ -0,0 +1,306 @@
+// export_parquet.rs
+
+use super::{
+    body::Body,
+    errors::*,
+    export::{for_each_filtered, ExportFilter},
+    message::Message,
+    message_stream::MessageStream,
+    utils::{message_type_name, stock_symbol},
+};
+use parquet::{
+    basic::{Repetition, Type as PhysicalType},
+    data_type::{BoolType, ByteArray, ByteArrayType, Int32Type, Int64Type},
+    file::{properties::WriterProperties, writer::SerializedFileWriter},
+    schema::parser::parse_message_type,
+};
+use std::{fs::File, io::Read, path::Path, sync::Arc};
+
+/// Number of rows buffered per file before a row group is written.
+const ROW_GROUP_SIZE: usize = 1 << 20;
+
+/// Columns shared by every message type.
+const HEADER_SCHEMA: &str = "
+    REQUIRED BYTE_ARRAY tag (STRING);
+    REQUIRED INT64 timestamp (INTEGER(64, false));
+    REQUIRED INT32 stock_locate (INTEGER(16, false));
+    REQUIRED INT32 tracking_number (INTEGER(16, false));
+    OPTIONAL BYTE_ARRAY symbol (STRING);
+";
+
+/// A single cell of a row, in schema order.
+enum Value {
+    Bool(bool),
+    Int32(i32),
+    Int64(i64),
+    Utf8(ByteArray),
+    OptionalUtf8(Option<ByteArray>),
+}
+
+/// Buffered values of a column, matching its physical type and repetition.
+enum ColumnData {
+    Bool(Vec<bool>),
+    Int32(Vec<i32>),
+    Int64(Vec<i64>),
+    Utf8(Vec<ByteArray>),
+    OptionalUtf8(Vec<Option<ByteArray>>),
+}
+
+/// A Parquet file holding the messages of one message type.
+struct Table {
+    writer: SerializedFileWriter<File>,
+    columns: Vec<ColumnData>,
+    rows: usize,
+}
+
+impl Table {
+    /// Creates `<dir>/<message type name>.parquet` with the header and body columns.
+    fn create(dir: &Path, tag: u8, body_schema: &str) -> Result<Self> {
+        let name = message_type_name(tag);
+        let schema = Arc::new(parse_message_type(&format!(
+            "message {} {{ {} {} }}",
+            name, HEADER_SCHEMA, body_schema
+        ))?);
+        let columns = schema
+            .get_fields()
+            .iter()
+            .map(|field| {
+                let optional = field.get_basic_info().repetition() == Repetition::OPTIONAL;
+                match field.get_physical_type() {
+                    PhysicalType::BOOLEAN => ColumnData::Bool(Vec::new()),
+                    PhysicalType::INT32 => ColumnData::Int32(Vec::new()),
+                    PhysicalType::INT64 => ColumnData::Int64(Vec::new()),
+                    _ if optional => ColumnData::OptionalUtf8(Vec::new()),
+                    _ => ColumnData::Utf8(Vec::new()),
+                }
+            })
+            .collect();
+        let file = File::create(dir.join(format!("{}.parquet", name)))?;
+        let props = Arc::new(WriterProperties::builder().build());
+        Ok(Self {
+            writer: SerializedFileWriter::new(file, schema, props)?,
+            columns,
+            rows: 0,
+        })
+    }
+
+    /// Appends a row and writes a row group once enough rows are buffered.
+    fn push(&mut self, row: Vec<Value>) -> Result<()> {
+        debug_assert_eq!(row.len(), self.columns.len());
+        for (column, value) in self.columns.iter_mut().zip(row) {
+            match (column, value) {
+                (ColumnData::Bool(c), Value::Bool(v)) => c.push(v),
+                (ColumnData::Int32(c), Value::Int32(v)) => c.push(v),
+                (ColumnData::Int64(c), Value::Int64(v)) => c.push(v),
+                (ColumnData::Utf8(c), Value::Utf8(v)) => c.push(v),
+                (ColumnData::OptionalUtf8(c), Value::OptionalUtf8(v)) => c.push(v),
+                _ => unreachable!("row does not match the schema"),
+            }
+        }
+        self.rows += 1;
+        if self.rows == ROW_GROUP_SIZE {
+            self.flush()?;
+        }
+        Ok(())
+    }
+
+    /// Writes the buffered rows as a row group.
+    fn flush(&mut self) -> Result<()> {
+        let mut row_group = self.writer.next_row_group()?;
+        for column in self.columns.iter_mut() {
+            let mut writer = row_group
+                .next_column()?
+                .expect("column count matches the schema");
+            match column {
+                ColumnData::Bool(c) => {
+                    writer.typed::<BoolType>().write_batch(c, None, None)?;
+                    c.clear();
+                }
+                ColumnData::Int32(c) => {
+                    writer.typed::<Int32Type>().write_batch(c, None, None)?;
+                    c.clear();
+                }
+                ColumnData::Int64(c) => {
+                    writer.typed::<Int64Type>().write_batch(c, None, None)?;
+                    c.clear();
+                }
+                ColumnData::Utf8(c) => {
+                    writer.typed::<ByteArrayType>().write_batch(c, None, None)?;
+                    c.clear();
+                }
+                ColumnData::OptionalUtf8(c) => {
+                    let def_levels: Vec<i16> = c.iter().map(|v| v.is_some() as i16).collect();
+                    let values: Vec<ByteArray> = c.drain(..).flatten().collect();
+                    writer.typed::<ByteArrayType>().write_batch(
+                        &values,
+                        Some(&def_levels),
+                        None,
+                    )?;
+                }
+            }
+            writer.close()?;
+        }
+        row_group.close()?;
+        self.rows = 0;
+        Ok(())
+    }
+
+    /// Writes the remaining rows and the file footer.
+    fn finish(mut self) -> Result<()> {
+        if self.rows > 0 {
+            self.flush()?;
+        }
+        self.writer.close()?;
+        Ok(())
+    }
+}
+
+/// Returns the schema of the body columns of a message.
+#[inline]
+fn body_schema(body: &Body) -> &'static str {
+    match body {
+        Body::AddOrder { .. } => {
+            "REQUIRED INT64 order_id (INTEGER(64, false));
+            REQUIRED BYTE_ARRAY side (STRING);
+            REQUIRED INT32 shares (INTEGER(32, false));
+            REQUIRED BYTE_ARRAY stock (STRING);
+            REQUIRED INT64 price (DECIMAL(18, 4));"
+        }
+        Body::DeleteOrder { .. } => "REQUIRED INT64 order_id (INTEGER(64, false));",
+        Body::OrderCancelled { .. } => {
+            "REQUIRED INT64 order_id (INTEGER(64, false));
+            REQUIRED INT32 shares (INTEGER(32, false));"
+        }
+        Body::OrderExecuted { .. } => {
+            "REQUIRED INT64 order_id (INTEGER(64, false));
+            REQUIRED INT32 shares (INTEGER(32, false));
+            REQUIRED INT64 match_number (INTEGER(64, false));"
+        }
+        Body::OrderExecutedWithPrice { .. } => {
+            "REQUIRED INT64 order_id (INTEGER(64, false));
+            REQUIRED INT32 shares (INTEGER(32, false));
+            REQUIRED INT64 match_number (INTEGER(64, false));
+            REQUIRED BOOLEAN printable;
+            REQUIRED INT64 price (DECIMAL(18, 4));"
+        }
+        Body::ReplaceOrder { .. } => {
+            "REQUIRED INT64 old_order_id (INTEGER(64, false));
+            REQUIRED INT64 new_order_id (INTEGER(64, false));
+            REQUIRED INT32 shares (INTEGER(32, false));
+            REQUIRED INT64 price (DECIMAL(18, 4));"
+        }
+        Body::SystemEvent { .. } => "REQUIRED BYTE_ARRAY event (STRING);",
+        Body::StockDirectory { .. } => "REQUIRED BYTE_ARRAY stock (STRING);",
+        Body::Pass(()) => "",
+    }
+}
+
+/// Returns the values of a message in schema order.
+/// Unsigned fields are stored with their bits reinterpreted, as the Parquet spec requires.
+fn row(msg: &Message, symbol: Option<&str>) -> Vec<Value> {
+    let mut row = vec![
+        Value::Utf8(ByteArray::from(vec![msg.tag])),
+        Value::Int64(msg.timestamp as i64),
+        Value::Int32(msg.stock_locate as i32),
+        Value::Int32(msg.tracking_number as i32),
+        Value::OptionalUtf8(symbol.map(ByteArray::from)),
+    ];
+    match msg.body {
+        Body::AddOrder {
+            order_id,
+            is_bid,
+            shares,
+            stock,
+            price,
+        } => row.extend([
+            Value::Int64(order_id as i64),
+            Value::Utf8(ByteArray::from(if is_bid { "B" } else { "S" })),
+            Value::Int32(shares as i32),
+            Value::Utf8(ByteArray::from(stock_symbol(stock).as_str())),
+            Value::Int64(price as i64),
+        ]),
+        Body::DeleteOrder { order_id } => row.push(Value::Int64(order_id as i64)),
+        Body::OrderCancelled { order_id, shares } => {
+            row.extend([Value::Int64(order_id as i64), Value::Int32(shares as i32)])
+        }
+        Body::OrderExecuted {
+            order_id,
+            shares,
+            match_number,
+        } => row.extend([
+            Value::Int64(order_id as i64),
+            Value::Int32(shares as i32),
+            Value::Int64(match_number as i64),
+        ]),
+        Body::OrderExecutedWithPrice {
+            order_id,
+            shares,
+            match_number,
+            printable,
+            price,
+        } => row.extend([
+            Value::Int64(order_id as i64),
+            Value::Int32(shares as i32),
+            Value::Int64(match_number as i64),
+            Value::Bool(printable),
+            Value::Int64(price as i64),
+        ]),
+        Body::ReplaceOrder {
+            old_order_id,
+            new_order_id,
+            shares,
+            price,
+        } => row.extend([
+            Value::Int64(old_order_id as i64),
+            Value::Int64(new_order_id as i64),
+            Value::Int32(shares as i32),
+            Value::Int64(price as i64),
+        ]),
+        Body::SystemEvent { event } => row.push(Value::Utf8(ByteArray::from(
+            format!("{:?}", event).as_str(),
+        ))),
+        Body::StockDirectory { stock } => {
+            row.push(Value::Utf8(ByteArray::from(stock_symbol(stock).as_str())))
+        }
+        Body::Pass(()) => {}
+    }
+    row
+}
+
+/// Writes every message passing the filter to Parquet files in `dir`, one file per message
+/// type named after it (e.g. `add_order.parquet`). Prices are stored as `DECIMAL(18, 4)`.
+/// If reading the stream fails, the files written so far are still closed before the error is
+/// returned.
+///
+/// # Returns
+/// Returns the number of exported messages.
+pub fn export_parquet<R: Read, P: AsRef<Path>>(
+    stream: &mut MessageStream<R>,
+    dir: P,
+    filter: &ExportFilter,
+) -> Result<u64> {
+    let dir = dir.as_ref();
+    std::fs::create_dir_all(dir)?;
+
+    let mut tables: Vec<Option<Table>> = (0..=u8::MAX).map(|_| None).collect();
+    let exported = for_each_filtered(stream, filter, |msg, symbol| {
+        let table = &mut tables[msg.tag as usize];
+        if table.is_none() {
+            *table = Some(Table::create(dir, msg.tag, body_schema(&msg.body))?);
+        }
+        table.as_mut().unwrap().push(row(msg, symbol))
+    });
+
+    // Every file gets its footer, even when the stream fails, so that the messages exported
+    // before the error stay readable.
+    let mut finished = Ok(());
+    for table in tables.into_iter().flatten() {
+        let result = table.finish();
+        if finished.is_ok() {
+            finished = result;
+        }
+    }
+    let exported = exported?;
+    finished?;
+    Ok(exported)
+}
@@ -0,0 +1,410 @@
+// generator.rs
+
+use super::{
+    body::{Body, EventCode},
+    errors::*,
+    message::Message,
+    message_writer::MessageWriter,
+};
+use std::{collections::BTreeMap, io::Write, path::Path};
+
+/// Nanoseconds since midnight at 09:30, when the generated market opens.
+const MARKET_OPEN: u64 = 34_200_000_000_000;
+/// Price(4) of one cent, the tick size of the generated books.
+const TICK: u32 = 100;
+
+/// Parameters of the random order-flow model.
+/// The weights give the relative frequency of each order message type.
+#[derive(Debug, Clone)]
+pub struct GeneratorConfig {
+    /// Seed of the random number generator. The same seed gives the same file.
+    pub seed: u64,
+    /// Symbols to generate, each gets the stock locate of its position plus one.
+    pub symbols: Vec<String>,
+    /// Number of order messages to generate, excluding the system and directory messages.
+    pub order_messages: usize,
+    /// Initial mid price of every symbol, as a Price(4).
+    pub initial_price: u32,
+    /// Percentage of the adds that carry an MPID attribution (tag `F`).
+    pub mpid_percent: u32,
+    pub add_weight: u32,
+    pub execute_weight: u32,
+    pub cancel_weight: u32,
+    pub delete_weight: u32,
+    pub replace_weight: u32,
+}
+
+impl Default for GeneratorConfig {
+    fn default() -> Self {
+        Self {
+            seed: 0,
+            symbols: vec!["AAPL".to_string(), "MSFT".to_string(), "QQQ".to_string()],
+            order_messages: 10_000,
+            initial_price: 1_000_000,
+            mpid_percent: 10,
+            add_weight: 45,
+            execute_weight: 5,
+            cancel_weight: 5,
+            delete_weight: 35,
+            replace_weight: 10,
+        }
+    }
+}
+
+/// A small, seedable SplitMix64 random number generator.
+struct Rng(u64);
+
+impl Rng {
+    #[inline]
+    fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    /// Returns a number in `0..n`.
+    #[inline]
+    fn below(&mut self, n: u64) -> u64 {
+        self.next_u64() % n
+    }
+}
+
+/// An order that is still resting in the generated book.
+#[derive(Debug, Clone, Copy)]
+struct LiveOrder {
+    order_id: u64,
+    stock_locate: u16,
+    is_bid: bool,
+    shares: u32,
+    price: u32,
+}
+
+/// The prices of the live orders of a symbol, with the number of orders resting at each.
+#[derive(Debug, Default, Clone)]
+struct RestingPrices {
+    bids: BTreeMap<u32, u32>,
+    asks: BTreeMap<u32, u32>,
+}
+
+impl RestingPrices {
+    #[inline]
+    fn side(&mut self, is_bid: bool) -> &mut BTreeMap<u32, u32> {
+        if is_bid {
+            &mut self.bids
+        } else {
+            &mut self.asks
+        }
+    }
+
+    fn insert(&mut self, order: &LiveOrder) {
+        *self.side(order.is_bid).entry(order.price).or_insert(0) += 1;
+    }
+
+    fn remove(&mut self, order: &LiveOrder) {
+        let side = self.side(order.is_bid);
+        if let Some(count) = side.get_mut(&order.price) {
+            *count -= 1;
+            if *count == 0 {
+                side.remove(&order.price);
+            }
+        }
+    }
+}
+
+/// Generates valid ITCH 5.0 data from a seeded random order-flow model.
+///
+/// The output starts with the system events and a `Stock Directory` entry per symbol, then
+/// mixes adds (with and without MPID), executes, cancels, deletes and replaces. Executes,
+/// cancels, deletes and replaces only ever refer to orders that are still live.
+pub struct Generator {
+    config: GeneratorConfig,
+    rng: Rng,
+    live: Vec<LiveOrder>,
+    mids: Vec<u32>,
+    resting: Vec<RestingPrices>,
+    next_order_id: u64,
+    next_match_number: u64,
+    timestamp: u64,
+}
+
+impl Generator {
+    /// Creates a new `Generator` with the given configuration.
+    pub fn new(config: GeneratorConfig) -> Self {
+        let mids = vec![config.initial_price; config.symbols.len()];
+        let resting = vec![RestingPrices::default(); config.symbols.len()];
+        Self {
+            rng: Rng(config.seed),
+            config,
+            live: Vec::new(),
+            mids,
+            resting,
+            next_order_id: 1,
+            next_match_number: 1,
+            timestamp: 0,
+        }
+    }
+
+    /// Writes a complete session to `writer`.
+    ///
+    /// # Returns
+    /// Returns the number of messages written.
+    pub fn generate<W: Write>(&mut self, writer: &mut MessageWriter<W>) -> Result<u64> {
+        if self.config.symbols.is_empty() || self.config.symbols.len() > u16::MAX as usize {
+            return Err("The generator needs between 1 and 65535 symbols".into());
+        }
+        let start_ct = writer.message_count();
+
+        self.timestamp = MARKET_OPEN - 1_000_000;
+        self.system_event(writer, EventCode::StartOfMessages)?;
+        self.system_event(writer, EventCode::StartOfSystemHours)?;
+        for idx in 0..self.config.symbols.len() {
+            let stock = self.stock(idx);
+            writer.write(&self.message(b'R', idx as u16 + 1, Body::StockDirectory { stock }))?;
+        }
+        self.timestamp = MARKET_OPEN;
+        self.system_event(writer, EventCode::StartOfMarketHours)?;
+
+        for _ in 0..self.config.order_messages {
+            self.timestamp += 1 + self.rng.below(1_000);
+            self.order_message(writer)?;
+        }
+
+        self.timestamp += 1;
+        self.system_event(writer, EventCode::EndOfMarketHours)?;
+        self.system_event(writer, EventCode::EndOfSystemHours)?;
+        self.system_event(writer, EventCode::EndOfMessages)?;
+        writer.flush()?;
+
+        Ok(writer.message_count() - start_ct)
+    }
+
+    /// Writes one random order message, keeping the live orders consistent.
+    fn order_message<W: Write>(&mut self, writer: &mut MessageWriter<W>) -> Result<()> {
+        let c = &self.config;
+        let weights = [
+            c.add_weight,
+            c.execute_weight,
+            c.cancel_weight,
+            c.delete_weight,
+            c.replace_weight,
+        ];
+        let total: u32 = weights.iter().sum();
+        // Only an add makes sense while there is no live order to refer to.
+        let kind = if self.live.is_empty() || total == 0 {
+            0
+        } else {
+            let mut pick = self.rng.below(total as u64) as u32;
+            let mut kind = 0;
+            while pick >= weights[kind] {
+                pick -= weights[kind];
+                kind += 1;
+            }
+            kind
+        };
+
+        match kind {
+            0 => self.add_order(writer),
+            1 => self.execute_order(writer),
+            2 => self.cancel_order(writer),
+            3 => self.delete_order(writer),
+            _ => self.replace_order(writer),
+        }
+    }
+
+    fn add_order<W: Write>(&mut self, writer: &mut MessageWriter<W>) -> Result<()> {
+        let idx = self.rng.below(self.config.symbols.len() as u64) as usize;
+        let is_bid = self.rng.below(2) == 0;
+        let order = LiveOrder {
+            order_id: self.next_order_id,
+            stock_locate: idx as u16 + 1,
+            is_bid,
+            shares: self.random_shares(),
+            price: self.random_price(idx, is_bid),
+        };
+        self.next_order_id += 1;
+        self.resting[idx].insert(&order);
+        self.live.push(order);
+
+        let with_mpid = self.rng.below(100) < self.config.mpid_percent as u64;
+        let msg = self.message(
+            if with_mpid { b'F' } else { b'A' },
+            order.stock_locate,
+            Body::AddOrder {
+                order_id: order.order_id,
+                is_bid,
+                shares: order.shares,
+                stock: self.stock(idx),
+                price: order.price,
+            },
+        );
+        if with_mpid {
+            writer.write_with_mpid(&msg, *b"GSCO")
+        } else {
+            writer.write(&msg)
+        }
+    }
+
+    fn execute_order<W: Write>(&mut self, writer: &mut MessageWriter<W>) -> Result<()> {
+        let pos = self.rng.below(self.live.len() as u64) as usize;
+        let order = self.live[pos];
+        let shares = 1 + self.rng.below(order.shares as u64) as u32;
+        if shares == order.shares {
+            self.live.swap_remove(pos);
+            self.resting[order.stock_locate as usize - 1].remove(&order);
+        } else {
+            self.live[pos].shares -= shares;
+        }
+
+        let match_number = self.next_match_number;
+        self.next_match_number += 1;
+        // The book trades at the execution price, so the mid follows it.
+        self.mids[order.stock_locate as usize - 1] = order.price;
+        writer.write(&self.message(
+            b'E',
+            order.stock_locate,
+            Body::OrderExecuted {
+                order_id: order.order_id,
+                shares,
+                match_number,
+            },
+        ))
+    }
+
+    fn cancel_order<W: Write>(&mut self, writer: &mut MessageWriter<W>) -> Result<()> {
+        let pos = self.rng.below(self.live.len() as u64) as usize;
+        let order = self.live[pos];
+        if order.shares < 2 {
+            // A cancel always leaves some shares, so fall back to a delete.
+            return self.delete_order_at(writer, pos);
+        }
+        let shares = 1 + self.rng.below(order.shares as u64 - 1) as u32;
+        self.live[pos].shares -= shares;
+        writer.write(&self.message(
+            b'X',
+            order.stock_locate,
+            Body::OrderCancelled {
+                order_id: order.order_id,
+                shares,
+            },
+        ))
+    }
+
+    fn delete_order<W: Write>(&mut self, writer: &mut MessageWriter<W>) -> Result<()> {
+        let pos = self.rng.below(self.live.len() as u64) as usize;
+        self.delete_order_at(writer, pos)
+    }
+
+    fn delete_order_at<W: Write>(
+        &mut self,
+        writer: &mut MessageWriter<W>,
+        pos: usize,
+    ) -> Result<()> {
+        let order = self.live.swap_remove(pos);
+        self.resting[order.stock_locate as usize - 1].remove(&order);
+        writer.write(&self.message(
+            b'D',
+            order.stock_locate,
+            Body::DeleteOrder {
+                order_id: order.order_id,
+            },
+        ))
+    }
+
+    fn replace_order<W: Write>(&mut self, writer: &mut MessageWriter<W>) -> Result<()> {
+        let pos = self.rng.below(self.live.len() as u64) as usize;
+        let old = self.live[pos];
+        let new = LiveOrder {
+            order_id: self.next_order_id,
+            shares: self.random_shares(),
+            price: self.random_price(old.stock_locate as usize - 1, old.is_bid),
+            ..old
+        };
+        self.next_order_id += 1;
+        let resting = &mut self.resting[old.stock_locate as usize - 1];
+        resting.remove(&old);
+        resting.insert(&new);
+        self.live[pos] = new;
+        writer.write(&self.message(
+            b'U',
+            old.stock_locate,
+            Body::ReplaceOrder {
+                old_order_id: old.order_id,
+                new_order_id: new.order_id,
+                shares: new.shares,
+                price: new.price,
+            },
+        ))
+    }
+
+    fn system_event<W: Write>(
+        &mut self,
+        writer: &mut MessageWriter<W>,
+        event: EventCode,
+    ) -> Result<()> {
+        writer.write(&self.message(b'S', 0, Body::SystemEvent { event }))
+    }
+
+    /// Returns a round or odd lot between 1 and 1000 shares.
+    #[inline]
+    fn random_shares(&mut self) -> u32 {
+        if self.rng.below(4) == 0 {
+            1 + self.rng.below(99) as u32
+        } else {
+            100 * (1 + self.rng.below(10) as u32)
+        }
+    }
+
+    /// Returns a price on the passive side of the symbol's mid, within 20 ticks of it.
+    /// The mid takes a small random step every time. Bids stay below the live asks of the
+    /// symbol and asks above its live bids, so the generated book never crosses.
+    #[inline]
+    fn random_price(&mut self, idx: usize, is_bid: bool) -> u32 {
+        let step = self.rng.below(3) as u32;
+        let mid = self.mids[idx]
+            .saturating_add(step * TICK)
+            .saturating_sub(TICK)
+            .clamp(21 * TICK, u32::MAX - 21 * TICK);
+        self.mids[idx] = mid;
+        let offset = (1 + self.rng.below(20) as u32) * TICK;
+        let resting = &self.resting[idx];
+        if is_bid {
+            let best_ask = resting.asks.keys().next().copied().unwrap_or(u32::MAX);
+            (mid - offset).min(best_ask.saturating_sub(TICK))
+        } else {
+            let best_bid = resting.bids.keys().next_back().copied().unwrap_or(0);
+            (mid + offset).max(best_bid.saturating_add(TICK))
+        }
+    }
+
+    /// Returns the 8-byte, space-padded stock field of a symbol.
+    #[inline]
+    fn stock(&self, idx: usize) -> u64 {
+        let mut stock = [b' '; 8];
+        let symbol = self.config.symbols[idx].as_bytes();
+        let len = symbol.len().min(8);
+        stock[..len].copy_from_slice(&symbol[..len]);
+        u64::from_be_bytes(stock)
+    }
+
+    #[inline]
+    fn message(&self, tag: u8, stock_locate: u16, body: Body) -> Message {
+        Message {
+            tag,
+            stock_locate,
+            tracking_number: 0,
+            timestamp: self.timestamp,
+            body,
+        }
+    }
+}
+
+/// Generates a synthetic ITCH 5.0 file at the specified path.
+///
+/// # Returns
+/// Returns the number of messages written.
+pub fn generate_file<P: AsRef<Path>>(path: P, config: GeneratorConfig) -> Result<u64> {
+    let mut writer = MessageWriter::create(path)?;
+    Generator::new(config).generate(&mut writer)
+}
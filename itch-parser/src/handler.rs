@@ -0,0 +1,133 @@
+// handler.rs
+
+use super::{
+    body::{Body, EventCode},
+    errors::*,
+    message::Message,
+    message_stream::MessageStream,
+};
+use std::io::Read;
+
+/// A visitor for ITCH messages.
+///
+/// Every method has a default no-op implementation, so a handler only needs to implement
+/// the message types it cares about. The `msg` argument gives access to the header fields
+/// (`stock_locate`, `tracking_number`, `timestamp`) while the remaining arguments are the
+/// fields of the body.
+#[allow(unused_variables)]
+pub trait ItchHandler {
+    /// Handles the `Add Order` and `Add Order with MPID Attribution` messages.
+    #[inline]
+    fn on_add_order(
+        &mut self,
+        msg: &Message,
+        order_id: u64,
+        is_bid: bool,
+        shares: u32,
+        stock: u64,
+        price: u32,
+    ) {
+    }
+
+    /// Handles the `Order Delete` message.
+    #[inline]
+    fn on_delete_order(&mut self, msg: &Message, order_id: u64) {}
+
+    /// Handles the `Order Cancel` message.
+    #[inline]
+    fn on_cancel(&mut self, msg: &Message, order_id: u64, shares: u32) {}
+
+    /// Handles the `Order Executed` message.
+    #[inline]
+    fn on_execute(&mut self, msg: &Message, order_id: u64, shares: u32, match_number: u64) {}
+
+    /// Handles the `Order Executed with Price` message.
+    #[inline]
+    fn on_execute_with_price(
+        &mut self,
+        msg: &Message,
+        order_id: u64,
+        shares: u32,
+        match_number: u64,
+        printable: bool,
+        price: u32,
+    ) {
+    }
+
+    /// Handles the `Order Replace` message.
+    #[inline]
+    fn on_replace_order(
+        &mut self,
+        msg: &Message,
+        old_order_id: u64,
+        new_order_id: u64,
+        shares: u32,
+        price: u32,
+    ) {
+    }
+
+    /// Handles the `System Event` message.
+    #[inline]
+    fn on_system_event(&mut self, msg: &Message, event: EventCode) {}
+
+    /// Handles the `Stock Directory` message.
+    #[inline]
+    fn on_stock_directory(&mut self, msg: &Message, stock: u64) {}
+
+    /// Handles every message type that the parser skips.
+    #[inline]
+    fn on_pass(&mut self, msg: &Message) {}
+}
+
+/// Calls the `ItchHandler` method matching the body of the message.
+#[inline]
+pub fn dispatch_message<H: ItchHandler + ?Sized>(handler: &mut H, msg: &Message) {
+    match msg.body {
+        Body::AddOrder {
+            order_id,
+            is_bid,
+            shares,
+            stock,
+            price,
+        } => handler.on_add_order(msg, order_id, is_bid, shares, stock, price),
+        Body::DeleteOrder { order_id } => handler.on_delete_order(msg, order_id),
+        Body::OrderCancelled { order_id, shares } => handler.on_cancel(msg, order_id, shares),
+        Body::OrderExecuted {
+            order_id,
+            shares,
+            match_number,
+        } => handler.on_execute(msg, order_id, shares, match_number),
+        Body::OrderExecutedWithPrice {
+            order_id,
+            shares,
+            match_number,
+            printable,
+            price,
+        } => handler.on_execute_with_price(msg, order_id, shares, match_number, printable, price),
+        Body::ReplaceOrder {
+            old_order_id,
+            new_order_id,
+            shares,
+            price,
+        } => handler.on_replace_order(msg, old_order_id, new_order_id, shares, price),
+        Body::SystemEvent { event } => handler.on_system_event(msg, event),
+        Body::StockDirectory { stock } => handler.on_stock_directory(msg, stock),
+        Body::Pass(()) => handler.on_pass(msg),
+    }
+}
+
+impl<R: Read> MessageStream<R> {
+    /// Drives the stream to the end, dispatching every message to the handler.
+    ///
+    /// # Returns
+    /// Returns the number of messages dispatched or the first error encountered.
+    #[inline]
+    pub fn dispatch<H: ItchHandler + ?Sized>(&mut self, handler: &mut H) -> Result<u64> {
+        let mut messages = 0;
+        for msg in self {
+            dispatch_message(handler, &msg?);
+            messages += 1;
+        }
+        Ok(messages)
+    }
+}
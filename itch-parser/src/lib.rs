@@ -1,13 +1,35 @@
 // lib.rs
 
+mod batch;
 mod body;
 mod errors;
+#[cfg(any(feature = "csv", feature = "json", feature = "parquet"))]
+mod export;
+#[cfg(feature = "parquet")]
+mod export_parquet;
+mod generator;
+mod handler;
 mod message;
 mod message_stream;
+mod message_writer;
+mod multi_file_stream;
+mod subset;
+mod symbol_directory;
 mod utils;
 
+pub use batch::*;
 pub use body::*;
 pub use errors::*;
+#[cfg(any(feature = "csv", feature = "json", feature = "parquet"))]
+pub use export::*;
+#[cfg(feature = "parquet")]
+pub use export_parquet::*;
+pub use generator::*;
+pub use handler::*;
 pub use message::*;
 pub use message_stream::*;
+pub use message_writer::*;
+pub use multi_file_stream::*;
+pub use subset::*;
+pub use symbol_directory::*;
 pub use utils::*;
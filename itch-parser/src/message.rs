@@ -13,6 +13,7 @@ use nom::{
 
 /// The Message struct. Contains the parsed values of a message.
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Message {
     /// Message Type (tag)
     pub tag: u8,
@@ -183,8 +184,8 @@ fn parse_body(input: &[u8], tag: u8) -> IResult<&[u8], Body> {
         }
         // Handles the `Stock Directory` message.
         b'R' => {
-            let (input, _) = take(28usize)(input)?;
-            Ok((input, Body::Pass(())))
+            let (input, (stock, _)) = tuple((be_u64, take(20usize)))(input)?;
+            Ok((input, Body::StockDirectory { stock }))
         }
         // Handles the `System Event` message.
         b'S' => {
@@ -68,21 +68,29 @@ impl<R: Read> MessageStream<R> {
         }
         Ok(self.reader.read(&mut self.buffer[self.buf_end..])?)
     }
-}
 
-impl<R: Read> Iterator for MessageStream<R> {
-    type Item = Result<Message>;
+    /// Parses the next message along with its raw frame, including the 2-byte length prefix.
+    /// The frame borrows the internal buffer, so it is only valid until the next call.
+    #[inline]
+    pub fn next_with_frame(&mut self) -> Option<Result<(Message, &[u8])>> {
+        match self.advance()? {
+            Ok((msg, frame_start)) => Some(Ok((msg, &self.buffer[frame_start..self.buf_start]))),
+            Err(e) => Some(Err(e)),
+        }
+    }
 
+    /// Parses the next message and returns it with the buffer offset its frame starts at.
     #[inline]
-    fn next(&mut self) -> Option<Result<Message>> {
+    fn advance(&mut self) -> Option<Result<(Message, usize)>> {
         {
             let buf = &self.buffer[self.buf_start..self.buf_end];
             match parse_message(buf) {
                 Ok((rest, msg)) => {
+                    let frame_start = self.buf_start;
                     self.buf_start = self.buf_end - rest.len();
                     self.message_ct += 1;
                     self.in_error_state = false;
-                    return Some(Ok(msg));
+                    return Some(Ok((msg, frame_start)));
                 }
                 Err(nom::Err::Error(_e)) | Err(nom::Err::Failure(_e)) => {
                     return if self.in_error_state {
@@ -117,7 +125,7 @@ impl<R: Read> Iterator for MessageStream<R> {
             Ok(ct) => {
                 self.buf_end += ct;
                 self.bytes_read += ct;
-                self.next()
+                self.advance()
             }
             Err(e) => {
                 if self.in_error_state {
@@ -130,3 +138,12 @@ impl<R: Read> Iterator for MessageStream<R> {
         }
     }
 }
+
+impl<R: Read> Iterator for MessageStream<R> {
+    type Item = Result<Message>;
+
+    #[inline]
+    fn next(&mut self) -> Option<Result<Message>> {
+        self.advance().map(|res| res.map(|(msg, _)| msg))
+    }
+}
@@ -0,0 +1,183 @@
+// message_writer.rs
+
+use super::{body::Body, errors::*, message::Message};
+use std::{
+    fs::File,
+    io::{BufWriter, Write},
+    path::Path,
+};
+
+/// Placeholder values for the `Stock Directory` fields that the parser skips: market category,
+/// financial status, round lot size, round lots only, issue classification, issue sub-type,
+/// authenticity, short sale threshold, IPO flag, LULD tier, ETP flag, ETP leverage and inverse.
+const STOCK_DIRECTORY_TAIL: [u8; 20] = [
+    b'Q', b'N', 0, 0, 0, 100, b'N', b'C', b'Z', b' ', b'P', b'N', b' ', b'1', b'N', 0, 0, 0, 0,
+    b'N',
+];
+
+/// Writes messages in the ITCH 5.0 binary file format, each prefixed with its 2-byte length.
+pub struct MessageWriter<W: Write> {
+    writer: W,
+    buffer: Vec<u8>,
+    message_ct: u64, // Total messages written so far
+}
+
+impl MessageWriter<BufWriter<File>> {
+    /// Creates a new `MessageWriter` to a file at the specified path.
+    pub fn create<P: AsRef<Path>>(path: P) -> Result<MessageWriter<BufWriter<File>>> {
+        let file = File::create(path)?;
+        Ok(MessageWriter::from_writer(BufWriter::new(file)))
+    }
+}
+
+impl<W: Write> MessageWriter<W> {
+    /// Creates a new `MessageWriter` to any type that implements the `Write` trait.
+    #[inline]
+    pub fn from_writer(writer: W) -> MessageWriter<W> {
+        MessageWriter {
+            writer,
+            buffer: Vec::with_capacity(64),
+            message_ct: 0,
+        }
+    }
+
+    /// Returns the number of messages written so far.
+    #[inline]
+    pub fn message_count(&self) -> u64 {
+        self.message_ct
+    }
+
+    /// Writes a message.
+    /// `Add Order` bodies are written as tag `A`, or as tag `F` with a blank MPID
+    /// when the message tag is `F`. Skipped (`Pass`) bodies cannot be written.
+    #[inline]
+    pub fn write(&mut self, msg: &Message) -> Result<()> {
+        self.write_with_mpid(msg, *b"    ")
+    }
+
+    /// Writes a message, using `mpid` as the attribution of `Add Order with MPID
+    /// Attribution` messages.
+    pub fn write_with_mpid(&mut self, msg: &Message, mpid: [u8; 4]) -> Result<()> {
+        let buf = &mut self.buffer;
+        buf.clear();
+        // Reserve the length prefix, filled in once the body is encoded.
+        buf.extend_from_slice(&[0, 0]);
+
+        let tag = match msg.body {
+            Body::AddOrder { .. } if msg.tag == b'F' => b'F',
+            Body::AddOrder { .. } => b'A',
+            Body::DeleteOrder { .. } => b'D',
+            Body::OrderCancelled { .. } => b'X',
+            Body::OrderExecuted { .. } => b'E',
+            Body::OrderExecutedWithPrice { .. } => b'C',
+            Body::ReplaceOrder { .. } => b'U',
+            Body::SystemEvent { .. } => b'S',
+            Body::StockDirectory { .. } => b'R',
+            Body::Pass(()) => {
+                return Err(format!(
+                    "Cannot write the skipped body of a '{}' message",
+                    msg.tag as char
+                )
+                .into())
+            }
+        };
+        buf.push(tag);
+        buf.extend_from_slice(&msg.stock_locate.to_be_bytes());
+        buf.extend_from_slice(&msg.tracking_number.to_be_bytes());
+        buf.extend_from_slice(&msg.timestamp.to_be_bytes()[2..]);
+
+        match msg.body {
+            Body::AddOrder {
+                order_id,
+                is_bid,
+                shares,
+                stock,
+                price,
+            } => {
+                buf.extend_from_slice(&order_id.to_be_bytes());
+                buf.push(if is_bid { b'B' } else { b'S' });
+                buf.extend_from_slice(&shares.to_be_bytes());
+                buf.extend_from_slice(&stock.to_be_bytes());
+                buf.extend_from_slice(&price.to_be_bytes());
+                if tag == b'F' {
+                    buf.extend_from_slice(&mpid);
+                }
+            }
+            Body::DeleteOrder { order_id } => {
+                buf.extend_from_slice(&order_id.to_be_bytes());
+            }
+            Body::OrderCancelled { order_id, shares } => {
+                buf.extend_from_slice(&order_id.to_be_bytes());
+                buf.extend_from_slice(&shares.to_be_bytes());
+            }
+            Body::OrderExecuted {
+                order_id,
+                shares,
+                match_number,
+            } => {
+                buf.extend_from_slice(&order_id.to_be_bytes());
+                buf.extend_from_slice(&shares.to_be_bytes());
+                buf.extend_from_slice(&match_number.to_be_bytes());
+            }
+            Body::OrderExecutedWithPrice {
+                order_id,
+                shares,
+                match_number,
+                printable,
+                price,
+            } => {
+                buf.extend_from_slice(&order_id.to_be_bytes());
+                buf.extend_from_slice(&shares.to_be_bytes());
+                buf.extend_from_slice(&match_number.to_be_bytes());
+                buf.push(if printable { b'Y' } else { b'N' });
+                buf.extend_from_slice(&price.to_be_bytes());
+            }
+            Body::ReplaceOrder {
+                old_order_id,
+                new_order_id,
+                shares,
+                price,
+            } => {
+                buf.extend_from_slice(&old_order_id.to_be_bytes());
+                buf.extend_from_slice(&new_order_id.to_be_bytes());
+                buf.extend_from_slice(&shares.to_be_bytes());
+                buf.extend_from_slice(&price.to_be_bytes());
+            }
+            Body::SystemEvent { event } => {
+                buf.push(event.code());
+            }
+            Body::StockDirectory { stock } => {
+                buf.extend_from_slice(&stock.to_be_bytes());
+                buf.extend_from_slice(&STOCK_DIRECTORY_TAIL);
+            }
+            Body::Pass(()) => unreachable!(),
+        }
+
+        let len = (buf.len() - 2) as u16;
+        buf[..2].copy_from_slice(&len.to_be_bytes());
+        self.writer.write_all(buf)?;
+        self.message_ct += 1;
+        Ok(())
+    }
+
+    /// Writes an already framed message, including its 2-byte length prefix, as is.
+    /// Useful to copy the messages whose body the parser skips.
+    #[inline]
+    pub fn write_raw(&mut self, frame: &[u8]) -> Result<()> {
+        self.writer.write_all(frame)?;
+        self.message_ct += 1;
+        Ok(())
+    }
+
+    /// Flushes the underlying writer.
+    #[inline]
+    pub fn flush(&mut self) -> Result<()> {
+        Ok(self.writer.flush()?)
+    }
+
+    /// Flushes and returns the underlying writer.
+    pub fn into_inner(mut self) -> Result<W> {
+        self.flush()?;
+        Ok(self.writer)
+    }
+}
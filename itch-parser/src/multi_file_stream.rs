@@ -0,0 +1,153 @@
+// multi_file_stream.rs
+
+use super::{errors::*, message::Message, message_stream::MessageStream};
+use std::{
+    collections::VecDeque,
+    fmt::{self, Display},
+    fs::File,
+    path::{Path, PathBuf},
+};
+
+/// The trading date of an ITCH session.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct SessionDate {
+    pub year: u16,
+    pub month: u8,
+    pub day: u8,
+}
+
+impl SessionDate {
+    /// Creates a new `SessionDate`.
+    #[inline]
+    pub fn new(year: u16, month: u8, day: u8) -> Self {
+        Self { year, month, day }
+    }
+
+    /// Parses the session date from a NASDAQ file name, which starts with `MMDDYYYY`
+    /// (e.g. `12302019.NASDAQ_ITCH50`).
+    pub fn from_path<P: AsRef<Path>>(path: P) -> Option<Self> {
+        let name = path.as_ref().file_name()?.to_str()?;
+        let digits = name.get(..8)?;
+        if !digits.bytes().all(|b| b.is_ascii_digit()) {
+            return None;
+        }
+        let month: u8 = digits[0..2].parse().ok()?;
+        let day: u8 = digits[2..4].parse().ok()?;
+        let year: u16 = digits[4..8].parse().ok()?;
+        if !(1..=12).contains(&month) || !(1..=days_in_month(year, month)).contains(&day) {
+            return None;
+        }
+        Some(Self::new(year, month, day))
+    }
+}
+
+/// Returns the number of days of a month, from 1 to 12, of the Gregorian calendar.
+#[inline]
+fn days_in_month(year: u16, month: u8) -> u8 {
+    let is_leap = year.is_multiple_of(4) && (!year.is_multiple_of(100) || year.is_multiple_of(400));
+    match month {
+        4 | 6 | 9 | 11 => 30,
+        2 if is_leap => 29,
+        2 => 28,
+        _ => 31,
+    }
+}
+
+impl Display for SessionDate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:04}-{:02}-{:02}", self.year, self.month, self.day)
+    }
+}
+
+/// An item produced by a `MultiFileStream`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum SessionEvent {
+    /// The first event of a session, before any of its messages.
+    SessionStart(SessionDate),
+    /// A message tagged with the date of the session it belongs to.
+    Message(SessionDate, Message),
+    /// The last event of a session, after all of its messages.
+    /// Consumers should reset their per-day state when they see it.
+    SessionEnd(SessionDate),
+}
+
+/// Chains the ITCH files of several sessions in date order.
+///
+/// Each session is framed by a `SessionStart` and a `SessionEnd` event, so that consumers
+/// such as an orderbook can reset their state between days.
+pub struct MultiFileStream {
+    sessions: VecDeque<(SessionDate, PathBuf)>,
+    current: Option<(SessionDate, MessageStream<File>)>,
+}
+
+impl MultiFileStream {
+    /// Creates a new `MultiFileStream` from files whose names start with the session date.
+    /// The files are sorted by date, whatever the order they are given in.
+    pub fn from_files<P, I>(paths: I) -> Result<MultiFileStream>
+    where
+        P: AsRef<Path>,
+        I: IntoIterator<Item = P>,
+    {
+        let sessions = paths
+            .into_iter()
+            .map(|path| {
+                let path = path.as_ref();
+                SessionDate::from_path(path)
+                    .map(|date| (date, path.to_path_buf()))
+                    .ok_or_else(|| {
+                        format!("Cannot parse the session date of {}", path.display()).into()
+                    })
+            })
+            .collect::<Result<Vec<_>>>()?;
+        Ok(MultiFileStream::from_sessions(sessions))
+    }
+
+    /// Creates a new `MultiFileStream` from explicitly dated files.
+    /// The sessions are sorted by date; files of the same date keep their relative order.
+    pub fn from_sessions<P: Into<PathBuf>>(sessions: Vec<(SessionDate, P)>) -> MultiFileStream {
+        let mut sessions: Vec<(SessionDate, PathBuf)> = sessions
+            .into_iter()
+            .map(|(date, path)| (date, path.into()))
+            .collect();
+        sessions.sort_by_key(|(date, _)| *date);
+        MultiFileStream {
+            sessions: sessions.into(),
+            current: None,
+        }
+    }
+
+    /// Returns the dates of the sessions that have not been started yet.
+    pub fn remaining_sessions(&self) -> impl Iterator<Item = SessionDate> + '_ {
+        self.sessions.iter().map(|(date, _)| *date)
+    }
+}
+
+impl Iterator for MultiFileStream {
+    type Item = Result<SessionEvent>;
+
+    fn next(&mut self) -> Option<Result<SessionEvent>> {
+        match &mut self.current {
+            Some((date, stream)) => {
+                let date = *date;
+                match stream.next() {
+                    Some(Ok(msg)) => Some(Ok(SessionEvent::Message(date, msg))),
+                    Some(Err(e)) => Some(Err(e)),
+                    None => {
+                        self.current = None;
+                        Some(Ok(SessionEvent::SessionEnd(date)))
+                    }
+                }
+            }
+            None => {
+                let (date, path) = self.sessions.pop_front()?;
+                match MessageStream::from_file(path) {
+                    Ok(stream) => {
+                        self.current = Some((date, stream));
+                        Some(Ok(SessionEvent::SessionStart(date)))
+                    }
+                    Err(e) => Some(Err(e)),
+                }
+            }
+        }
+    }
+}
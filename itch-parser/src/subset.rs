@@ -0,0 +1,216 @@
+// subset.rs
+
+use super::{
+    body::Body, errors::*, message::Message, message_stream::MessageStream,
+    message_writer::MessageWriter, symbol_directory::SymbolDirectory,
+};
+use std::{
+    collections::BTreeMap,
+    io::{Read, Write},
+};
+
+/// Selects the symbols and the time window of a subset.
+/// An unset filter keeps every message.
+#[derive(Debug, Default, Clone)]
+pub struct SubsetFilter {
+    symbols: Option<Vec<String>>,
+    start: Option<u64>,
+    end: Option<u64>,
+}
+
+impl SubsetFilter {
+    /// Creates a filter that keeps every message.
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Only keeps the messages of the given symbols, along with the market-wide messages.
+    pub fn with_symbols<S: AsRef<str>>(mut self, symbols: &[S]) -> Self {
+        self.symbols = Some(symbols.iter().map(|s| s.as_ref().to_string()).collect());
+        self
+    }
+
+    /// Only keeps the messages whose timestamp, in nanoseconds since midnight, is in
+    /// `start..=end`. System events and `Stock Directory` entries are kept regardless.
+    pub fn with_window(mut self, start: Option<u64>, end: Option<u64>) -> Self {
+        self.start = start;
+        self.end = end;
+        self
+    }
+
+    #[inline]
+    fn selects(&self, symbol: Option<&str>) -> bool {
+        match (&self.symbols, symbol) {
+            (None, _) => true,
+            (Some(symbols), Some(symbol)) => symbols.iter().any(|s| s == symbol),
+            (Some(_), None) => false,
+        }
+    }
+}
+
+/// An order that is resting in the book of a selected symbol.
+#[derive(Debug, Clone, Copy)]
+struct LiveOrder {
+    stock_locate: u16,
+    is_bid: bool,
+    shares: u32,
+    stock: u64,
+    price: u32,
+}
+
+/// Tracks the live orders of the selected symbols, keyed by order id.
+#[derive(Debug, Default)]
+struct LiveOrders(BTreeMap<u64, LiveOrder>);
+
+impl LiveOrders {
+    /// Applies an order message of a selected symbol.
+    /// Returns false if the message refers to an order that is not live.
+    fn apply(&mut self, msg: &Message) -> bool {
+        match msg.body {
+            Body::AddOrder {
+                order_id,
+                is_bid,
+                shares,
+                stock,
+                price,
+            } => {
+                let order = LiveOrder {
+                    stock_locate: msg.stock_locate,
+                    is_bid,
+                    shares,
+                    stock,
+                    price,
+                };
+                self.0.insert(order_id, order);
+                true
+            }
+            Body::OrderExecuted {
+                order_id, shares, ..
+            }
+            | Body::OrderExecutedWithPrice {
+                order_id, shares, ..
+            }
+            | Body::OrderCancelled { order_id, shares } => match self.0.get_mut(&order_id) {
+                Some(order) => {
+                    order.shares = order.shares.saturating_sub(shares);
+                    if order.shares == 0 {
+                        self.0.remove(&order_id);
+                    }
+                    true
+                }
+                None => false,
+            },
+            Body::DeleteOrder { order_id } => self.0.remove(&order_id).is_some(),
+            Body::ReplaceOrder {
+                old_order_id,
+                new_order_id,
+                shares,
+                price,
+            } => match self.0.remove(&old_order_id) {
+                Some(order) => {
+                    self.0.insert(
+                        new_order_id,
+                        LiveOrder {
+                            shares,
+                            price,
+                            ..order
+                        },
+                    );
+                    true
+                }
+                None => false,
+            },
+            _ => true,
+        }
+    }
+}
+
+/// Returns true for the messages that add, modify or remove an order.
+#[inline]
+fn is_order_message(body: &Body) -> bool {
+    matches!(
+        body,
+        Body::AddOrder { .. }
+            | Body::OrderExecuted { .. }
+            | Body::OrderExecutedWithPrice { .. }
+            | Body::OrderCancelled { .. }
+            | Body::DeleteOrder { .. }
+            | Body::ReplaceOrder { .. }
+    )
+}
+
+/// Writes a self-contained ITCH subset of `stream` to `writer`.
+///
+/// The subset holds every system event, the `Stock Directory` entries of the selected symbols,
+/// and their messages within the time window, copied byte for byte. Market-wide messages
+/// (stock locate 0) within the window are kept as well. When the window starts after the
+/// beginning of the stream, the orders of the selected symbols that are still live at that time
+/// are re-created as `Add Order` messages, so that every later execute, cancel, delete and
+/// replace refers to a known order. They are written just before the first message at or after
+/// the window start, system events included, and stamped with its timestamp.
+///
+/// # Returns
+/// Returns the number of messages written.
+pub fn extract_subset<R: Read, W: Write>(
+    stream: &mut MessageStream<R>,
+    writer: &mut MessageWriter<W>,
+    filter: &SubsetFilter,
+) -> Result<u64> {
+    let start_ct = writer.message_count();
+    let mut directory = SymbolDirectory::new();
+    let mut live = LiveOrders::default();
+    // Whether the live orders before the window have been re-created yet.
+    let mut in_window = filter.start.is_none();
+
+    while let Some(res) = stream.next_with_frame() {
+        let (msg, frame) = res?;
+        directory.update(&msg);
+
+        // The window opens on the first message at or after its start, whatever its symbol.
+        if !in_window && msg.timestamp >= filter.start.unwrap_or(0) {
+            in_window = true;
+            for (&order_id, order) in live.0.iter() {
+                writer.write(&Message {
+                    tag: b'A',
+                    stock_locate: order.stock_locate,
+                    tracking_number: 0,
+                    timestamp: msg.timestamp,
+                    body: Body::AddOrder {
+                        order_id,
+                        is_bid: order.is_bid,
+                        shares: order.shares,
+                        stock: order.stock,
+                        price: order.price,
+                    },
+                })?;
+            }
+        }
+
+        if let Body::SystemEvent { .. } = msg.body {
+            writer.write_raw(frame)?;
+            continue;
+        }
+        let selected = msg.stock_locate == 0 || filter.selects(directory.symbol(msg.stock_locate));
+        if !selected {
+            continue;
+        }
+        if let Body::StockDirectory { .. } = msg.body {
+            writer.write_raw(frame)?;
+            continue;
+        }
+
+        let known = if is_order_message(&msg.body) {
+            live.apply(&msg)
+        } else {
+            true
+        };
+        let after_end = matches!(filter.end, Some(end) if msg.timestamp > end);
+        if in_window && !after_end && known {
+            writer.write_raw(frame)?;
+        }
+    }
+    writer.flush()?;
+
+    Ok(writer.message_count() - start_ct)
+}
@@ -0,0 +1,37 @@
+// symbol_directory.rs
+
+use super::{body::Body, message::Message, utils::stock_symbol};
+use std::collections::HashMap;
+
+/// Keeps track of the symbol of every stock locate seen in a stream.
+/// Symbols are learned from the `Stock Directory` and `Add Order` messages.
+#[derive(Debug, Default, Clone)]
+pub struct SymbolDirectory {
+    symbols: HashMap<u16, String>,
+}
+
+impl SymbolDirectory {
+    /// Creates a new, empty `SymbolDirectory`.
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Learns the symbol of the message's stock locate, if the message carries one.
+    #[inline]
+    pub fn update(&mut self, msg: &Message) {
+        let stock = match msg.body {
+            Body::StockDirectory { stock } | Body::AddOrder { stock, .. } => stock,
+            _ => return,
+        };
+        self.symbols
+            .entry(msg.stock_locate)
+            .or_insert_with(|| stock_symbol(stock));
+    }
+
+    /// Gets the symbol of a stock locate.
+    #[inline]
+    pub fn symbol(&self, stock_locate: u16) -> Option<&str> {
+        self.symbols.get(&stock_locate).map(String::as_str)
+    }
+}
@@ -33,3 +33,69 @@ pub fn be_u48(i: &[u8]) -> IResult<&[u8], u64> {
         Ok((&i[6..], res))
     }
 }
+
+/// Decodes the 8-byte, space-padded stock symbol of a message.
+#[inline]
+pub fn stock_symbol(stock: u64) -> String {
+    String::from_utf8_lossy(&stock.to_be_bytes())
+        .trim_end()
+        .to_string()
+}
+
+/// Formats a `Price(4)` field, which has 4 implied decimal places.
+#[inline]
+pub fn format_price(price: u32) -> String {
+    format!("{}.{:04}", price / 10_000, price % 10_000)
+}
+
+/// Returns the snake case name of a message type from its tag.
+#[inline]
+pub fn message_type_name(tag: u8) -> &'static str {
+    match tag {
+        b'A' => "add_order",
+        b'B' => "broken_trade",
+        b'C' => "order_executed_with_price",
+        b'D' => "order_delete",
+        b'E' => "order_executed",
+        b'F' => "add_order_mpid",
+        b'H' => "stock_trading_action",
+        b'I' => "net_order_imbalance_indicator",
+        b'J' => "luld_auction_collar",
+        b'K' => "quoting_period_update",
+        b'L' => "market_participant_position",
+        b'N' => "retail_price_improvement_indicator",
+        b'P' => "non_cross_trade",
+        b'Q' => "cross_trade",
+        b'R' => "stock_directory",
+        b'S' => "system_event",
+        b'U' => "order_replace",
+        b'V' => "mwcb_decline_level",
+        b'W' => "mwcb_status",
+        b'X' => "order_cancel",
+        b'Y' => "reg_sho_restriction",
+        _ => "unknown",
+    }
+}
+
+/// Parses a `HH:MM:SS[.fraction]` time of day into nanoseconds since midnight,
+/// the unit of the message timestamps.
+pub fn parse_time_of_day(time: &str) -> Option<u64> {
+    let mut parts = time.split(':');
+    let hours: u64 = parts.next()?.parse().ok()?;
+    let minutes: u64 = parts.next()?.parse().ok()?;
+    let seconds = parts.next()?;
+    if parts.next().is_some() {
+        return None;
+    }
+    let (seconds, fraction) = seconds.split_once('.').unwrap_or((seconds, ""));
+    let seconds: u64 = seconds.parse().ok()?;
+    if hours > 23 || minutes > 59 || seconds > 59 || fraction.len() > 9 {
+        return None;
+    }
+    let nanos: u64 = if fraction.is_empty() {
+        0
+    } else {
+        format!("{:0<9}", fraction).parse().ok()?
+    };
+    Some(((hours * 60 + minutes) * 60 + seconds) * 1_000_000_000 + nanos)
+}
@@ -0,0 +1,184 @@
+// allocation.rs
+
+use crate::{
+    error::SnapshotError,
+    events::EventSink,
+    l3_orderbook::L3OrderBook,
+    oid_map::OrderIdMap,
+    orderbook_manager::OrderBookManager,
+    quantity::Qty,
+    snapshot::{Reader, Snapshot},
+    utils::BookId,
+};
+
+/// How the matching engine splits an incoming quantity among the resting orders of a price level.
+///
+/// - `Fifo`: price-time priority, the oldest order is filled first.
+/// - `ProRata`: in proportion to the size of each order.
+/// - `TopOrderProRata`: the oldest order is filled first, the rest in proportion to size.
+pub trait AllocationPolicy: Clone + Default {
+    /// Splits `qty` among the orders of a level, given by their quantities in time priority.
+    /// Returns one allocation per order, each at most the quantity of its order. The
+    /// allocations add up to `qty`, or to the total quantity of the level if that is smaller.
+    fn allocate(&self, qty: Qty, orders: &[Qty]) -> Vec<Qty>;
+
+    /// Returns true if the policy only allocates to the oldest orders of a level, so the
+    /// engine can stop reading the level once they cover the incoming quantity.
+    #[inline]
+    fn is_time_priority(&self) -> bool {
+        false
+    }
+}
+
+/// Price-time priority: orders are filled one after the other, oldest first.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Fifo;
+
+impl AllocationPolicy for Fifo {
+    fn allocate(&self, qty: Qty, orders: &[Qty]) -> Vec<Qty> {
+        let mut left = qty.value();
+        orders
+            .iter()
+            .map(|order| {
+                let allocation = left.min(order.value());
+                left -= allocation;
+                Qty(allocation)
+            })
+            .collect()
+    }
+
+    #[inline]
+    fn is_time_priority(&self) -> bool {
+        true
+    }
+}
+
+/// Pro-rata allocation: every order receives its share of the incoming quantity in proportion
+/// to its size, rounded down. Shares below `min_qty` are dropped. What is left over after
+/// rounding is allocated in time priority, oldest order first.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct ProRata {
+    pub min_qty: Qty, // Smallest pro-rata share an order receives. Zero for no minimum.
+}
+
+impl AllocationPolicy for ProRata {
+    fn allocate(&self, qty: Qty, orders: &[Qty]) -> Vec<Qty> {
+        pro_rata(qty, orders, self.min_qty)
+    }
+}
+
+/// Top order priority followed by pro-rata: the oldest order of the level is filled first,
+/// then the rest of the incoming quantity is allocated pro-rata among the other orders, as
+/// with `ProRata`.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct TopOrderProRata {
+    pub min_qty: Qty, // Smallest pro-rata share an order receives. Zero for no minimum.
+}
+
+impl AllocationPolicy for TopOrderProRata {
+    fn allocate(&self, qty: Qty, orders: &[Qty]) -> Vec<Qty> {
+        let Some((top, rest)) = orders.split_first() else {
+            return Vec::new();
+        };
+        let top_allocation = Qty(qty.value().min(top.value()));
+        let mut allocations = Vec::with_capacity(orders.len());
+        allocations.push(top_allocation);
+        allocations.extend(pro_rata(
+            Qty(qty.value() - top_allocation.value()),
+            rest,
+            self.min_qty,
+        ));
+        allocations
+    }
+}
+
+/// Allocates `qty` in proportion to the order sizes, rounding down and dropping the shares
+/// below `min_qty`, then allocates the remainder in time priority.
+fn pro_rata(qty: Qty, orders: &[Qty], min_qty: Qty) -> Vec<Qty> {
+    let total: u64 = orders.iter().map(|order| order.value() as u64).sum();
+    if qty.value() as u64 >= total {
+        return orders.to_vec();
+    }
+
+    let mut left = qty.value();
+    let mut allocations: Vec<Qty> = orders
+        .iter()
+        .map(|order| {
+            let share = (qty.value() as u64 * order.value() as u64 / total) as u32;
+            if share < min_qty.value() {
+                Qty(0)
+            } else {
+                left -= share;
+                Qty(share)
+            }
+        })
+        .collect();
+    for (allocation, order) in allocations.iter_mut().zip(orders) {
+        if left == 0 {
+            break;
+        }
+        let extra = left.min(order.value() - allocation.value());
+        *allocation += Qty(extra);
+        left -= extra;
+    }
+    allocations
+}
+
+impl Snapshot for Fifo {
+    #[inline]
+    fn encode(&self, _out: &mut Vec<u8>) {}
+
+    #[inline]
+    fn decode(_reader: &mut Reader<'_>) -> Result<Self, SnapshotError> {
+        Ok(Fifo)
+    }
+}
+
+impl Snapshot for ProRata {
+    #[inline]
+    fn encode(&self, out: &mut Vec<u8>) {
+        self.min_qty.encode(out);
+    }
+
+    #[inline]
+    fn decode(reader: &mut Reader<'_>) -> Result<Self, SnapshotError> {
+        Ok(Self {
+            min_qty: reader.read()?,
+        })
+    }
+}
+
+impl Snapshot for TopOrderProRata {
+    #[inline]
+    fn encode(&self, out: &mut Vec<u8>) {
+        self.min_qty.encode(out);
+    }
+
+    #[inline]
+    fn decode(reader: &mut Reader<'_>) -> Result<Self, SnapshotError> {
+        Ok(Self {
+            min_qty: reader.read()?,
+        })
+    }
+}
+
+impl<M: OrderIdMap, A: AllocationPolicy, E: EventSink> OrderBookManager<M, L3OrderBook<A>, E> {
+    /// Sets the allocation policy used to match incoming orders against a book.
+    /// ## Arguments:
+    /// - `book_id`: The identifier for the book.
+    /// - `allocation`: The allocation policy, e.g. a `ProRata` with its minimum allocation.
+    ///
+    /// ## Example:
+    /// ```
+    /// let mut orderbook_manager: OrderBookManager<OidMap, L3OrderBook<ProRata>> =
+    ///     OrderBookManager::from_oid_map(OidMap::new());
+    ///
+    /// orderbook_manager.set_allocation_policy(BookId(0), ProRata { min_qty: Qty(2) });
+    /// ```
+    #[inline]
+    pub fn set_allocation_policy(&mut self, book_id: BookId, allocation: A) {
+        self.books[book_id.value() as usize]
+            .get_or_insert_with(L3OrderBook::default)
+            .allocation = allocation;
+    }
+}
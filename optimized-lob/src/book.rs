@@ -0,0 +1,126 @@
+// book.rs
+
+use crate::{
+    events::EventSink,
+    level::DepthLevel,
+    oid_map::OrderIdMap,
+    order::{Order, OrderId},
+    orderbook::OrderBook,
+    price::Price,
+    quantity::Qty,
+    side::Side,
+    utils::BookId,
+};
+
+/// A single instrument's book, as maintained by the `OrderBookManager`.
+///
+/// - `OrderBook`: aggregate quantity and order count per level, the fastest.
+/// - `L3OrderBook`: also keeps every order in a FIFO queue per level, for queue positions.
+pub trait Book: Clone + Default {
+    /// The state the book keeps for each of its orders besides their level and quantity.
+    /// Aggregate books keep none.
+    type Attributes: Copy + Default;
+
+    /// Adds an order to the book at the given price, and records its level in the order.
+    fn add_order(
+        &mut self,
+        order_id: OrderId,
+        order: &mut Order,
+        price: Price,
+        qty: Qty,
+        attributes: Self::Attributes,
+    );
+
+    /// Reduces the quantity of an existing order in the book.
+    fn reduce_order(&mut self, order_id: OrderId, order: &mut Order, qty: Qty);
+
+    /// Removes an order from the book.
+    ///
+    /// # Returns
+    /// Returns the attributes the order had in the book.
+    fn remove_order(&mut self, order_id: OrderId, order: &mut Order) -> Self::Attributes;
+
+    /// Displays the next part of the hidden reserve of an iceberg order whose displayed
+    /// quantity was used up, and sets the quantity of the order to it. The replenished order
+    /// loses its time priority.
+    ///
+    /// # Returns
+    /// Returns false, leaving the book unchanged, if the order has no hidden reserve left.
+    fn replenish_order(&mut self, order_id: OrderId, order: &mut Order) -> bool;
+
+    /// Returns the signed price of the level an order rests at.
+    fn order_price(&self, order: &Order) -> Price;
+
+    /// Returns the highest displayed bid level, if any.
+    fn best_bid(&self) -> Option<DepthLevel>;
+
+    /// Returns the lowest displayed ask level, if any.
+    fn best_ask(&self) -> Option<DepthLevel>;
+
+    /// Returns the level at the given unsigned price on one side, if a displayed order rests there.
+    fn level_at(&self, side: Side, price: u32) -> Option<DepthLevel>;
+
+    /// Reprices the pegged orders of the book after its best prices may have changed, and
+    /// reports the orders and levels that moved to `events`. Books without pegged orders do
+    /// nothing.
+    #[inline]
+    fn reprice_pegs<M: OrderIdMap, E: EventSink>(
+        &mut self,
+        _book_id: BookId,
+        _oid_map: &mut M,
+        _events: &mut E,
+    ) {
+    }
+}
+
+impl Book for OrderBook {
+    type Attributes = ();
+
+    #[inline]
+    fn add_order(
+        &mut self,
+        _order_id: OrderId,
+        order: &mut Order,
+        price: Price,
+        qty: Qty,
+        _attributes: (),
+    ) {
+        OrderBook::add_order(self, order, price, qty)
+    }
+
+    #[inline]
+    fn reduce_order(&mut self, _order_id: OrderId, order: &mut Order, qty: Qty) {
+        OrderBook::reduce_order(self, order, qty)
+    }
+
+    #[inline]
+    fn remove_order(&mut self, _order_id: OrderId, order: &mut Order) {
+        OrderBook::remove_order(self, order)
+    }
+
+    /// An aggregate book has no iceberg orders to replenish.
+    #[inline]
+    fn replenish_order(&mut self, _order_id: OrderId, _order: &mut Order) -> bool {
+        false
+    }
+
+    #[inline]
+    fn order_price(&self, order: &Order) -> Price {
+        self.level_pool.get(order.level_id()).unwrap().price()
+    }
+
+    #[inline]
+    fn best_bid(&self) -> Option<DepthLevel> {
+        OrderBook::best_bid(self)
+    }
+
+    #[inline]
+    fn best_ask(&self) -> Option<DepthLevel> {
+        OrderBook::best_ask(self)
+    }
+
+    #[inline]
+    fn level_at(&self, side: Side, price: u32) -> Option<DepthLevel> {
+        OrderBook::level_at(self, side, price)
+    }
+}
@@ -0,0 +1,129 @@
+// error.rs
+
+use crate::order::OrderId;
+use std::fmt;
+
+/// Errors returned by the order operations of the `OrderBookManager`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OrderError {
+    /// No order with this ID is resting in any book.
+    UnknownOrder(OrderId),
+    /// The new quantity of an order is zero. Use `remove_order` to remove an order.
+    ZeroQuantity,
+}
+
+impl fmt::Display for OrderError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            OrderError::UnknownOrder(order_id) => write!(f, "unknown order id {}", order_id.0),
+            OrderError::ZeroQuantity => write!(f, "order quantity is zero"),
+        }
+    }
+}
+
+impl std::error::Error for OrderError {}
+
+/// Errors returned when applying the incremental L2 feed to an `L2Book`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FeedError {
+    /// An update was missed. The book has to be rebuilt from a snapshot.
+    SequenceGap { expected: u64, received: u64 },
+}
+
+impl fmt::Display for FeedError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FeedError::SequenceGap { expected, received } => write!(
+                f,
+                "sequence gap: expected update {}, received {}",
+                expected, received
+            ),
+        }
+    }
+}
+
+impl std::error::Error for FeedError {}
+
+/// Errors returned when restoring an `OrderBookManager` from a snapshot.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SnapshotError {
+    /// The data does not start with the snapshot magic bytes.
+    BadMagic,
+    /// The snapshot was written in a format version this build cannot read.
+    UnsupportedVersion(u16),
+    /// The data ends in the middle of a value.
+    Truncated,
+    /// A value is out of range, e.g. a level that is not in the level pool.
+    Invalid(&'static str),
+}
+
+impl fmt::Display for SnapshotError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SnapshotError::BadMagic => write!(f, "not an order book snapshot"),
+            SnapshotError::UnsupportedVersion(version) => {
+                write!(f, "unsupported snapshot version {}", version)
+            }
+            SnapshotError::Truncated => write!(f, "truncated snapshot"),
+            SnapshotError::Invalid(what) => write!(f, "invalid snapshot: {}", what),
+        }
+    }
+}
+
+impl std::error::Error for SnapshotError {}
+
+/// Errors returned by the write-ahead journal and by the recovery of an `OrderBookManager`.
+#[derive(Debug)]
+pub enum JournalError {
+    /// Reading or writing the journal file failed.
+    Io(std::io::Error),
+    /// The file is not a journal, or was written in a format version this build cannot read.
+    BadHeader,
+    /// The snapshot to recover from is invalid.
+    Snapshot(SnapshotError),
+    /// The journal does not hold the records that follow the snapshot.
+    MissingRecords { expected: u64, found: u64 },
+    /// A record that is not the last one of the journal is damaged. `seq` is the sequence
+    /// number it should have, and `offset` its position in the file.
+    Corrupt { seq: u64, offset: u64 },
+}
+
+impl fmt::Display for JournalError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            JournalError::Io(err) => write!(f, "journal i/o error: {}", err),
+            JournalError::BadHeader => write!(f, "not a journal of this version"),
+            JournalError::Snapshot(err) => write!(f, "{}", err),
+            JournalError::MissingRecords { expected, found } => write!(
+                f,
+                "journal is missing records: expected record {}, found {}",
+                expected, found
+            ),
+            JournalError::Corrupt { seq, offset } => {
+                write!(f, "journal record {} at offset {} is corrupt", seq, offset)
+            }
+        }
+    }
+}
+
+impl std::error::Error for JournalError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            JournalError::Io(err) => Some(err),
+            JournalError::Snapshot(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<std::io::Error> for JournalError {
+    fn from(err: std::io::Error) -> Self {
+        JournalError::Io(err)
+    }
+}
+
+impl From<SnapshotError> for JournalError {
+    fn from(err: SnapshotError) -> Self {
+        JournalError::Snapshot(err)
+    }
+}
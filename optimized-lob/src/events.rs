@@ -0,0 +1,111 @@
+// events.rs
+
+use crate::{book::Book, order::OrderId, quantity::Qty, side::Side, utils::BookId};
+
+/// Receives the changes made to the books of an `OrderBookManager`, as they happen.
+/// Every callback does nothing by default, so a sink only implements the events it needs.
+///
+/// A change to an order is reported first, then the change of its level and of the best bid
+/// and ask, if they changed. An order that loses its time priority, such as a replenished
+/// iceberg or a repriced pegged order, is reported as removed and added again.
+pub trait EventSink {
+    /// False for a sink that ignores every event, so the manager skips the work of reporting.
+    const ENABLED: bool = true;
+
+    /// An order was added to a book, with its quantity but without the hidden reserve of an
+    /// iceberg order.
+    #[inline]
+    fn on_order_added(
+        &mut self,
+        _book_id: BookId,
+        _order_id: OrderId,
+        _side: Side,
+        _price: u32,
+        _qty: Qty,
+    ) {
+    }
+
+    /// The quantity of an order was reduced by `qty` to `remaining`. It keeps its priority.
+    #[inline]
+    fn on_order_reduced(
+        &mut self,
+        _book_id: BookId,
+        _order_id: OrderId,
+        _qty: Qty,
+        _remaining: Qty,
+    ) {
+    }
+
+    /// An order was removed from a book, cancelled or fully executed.
+    #[inline]
+    fn on_order_removed(&mut self, _book_id: BookId, _order_id: OrderId) {}
+
+    /// `qty` of a resting order of the given side traded at `price`. The reduction or removal
+    /// of the order follows.
+    #[inline]
+    fn on_trade(
+        &mut self,
+        _book_id: BookId,
+        _order_id: OrderId,
+        _side: Side,
+        _price: u32,
+        _qty: Qty,
+    ) {
+    }
+
+    /// The displayed quantity or the order count of a level changed. Both are zero once no
+    /// displayed order rests at the level.
+    #[inline]
+    fn on_level_changed(
+        &mut self,
+        _book_id: BookId,
+        _side: Side,
+        _price: u32,
+        _new_qty: Qty,
+        _order_count: u32,
+    ) {
+    }
+
+    /// The price or displayed quantity of the best bid or ask changed.
+    #[inline]
+    fn on_bbo_changed(
+        &mut self,
+        _book_id: BookId,
+        _bid: Option<(u32, Qty)>,
+        _ask: Option<(u32, Qty)>,
+    ) {
+    }
+}
+
+/// A sink that ignores every event. The default sink of the `OrderBookManager`, which then
+/// does no work to report events.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct NoopSink;
+
+impl EventSink for NoopSink {
+    const ENABLED: bool = false;
+}
+
+/// The displayed quantity and order count of a level, both zero if no displayed order rests
+/// there.
+#[inline]
+pub(crate) fn level_state<B: Book>(book: &B, side: Side, price: u32) -> (Qty, u32) {
+    book.level_at(side, price)
+        .map_or((Qty(0), 0), |level| (level.qty, level.order_count))
+}
+
+/// Reports the change of a level to `events`, if it differs from its state `before`.
+#[inline]
+pub(crate) fn report_level<B: Book, E: EventSink>(
+    book: &B,
+    book_id: BookId,
+    side: Side,
+    price: u32,
+    before: (Qty, u32),
+    events: &mut E,
+) {
+    let (qty, order_count) = level_state(book, side, price);
+    if (qty, order_count) != before {
+        events.on_level_changed(book_id, side, price, qty, order_count);
+    }
+}
@@ -0,0 +1,436 @@
+// journal.rs
+
+use crate::{
+    book::Book,
+    error::{JournalError, SnapshotError},
+    events::EventSink,
+    oid_map::OrderIdMap,
+    order::OrderId,
+    orderbook_manager::OrderBookManager,
+    quantity::Qty,
+    snapshot::{Reader, Snapshot},
+    utils::BookId,
+};
+use std::{
+    fs::{File, OpenOptions},
+    io::{Read, Seek, SeekFrom, Write},
+    path::Path,
+};
+
+/// Magic bytes at the start of a journal file.
+pub const JOURNAL_MAGIC: [u8; 4] = *b"LOBJ";
+/// Version of the journal format, bumped whenever the encoding of a record changes.
+pub const JOURNAL_VERSION: u16 = 1;
+
+const HEADER_LEN: usize = JOURNAL_MAGIC.len() + 2;
+/// Payload length, checksum and sequence number in front of every record.
+const RECORD_HEADER_LEN: usize = 4 + 4 + 8;
+
+/// A command that changes the books of an `OrderBookManager`, as written to the journal.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Command {
+    /// `add_order`.
+    Add {
+        order_id: OrderId,
+        book_id: BookId,
+        qty: Qty,
+        price: u32,
+        is_bid: bool,
+    },
+    /// `cancel_order`.
+    Cancel { order_id: OrderId, qty: Qty },
+    /// `execute_order`.
+    Execute { order_id: OrderId, qty: Qty },
+    /// `replace_order`.
+    Replace {
+        order_id: OrderId,
+        new_order_id: OrderId,
+        qty: Qty,
+        price: u32,
+    },
+    /// `remove_order`.
+    Delete { order_id: OrderId },
+}
+
+impl Snapshot for Command {
+    fn encode(&self, out: &mut Vec<u8>) {
+        match *self {
+            Command::Add {
+                order_id,
+                book_id,
+                qty,
+                price,
+                is_bid,
+            } => {
+                b'A'.encode(out);
+                order_id.encode(out);
+                book_id.encode(out);
+                qty.encode(out);
+                price.encode(out);
+                is_bid.encode(out);
+            }
+            Command::Cancel { order_id, qty } => {
+                b'X'.encode(out);
+                order_id.encode(out);
+                qty.encode(out);
+            }
+            Command::Execute { order_id, qty } => {
+                b'E'.encode(out);
+                order_id.encode(out);
+                qty.encode(out);
+            }
+            Command::Replace {
+                order_id,
+                new_order_id,
+                qty,
+                price,
+            } => {
+                b'U'.encode(out);
+                order_id.encode(out);
+                new_order_id.encode(out);
+                qty.encode(out);
+                price.encode(out);
+            }
+            Command::Delete { order_id } => {
+                b'D'.encode(out);
+                order_id.encode(out);
+            }
+        }
+    }
+
+    fn decode(reader: &mut Reader<'_>) -> Result<Self, SnapshotError> {
+        Ok(match reader.read::<u8>()? {
+            b'A' => Command::Add {
+                order_id: reader.read()?,
+                book_id: reader.read()?,
+                qty: reader.read()?,
+                price: reader.read()?,
+                is_bid: reader.read()?,
+            },
+            b'X' => Command::Cancel {
+                order_id: reader.read()?,
+                qty: reader.read()?,
+            },
+            b'E' => Command::Execute {
+                order_id: reader.read()?,
+                qty: reader.read()?,
+            },
+            b'U' => Command::Replace {
+                order_id: reader.read()?,
+                new_order_id: reader.read()?,
+                qty: reader.read()?,
+                price: reader.read()?,
+            },
+            b'D' => Command::Delete {
+                order_id: reader.read()?,
+            },
+            _ => return Err(SnapshotError::Invalid("command")),
+        })
+    }
+}
+
+/// A command read back from the journal, with its sequence number.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct JournalRecord {
+    pub seq: u64,         // Sequence number of the record, from 1 and without gaps.
+    pub command: Command, // The command.
+}
+
+/// An append-only, write-ahead journal of the commands applied to an `OrderBookManager`.
+///
+/// Every record holds its payload length, a CRC-32 checksum, its sequence number and the
+/// command. When a journal is opened, a last record that is incomplete, fails its checksum or
+/// is out of sequence is treated as torn by a crash, and truncated. A bad record anywhere else
+/// is reported as corruption, since the records after it were already acknowledged.
+pub struct Journal {
+    file: File,
+    last_seq: u64,  // Sequence number of the last record written.
+    end: u64,       // Length of the file up to the end of the last record.
+    truncated: u64, // Number of bytes of torn records truncated when the journal was opened.
+    sync: bool,     // Whether every append is flushed to the disk before it returns.
+    buf: Vec<u8>,   // Buffer the records are encoded into.
+}
+
+impl Journal {
+    /// Opens the journal at `path`, creating it if needed, and truncates its torn tail.
+    ///
+    /// # Returns
+    /// Returns the journal, positioned to append after its last record, and the records it
+    /// holds. Returns `JournalError::BadHeader` if the file is not a journal of this version,
+    /// and `JournalError::Corrupt` if a record other than the last one is damaged.
+    pub fn open(path: impl AsRef<Path>) -> Result<(Self, Vec<JournalRecord>), JournalError> {
+        let mut file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(path)?;
+        let mut bytes = Vec::new();
+        file.read_to_end(&mut bytes)?;
+
+        // A file shorter than its header was torn before its first record.
+        if bytes.len() < HEADER_LEN {
+            if !JOURNAL_MAGIC.starts_with(&bytes[..bytes.len().min(JOURNAL_MAGIC.len())]) {
+                return Err(JournalError::BadHeader);
+            }
+            let mut header = JOURNAL_MAGIC.to_vec();
+            JOURNAL_VERSION.encode(&mut header);
+            file.set_len(0)?;
+            file.seek(SeekFrom::Start(0))?;
+            file.write_all(&header)?;
+            file.sync_data()?;
+            bytes = header;
+        }
+        let mut reader = Reader::new(&bytes);
+        if reader.read_bytes(JOURNAL_MAGIC.len())? != JOURNAL_MAGIC
+            || reader.read::<u16>()? != JOURNAL_VERSION
+        {
+            return Err(JournalError::BadHeader);
+        }
+
+        let mut records = Vec::new();
+        let mut end = HEADER_LEN;
+        while let Some((record, len)) = read_record(&bytes[end..], records.len() as u64 + 1) {
+            records.push(record);
+            end += len;
+        }
+        let seq = records.len() as u64 + 1;
+        if end < bytes.len() && !is_torn(&bytes[end..], seq) {
+            return Err(JournalError::Corrupt {
+                seq,
+                offset: end as u64,
+            });
+        }
+        let truncated = (bytes.len() - end) as u64;
+        if truncated > 0 {
+            file.set_len(end as u64)?;
+            file.sync_data()?;
+        }
+        file.seek(SeekFrom::Start(end as u64))?;
+
+        let journal = Self {
+            file,
+            last_seq: records.len() as u64,
+            end: end as u64,
+            truncated,
+            sync: true,
+            buf: Vec::new(),
+        };
+        Ok((journal, records))
+    }
+
+    /// Sets whether every append is flushed to the disk before it returns. Enabled by
+    /// default. Without it, an append survives a crash of the process but not of the machine.
+    #[inline]
+    pub fn with_sync(self, sync: bool) -> Self {
+        Self { sync, ..self }
+    }
+
+    /// Returns the sequence number of the last record written, or 0 for an empty journal.
+    #[inline]
+    pub fn last_seq(&self) -> u64 {
+        self.last_seq
+    }
+
+    /// Returns the number of bytes of torn records truncated when the journal was opened.
+    #[inline]
+    pub fn truncated(&self) -> u64 {
+        self.truncated
+    }
+
+    /// Appends a command to the journal.
+    ///
+    /// # Returns
+    /// Returns the sequence number of the record.
+    pub fn append(&mut self, command: &Command) -> Result<u64, JournalError> {
+        let seq = self.last_seq + 1;
+        self.buf.clear();
+        self.buf.resize(RECORD_HEADER_LEN, 0);
+        command.encode(&mut self.buf);
+        let len = (self.buf.len() - RECORD_HEADER_LEN) as u32;
+        self.buf[0..4].copy_from_slice(&len.to_le_bytes());
+        self.buf[8..16].copy_from_slice(&seq.to_le_bytes());
+        let crc = crc32(&self.buf[8..]);
+        self.buf[4..8].copy_from_slice(&crc.to_le_bytes());
+
+        let written = self.file.write_all(&self.buf).and_then(|()| {
+            if self.sync {
+                self.file.sync_data()
+            } else {
+                Ok(())
+            }
+        });
+        if let Err(err) = written {
+            // Drop what was written of the record, so that the next one does not follow a
+            // torn record and get truncated with it.
+            let _ = self.file.set_len(self.end);
+            let _ = self.file.seek(SeekFrom::Start(self.end));
+            return Err(err.into());
+        }
+        self.end += self.buf.len() as u64;
+        self.last_seq = seq;
+        Ok(seq)
+    }
+}
+
+/// Reads the record at the front of `bytes`, which must have the sequence number `seq`.
+///
+/// # Returns
+/// Returns the record and its length in bytes, or None if it is torn.
+fn read_record(bytes: &[u8], seq: u64) -> Option<(JournalRecord, usize)> {
+    let mut reader = Reader::new(bytes);
+    let len = reader.read::<u32>().ok()? as usize;
+    let crc: u32 = reader.read().ok()?;
+    let record = bytes.get(8..RECORD_HEADER_LEN + len)?;
+    if crc32(record) != crc {
+        return None;
+    }
+
+    let mut reader = Reader::new(record);
+    if reader.read::<u64>().ok()? != seq {
+        return None;
+    }
+    let command = reader.read().ok()?;
+    if reader.remaining() > 0 {
+        return None;
+    }
+    Some((JournalRecord { seq, command }, RECORD_HEADER_LEN + len))
+}
+
+/// Returns true if the bad record at the front of `bytes`, which should have the sequence
+/// number `seq`, is the torn last record of the journal: it runs to the end of the file, and
+/// no valid record follows it.
+fn is_torn(bytes: &[u8], seq: u64) -> bool {
+    let runs_to_end = match bytes.get(..4) {
+        Some(len) => {
+            RECORD_HEADER_LEN + u32::from_le_bytes(len.try_into().unwrap()) as usize >= bytes.len()
+        }
+        None => true,
+    };
+    runs_to_end && (1..bytes.len()).all(|offset| read_record(&bytes[offset..], seq + 1).is_none())
+}
+
+/// Returns the CRC-32 (IEEE) checksum of `bytes`. Computed bit by bit, records being small.
+fn crc32(bytes: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for &byte in bytes {
+        crc ^= byte as u32;
+        for _ in 0..8 {
+            crc = (crc >> 1) ^ (0xEDB8_8320 & (crc & 1).wrapping_neg());
+        }
+    }
+    !crc
+}
+
+impl<M: OrderIdMap, B: Book, E: EventSink> OrderBookManager<M, B, E> {
+    /// Applies a command to the books, without journaling it.
+    /// ## Arguments:
+    /// - `command`: The command, e.g. a record read back from the journal.
+    ///
+    /// ## Example:
+    /// ```
+    /// let mut orderbook_manager = OrderBookManager::new();
+    ///
+    /// orderbook_manager.apply(&Command::Delete { order_id: OrderId(0) });
+    /// ```
+    pub fn apply(&mut self, command: &Command) {
+        match *command {
+            Command::Add {
+                order_id,
+                book_id,
+                qty,
+                price,
+                is_bid,
+            } => self.add_order(order_id, book_id, qty, price, is_bid),
+            Command::Cancel { order_id, qty } => self.cancel_order(order_id, qty),
+            Command::Execute { order_id, qty } => self.execute_order(order_id, qty),
+            Command::Replace {
+                order_id,
+                new_order_id,
+                qty,
+                price,
+            } => self.replace_order(order_id, new_order_id, qty, price),
+            Command::Delete { order_id } => self.remove_order(order_id),
+        }
+    }
+
+    /// Appends a command to the journal, then applies it to the books. The command is not
+    /// applied if it cannot be journaled.
+    /// ## Arguments:
+    /// - `journal`: The write-ahead journal of the manager.
+    /// - `command`: The command.
+    ///
+    /// # Returns
+    /// Returns the sequence number of the command in the journal.
+    ///
+    /// ## Example:
+    /// ```
+    /// let (mut journal, _) = Journal::open("books.journal")?;
+    /// let mut orderbook_manager = OrderBookManager::new();
+    ///
+    /// orderbook_manager.submit(
+    ///     &mut journal,
+    ///     &Command::Add {
+    ///         order_id: OrderId(0),
+    ///         book_id: BookId(0),
+    ///         qty: Qty(100),
+    ///         price: 600,
+    ///         is_bid: true,
+    ///     },
+    /// )?;
+    /// ```
+    pub fn submit(
+        &mut self,
+        journal: &mut Journal,
+        command: &Command,
+    ) -> Result<u64, JournalError> {
+        let seq = journal.append(command)?;
+        self.apply(command);
+        Ok(seq)
+    }
+}
+
+impl<M, B, E> OrderBookManager<M, B, E>
+where
+    M: OrderIdMap + Snapshot,
+    B: Book + Snapshot,
+    E: EventSink + Default,
+{
+    /// Recovers the books after a restart, from the last snapshot and the tail of the journal.
+    /// The torn tail of the journal is truncated first.
+    /// ## Arguments:
+    /// - `snapshot`: The last snapshot written by `save`, with the sequence number of the last
+    ///   journal record it includes. None to replay the whole journal.
+    /// - `path`: The path of the journal.
+    ///
+    /// # Returns
+    /// Returns the recovered manager and the journal to append the next commands to. Returns
+    /// `JournalError::MissingRecords` if the journal ends before the snapshot.
+    ///
+    /// ## Example:
+    /// ```
+    /// let snapshot = std::fs::read("books.snap")?;
+    /// let (mut orderbook_manager, mut journal): (OrderBookManager, Journal) =
+    ///     OrderBookManager::recover(Some((&snapshot, snapshot_seq)), "books.journal")?;
+    /// ```
+    pub fn recover(
+        snapshot: Option<(&[u8], u64)>,
+        path: impl AsRef<Path>,
+    ) -> Result<(Self, Journal), JournalError> {
+        let (journal, records) = Journal::open(path)?;
+        let (mut orderbook_manager, seq) = match snapshot {
+            Some((bytes, seq)) => (Self::restore(bytes)?, seq),
+            None => (Self::default(), 0),
+        };
+        if journal.last_seq() < seq {
+            return Err(JournalError::MissingRecords {
+                expected: seq,
+                found: journal.last_seq(),
+            });
+        }
+        for record in &records[seq as usize..] {
+            orderbook_manager.apply(&record.command);
+        }
+        Ok((orderbook_manager, journal))
+    }
+}
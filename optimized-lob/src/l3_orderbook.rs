@@ -0,0 +1,440 @@
+// l3_orderbook.rs
+
+use crate::{
+    allocation::{AllocationPolicy, Fifo},
+    book::Book,
+    error::SnapshotError,
+    events::{level_state, report_level, EventSink},
+    level::{DepthLevel, LevelId},
+    oid_map::OrderIdMap,
+    order::{Order, OrderId},
+    orderbook::OrderBook,
+    peg::PegBook,
+    price::Price,
+    quantity::Qty,
+    queue::{NodeId, NodePool, OrderAttributes, OrderQueue, QueueIter, QueueNode},
+    side::Side,
+    snapshot::{Reader, Snapshot},
+    stop_book::StopBook,
+    utils::BookId,
+};
+use std::collections::HashMap;
+
+/// Where an order stands in the FIFO queue of its price level.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct QueuePosition {
+    pub orders_ahead: u32, // Number of orders with time priority over this one.
+    pub qty_ahead: Qty,    // Total quantity of those orders.
+}
+
+/// An order-by-order (L3) book.
+/// On top of the aggregate levels of an `OrderBook`, every level keeps its orders in a FIFO
+/// queue, so that queue positions and time priority can be queried. The allocation policy
+/// decides how the matching engine splits an incoming order among the orders of a level.
+#[derive(Clone, Default)]
+pub struct L3OrderBook<A: AllocationPolicy = Fifo> {
+    pub aggregate: OrderBook, // Aggregate quantity and order count per level.
+    pub nodes: NodePool,      // The queued orders of every level.
+    pub stops: StopBook,      // Stop orders waiting for their trigger price.
+    pub pegs: PegBook,        // Pegged orders resting in the book.
+    pub allocation: A,        // How incoming orders are allocated among the orders of a level.
+    queues: Vec<OrderQueue>,  // The FIFO queue of each level, indexed by LevelId.
+    index: HashMap<OrderId, NodeId>, // The queue node of each resting order.
+    next_seq: u64,            // Arrival sequence number of the next order.
+    last_trade_price: Option<u32>, // Price of the last trade of the matching engine.
+    pegged_to: (Option<u32>, Option<u32>), // Best bid and ask the pegged orders were priced from.
+}
+
+impl L3OrderBook {
+    /// Creates a new, empty L3OrderBook with price-time priority.
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl<A: AllocationPolicy> L3OrderBook<A> {
+    /// Creates a new, empty L3OrderBook with the given allocation policy.
+    #[inline]
+    pub fn with_allocation(allocation: A) -> Self {
+        Self {
+            allocation,
+            ..Self::default()
+        }
+    }
+
+    /// Returns the index of the queue of a level, growing the queue table if the level is new.
+    #[inline]
+    fn queue_idx(&mut self, level_id: LevelId) -> usize {
+        let idx = level_id.value() as usize;
+        if idx >= self.queues.len() {
+            self.queues.resize(idx + 1, OrderQueue::default());
+        }
+        idx
+    }
+
+    /// Returns the FIFO queue of a level.
+    #[inline]
+    pub fn queue(&self, level_id: LevelId) -> OrderQueue {
+        self.queues
+            .get(level_id.value() as usize)
+            .copied()
+            .unwrap_or_default()
+    }
+
+    /// Iterates over the orders resting at a price, from the oldest to the newest.
+    /// The iterator is empty if no order rests at that price.
+    #[inline]
+    pub fn orders_at(&self, side: Side, price: u32) -> QueueIter<'_> {
+        let price = Price::from_u32(price, side.is_bid());
+        let queue = self
+            .aggregate
+            .levels(side)
+            .find(price)
+            .map(|px| self.queue(px.level_id()))
+            .unwrap_or_default();
+        self.nodes.iter(&queue)
+    }
+
+    /// Returns the oldest order at the best price of one side, which is the next to trade.
+    #[inline]
+    pub fn front(&self, side: Side) -> Option<&QueueNode> {
+        let px = self.aggregate.levels(side).best()?;
+        self.nodes.get(self.queue(px.level_id()).head()?)
+    }
+
+    /// Returns the queue node of an order resting in this book, with its attributes.
+    #[inline]
+    pub fn node(&self, order_id: OrderId) -> Option<&QueueNode> {
+        self.nodes.get(*self.index.get(&order_id)?)
+    }
+
+    /// Returns the queue position of an order resting in this book, or None if it does not
+    /// rest here.
+    pub fn queue_position(&self, order_id: OrderId) -> Option<QueuePosition> {
+        let mut position = QueuePosition {
+            orders_ahead: 0,
+            qty_ahead: Qty(0),
+        };
+        let mut cur = self.node(order_id)?.prev();
+        while let Some(node) = cur.and_then(|idx| self.nodes.get(idx)) {
+            position.orders_ahead += 1;
+            position.qty_ahead += node.qty();
+            cur = node.prev();
+        }
+        Some(position)
+    }
+
+    /// Returns the best bid and ask prices of the non-pegged orders, which pegged orders follow.
+    /// Levels holding only pegged orders or only hidden orders are skipped.
+    pub fn peg_reference(&self) -> (Option<u32>, Option<u32>) {
+        let best = |side: Side| {
+            self.aggregate
+                .levels(side)
+                .iter_best_first()
+                .find(|px| {
+                    let level = self.aggregate.level_pool.get(px.level_id()).unwrap();
+                    level.order_count() > self.pegs.count_at(px.price())
+                        && (!level.size().is_empty() || level.hidden().is_empty())
+                })
+                .map(|px| px.price().to_u32())
+        };
+        (best(Side::Bid), best(Side::Ask))
+    }
+
+    /// Adds an order at the back of the queue of its level. The quantity of a hidden order,
+    /// and the reserve of an iceberg order, go to the hidden quantity of the level.
+    #[inline]
+    fn enqueue(
+        &mut self,
+        order_id: OrderId,
+        order: &mut Order,
+        price: Price,
+        qty: Qty,
+        attributes: OrderAttributes,
+    ) {
+        if attributes.fully_hidden {
+            self.aggregate.add_hidden_order(order, price, qty);
+        } else {
+            self.aggregate.add_order(order, price, qty);
+        }
+        if !attributes.hidden.is_empty() {
+            self.aggregate.add_hidden(order, attributes.hidden);
+        }
+        let node_id = self.nodes.alloc(order_id, qty, self.next_seq, attributes);
+        self.next_seq += 1;
+        self.index.insert(order_id, node_id);
+
+        let idx = self.queue_idx(order.level_id());
+        self.nodes.push_back(&mut self.queues[idx], node_id);
+    }
+
+    /// Removes an order from the queue of its level.
+    /// The level is freed with its last order, so its id is never reused with orders queued.
+    ///
+    /// # Returns
+    /// Returns the attributes of the order, or the default ones if it is not queued.
+    #[inline]
+    fn dequeue(&mut self, order_id: OrderId, order: &mut Order) -> OrderAttributes {
+        let Some(node_id) = self.index.remove(&order_id) else {
+            return OrderAttributes::default();
+        };
+        let attributes = self.nodes.get(node_id).unwrap().attributes();
+        let idx = self.queue_idx(order.level_id());
+        self.nodes.unlink(&mut self.queues[idx], node_id);
+        self.nodes.free(node_id);
+        debug_assert_eq!(
+            self.queues[idx].is_empty(),
+            self.aggregate
+                .level_pool
+                .get(order.level_id())
+                .is_some_and(|level| level.order_count() == 1),
+            "the queue of a level must be empty exactly when its last order is removed"
+        );
+        if attributes.fully_hidden {
+            self.aggregate.remove_hidden_order(order);
+        } else {
+            self.aggregate.reduce_hidden(order, attributes.hidden);
+            self.aggregate.remove_order(order);
+        }
+        attributes
+    }
+
+    /// Returns the price of the last trade produced by the matching engine in this book.
+    #[inline]
+    pub fn last_trade_price(&self) -> Option<u32> {
+        self.last_trade_price
+    }
+
+    /// Records the price of a trade produced by the matching engine.
+    #[inline]
+    pub(crate) fn set_last_trade_price(&mut self, price: u32) {
+        self.last_trade_price = Some(price);
+    }
+
+    /// Returns the highest bid level, if any.
+    #[inline]
+    pub fn best_bid(&self) -> Option<DepthLevel> {
+        self.aggregate.best_bid()
+    }
+
+    /// Returns the lowest ask level, if any.
+    #[inline]
+    pub fn best_ask(&self) -> Option<DepthLevel> {
+        self.aggregate.best_ask()
+    }
+
+    /// Returns the difference between the best ask and the best bid prices.
+    #[inline]
+    pub fn spread(&self) -> Option<u32> {
+        self.aggregate.spread()
+    }
+
+    /// Returns the average of the best bid and ask prices, in Price(4) units.
+    #[inline]
+    pub fn mid(&self) -> Option<f64> {
+        self.aggregate.mid()
+    }
+
+    /// Returns up to `n` levels of one side, from the best to the worst price.
+    #[inline]
+    pub fn depth(&self, side: Side, n: usize) -> Vec<DepthLevel> {
+        self.aggregate.depth(side, n)
+    }
+
+    /// Returns the level at the given unsigned price on one side, if any order rests there.
+    #[inline]
+    pub fn level_at(&self, side: Side, price: u32) -> Option<DepthLevel> {
+        self.aggregate.level_at(side, price)
+    }
+}
+
+impl<A: AllocationPolicy> Book for L3OrderBook<A> {
+    type Attributes = OrderAttributes;
+
+    /// Adds the order to its level and queues it behind the orders already resting there.
+    #[inline]
+    fn add_order(
+        &mut self,
+        order_id: OrderId,
+        order: &mut Order,
+        price: Price,
+        qty: Qty,
+        attributes: OrderAttributes,
+    ) {
+        self.enqueue(order_id, order, price, qty, attributes);
+    }
+
+    /// Reduces the quantity of the order. It keeps its place in the queue.
+    #[inline]
+    fn reduce_order(&mut self, order_id: OrderId, order: &mut Order, qty: Qty) {
+        let Some(node) = self
+            .index
+            .get(&order_id)
+            .and_then(|node_id| self.nodes.get_mut(*node_id))
+        else {
+            return;
+        };
+        let mut remaining = node.qty();
+        remaining -= qty;
+        node.set_qty(remaining);
+        if node.attributes().fully_hidden {
+            self.aggregate.reduce_hidden(order, qty);
+        } else {
+            self.aggregate.reduce_order(order, qty);
+        }
+    }
+
+    #[inline]
+    fn remove_order(&mut self, order_id: OrderId, order: &mut Order) -> OrderAttributes {
+        if !self.pegs.is_empty() {
+            self.pegs.remove(order_id);
+        }
+        self.dequeue(order_id, order)
+    }
+
+    /// Requeues the order at the back of its level with the replenished quantity.
+    #[inline]
+    fn replenish_order(&mut self, order_id: OrderId, order: &mut Order) -> bool {
+        let Some(&node_id) = self.index.get(&order_id) else {
+            return false;
+        };
+        let node = self.nodes.get_mut(node_id).unwrap();
+        let mut attributes = node.attributes();
+        if attributes.hidden.is_empty() {
+            return false;
+        }
+        let refill = attributes.peak.min(attributes.hidden);
+        attributes.hidden -= refill;
+        node.set_attributes(attributes);
+        node.set_qty(refill);
+
+        self.aggregate.reduce_order(order, order.qty());
+        self.aggregate.replenish_order(order, refill);
+        let idx = self.queue_idx(order.level_id());
+        self.nodes
+            .requeue(&mut self.queues[idx], node_id, self.next_seq);
+        self.next_seq += 1;
+        order.set_qty(refill);
+        true
+    }
+
+    #[inline]
+    fn order_price(&self, order: &Order) -> Price {
+        self.aggregate.order_price(order)
+    }
+
+    #[inline]
+    fn best_bid(&self) -> Option<DepthLevel> {
+        self.aggregate.best_bid()
+    }
+
+    #[inline]
+    fn best_ask(&self) -> Option<DepthLevel> {
+        self.aggregate.best_ask()
+    }
+
+    #[inline]
+    fn level_at(&self, side: Side, price: u32) -> Option<DepthLevel> {
+        self.aggregate.level_at(side, price)
+    }
+
+    /// Moves every pegged order whose price changed with the best bid or ask to the back of
+    /// the queue at its new price, in arrival order.
+    fn reprice_pegs<M: OrderIdMap, E: EventSink>(
+        &mut self,
+        book_id: BookId,
+        oid_map: &mut M,
+        events: &mut E,
+    ) {
+        if self.pegs.is_empty() {
+            self.pegged_to = (None, None);
+            return;
+        }
+        let (bid, ask) = self.peg_reference();
+        if (bid, ask) == self.pegged_to {
+            return;
+        }
+        self.pegged_to = (bid, ask);
+
+        for idx in 0..self.pegs.len() {
+            let peg = self.pegs.at(idx);
+            let Some(price) = peg.spec.price(peg.side, bid, ask) else {
+                continue;
+            };
+            if price == peg.price {
+                continue;
+            }
+            let Some(order) = oid_map.get_mut(peg.order_id) else {
+                continue;
+            };
+            let before = E::ENABLED.then(|| level_state(self, peg.side, peg.price));
+            let attributes = self.dequeue(peg.order_id, order);
+            if let Some(before) = before {
+                events.on_order_removed(book_id, peg.order_id);
+                report_level(self, book_id, peg.side, peg.price, before, events);
+            }
+            let before = E::ENABLED.then(|| level_state(self, peg.side, price));
+            self.enqueue(
+                peg.order_id,
+                order,
+                Price::from_u32(price, peg.side.is_bid()),
+                order.qty(),
+                attributes,
+            );
+            self.pegs.set_price(idx, price);
+            if let Some(before) = before {
+                events.on_order_added(book_id, peg.order_id, peg.side, price, order.qty());
+                report_level(self, book_id, peg.side, price, before, events);
+            }
+        }
+    }
+}
+
+impl<A: AllocationPolicy + Snapshot> Snapshot for L3OrderBook<A> {
+    fn encode(&self, out: &mut Vec<u8>) {
+        self.aggregate.encode(out);
+        self.nodes.encode(out);
+        self.stops.encode(out);
+        self.pegs.encode(out);
+        self.allocation.encode(out);
+        self.queues.encode(out);
+        self.next_seq.encode(out);
+        self.last_trade_price.encode(out);
+        self.pegged_to.0.encode(out);
+        self.pegged_to.1.encode(out);
+    }
+
+    fn decode(reader: &mut Reader<'_>) -> Result<Self, SnapshotError> {
+        let mut book = Self {
+            aggregate: reader.read()?,
+            nodes: reader.read()?,
+            stops: reader.read()?,
+            pegs: reader.read()?,
+            allocation: reader.read()?,
+            queues: reader.read()?,
+            index: HashMap::new(),
+            next_seq: reader.read()?,
+            last_trade_price: reader.read()?,
+            pegged_to: (reader.read()?, reader.read()?),
+        };
+        let node_count = book.nodes.len();
+        if book
+            .queues
+            .iter()
+            .flat_map(|queue| [queue.head(), queue.tail()])
+            .flatten()
+            .any(|idx| idx.value() as usize >= node_count)
+        {
+            return Err(SnapshotError::Invalid("node id"));
+        }
+        // The index is not encoded, so that equal books encode to the same bytes whatever
+        // the iteration order of their maps.
+        book.index = book
+            .nodes
+            .iter_used()
+            .map(|(node_id, node)| (node.order_id(), node_id))
+            .collect();
+        Ok(book)
+    }
+}
@@ -1,6 +1,11 @@
 // level.rs
 
-use crate::{price::Price, quantity::Qty};
+use crate::{
+    error::SnapshotError,
+    price::Price,
+    quantity::Qty,
+    snapshot::{Reader, Snapshot},
+};
 use std::cmp::Ordering;
 use std::fmt::Debug;
 
@@ -16,11 +21,13 @@ impl LevelId {
 }
 
 /// Represents the Level for a price.
-/// It stores the price and total capacity of the level.
+/// It stores the price, displayed and hidden capacity and number of orders of the level.
 #[derive(Debug, Clone)]
 pub struct Level {
     price: Price,
     size: Qty,
+    hidden: Qty, // Reserve quantity of the iceberg orders of the level, not displayed.
+    order_count: u32,
 }
 
 impl Default for Level {
@@ -29,6 +36,8 @@ impl Default for Level {
         Self {
             price: Price(0),
             size: Qty(0),
+            hidden: Qty(0),
+            order_count: 0,
         }
     }
 }
@@ -36,7 +45,12 @@ impl Default for Level {
 impl Level {
     #[inline]
     pub fn new(price: Price, size: Qty) -> Self {
-        Self { price, size }
+        Self {
+            price,
+            size,
+            hidden: Qty(0),
+            order_count: 0,
+        }
     }
 
     #[inline]
@@ -49,6 +63,24 @@ impl Level {
         self.size
     }
 
+    /// Returns the hidden reserve quantity of the level.
+    #[inline]
+    pub fn hidden(&self) -> Qty {
+        self.hidden
+    }
+
+    /// Returns the displayed and hidden quantity of the level.
+    #[inline]
+    pub fn total(&self) -> Qty {
+        Qty(self.size.value() + self.hidden.value())
+    }
+
+    /// Returns the number of orders resting at the level.
+    #[inline]
+    pub fn order_count(&self) -> u32 {
+        self.order_count
+    }
+
     #[inline]
     pub fn set_price(&mut self, price: Price) {
         self.price = price
@@ -68,6 +100,44 @@ impl Level {
     pub fn decr(&mut self, size: Qty) {
         self.size -= size
     }
+
+    #[inline]
+    pub fn incr_hidden(&mut self, size: Qty) {
+        self.hidden += size
+    }
+
+    #[inline]
+    pub fn decr_hidden(&mut self, size: Qty) {
+        self.hidden -= size
+    }
+
+    /// Adds an order of the given size to the level.
+    #[inline]
+    pub fn add_order(&mut self, size: Qty) {
+        self.size += size;
+        self.order_count += 1;
+    }
+
+    /// Removes an order of the given remaining size from the level.
+    #[inline]
+    pub fn remove_order(&mut self, size: Qty) {
+        self.size -= size;
+        self.order_count -= 1;
+    }
+
+    /// Adds a hidden order of the given size to the level. Its size is not displayed.
+    #[inline]
+    pub fn add_hidden_order(&mut self, size: Qty) {
+        self.hidden += size;
+        self.order_count += 1;
+    }
+
+    /// Removes a hidden order of the given remaining size from the level.
+    #[inline]
+    pub fn remove_hidden_order(&mut self, size: Qty) {
+        self.hidden -= size;
+        self.order_count -= 1;
+    }
 }
 
 /// Represents a price level that will be used to locate the level in the orderbook.
@@ -125,6 +195,17 @@ impl PartialOrd for PriceLevel {
     }
 }
 
+/// The aggregate state of a price level, as returned by the depth queries of the orderbook.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DepthLevel {
+    pub price: u32,       // Unsigned price of the level. Represented as Price(4).
+    pub qty: Qty,         // Displayed quantity resting at the level.
+    pub total_qty: Qty,   // Displayed and hidden quantity resting at the level.
+    pub order_count: u32, // Number of orders resting at the level.
+}
+
+/// Price levels of one side of the book, sorted in ascending order of their signed price.
+/// The best level of either side is therefore the last one.
 #[derive(Default, Clone)]
 pub struct SortedLevels(Vec<PriceLevel>);
 
@@ -144,6 +225,25 @@ impl SortedLevels {
         self.0.is_empty()
     }
 
+    /// Returns the best price level, if any.
+    #[inline]
+    pub fn best(&self) -> Option<&PriceLevel> {
+        self.0.last()
+    }
+
+    /// Iterates over the price levels from the best to the worst.
+    #[inline]
+    pub fn iter_best_first(&self) -> impl Iterator<Item = &PriceLevel> {
+        self.0.iter().rev()
+    }
+
+    /// Finds the price level at the given signed price.
+    #[inline]
+    pub fn find(&self, price: Price) -> Option<&PriceLevel> {
+        let idx = self.0.binary_search_by(|px| px.price.cmp(&price)).ok()?;
+        Some(&self.0[idx])
+    }
+
     #[inline]
     pub fn get_mut(&mut self, idx: usize) -> &mut PriceLevel {
         &mut self.0[idx]
@@ -164,3 +264,49 @@ impl SortedLevels {
         }
     }
 }
+
+impl Snapshot for Level {
+    fn encode(&self, out: &mut Vec<u8>) {
+        self.price.encode(out);
+        self.size.encode(out);
+        self.hidden.encode(out);
+        self.order_count.encode(out);
+    }
+
+    fn decode(reader: &mut Reader<'_>) -> Result<Self, SnapshotError> {
+        Ok(Self {
+            price: reader.read()?,
+            size: reader.read()?,
+            hidden: reader.read()?,
+            order_count: reader.read()?,
+        })
+    }
+}
+
+impl Snapshot for PriceLevel {
+    fn encode(&self, out: &mut Vec<u8>) {
+        self.price.encode(out);
+        self.level_idx.encode(out);
+    }
+
+    fn decode(reader: &mut Reader<'_>) -> Result<Self, SnapshotError> {
+        Ok(Self {
+            price: reader.read()?,
+            level_idx: reader.read()?,
+        })
+    }
+}
+
+impl Snapshot for SortedLevels {
+    fn encode(&self, out: &mut Vec<u8>) {
+        self.0.encode(out);
+    }
+
+    fn decode(reader: &mut Reader<'_>) -> Result<Self, SnapshotError> {
+        let levels: Vec<PriceLevel> = reader.read()?;
+        if levels.windows(2).any(|pair| pair[0].price >= pair[1].price) {
+            return Err(SnapshotError::Invalid("unsorted levels"));
+        }
+        Ok(Self(levels))
+    }
+}
@@ -1,8 +1,22 @@
+pub mod allocation;
+pub mod book;
+pub mod error;
+pub mod events;
+pub mod journal;
+pub mod l3_orderbook;
 pub mod level;
+pub mod market_data;
+pub mod matching;
+pub mod oid_map;
 pub mod order;
 pub mod orderbook;
 pub mod orderbook_manager;
+pub mod peg;
 pub mod pool;
 pub mod price;
 pub mod quantity;
+pub mod queue;
+pub mod side;
+pub mod snapshot;
+pub mod stop_book;
 pub mod utils;
@@ -0,0 +1,265 @@
+// market_data.rs
+
+use crate::{
+    error::FeedError, events::EventSink, price::Price, quantity::Qty, side::Side, utils::BookId,
+};
+use std::collections::{BTreeMap, HashMap};
+
+/// What an incremental update does to a price level.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum L2Action {
+    /// Displayed orders now rest at a level that had none.
+    New,
+    /// The displayed quantity or the order count of a level changed.
+    Change,
+    /// No displayed order rests at the level anymore.
+    Delete,
+}
+
+/// The displayed quantity and order count of a price level.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct L2Level {
+    pub price: u32,       // Unsigned price of the level. Represented as Price(4).
+    pub qty: Qty,         // Displayed quantity resting at the level.
+    pub order_count: u32, // Number of orders resting at the level.
+}
+
+/// An incremental update of one level of a book.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct L2Update {
+    pub book_id: BookId,  // The book of the level.
+    pub seq: u64,         // Sequence number in the book, from 1 and without gaps.
+    pub action: L2Action, // What the update does to the level.
+    pub side: Side,       // The side of the level.
+    pub level: L2Level,   // The level after the update, with no quantity or order for a Delete.
+}
+
+/// A full image of the displayed levels of a book.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct L2Snapshot {
+    pub book_id: BookId,    // The book of the snapshot.
+    pub seq: u64,           // Sequence number of the last update included in the snapshot.
+    pub bids: Vec<L2Level>, // Bid levels, best price first.
+    pub asks: Vec<L2Level>, // Ask levels, best price first.
+}
+
+/// A message of the incremental L2 feed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum L2Message {
+    Update(L2Update),
+    Snapshot(L2Snapshot),
+}
+
+impl L2Message {
+    /// Returns the book the message is about.
+    #[inline]
+    pub fn book_id(&self) -> BookId {
+        match self {
+            L2Message::Update(update) => update.book_id,
+            L2Message::Snapshot(snapshot) => snapshot.book_id,
+        }
+    }
+}
+
+/// The displayed levels of one book, as rebuilt by a consumer of the L2 feed from a snapshot
+/// and the updates that follow it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct L2Book {
+    book_id: BookId,
+    seq: u64,                         // Sequence number of the last update applied.
+    levels: BTreeMap<Price, L2Level>, // Levels by signed price. The best of either side is last.
+}
+
+impl L2Book {
+    /// Creates an empty book that expects the first update of the feed.
+    #[inline]
+    pub fn new(book_id: BookId) -> Self {
+        Self {
+            book_id,
+            seq: 0,
+            levels: BTreeMap::new(),
+        }
+    }
+
+    /// Returns the sequence number of the last update applied.
+    #[inline]
+    pub fn seq(&self) -> u64 {
+        self.seq
+    }
+
+    /// Iterates over the bid levels, from the best to the worst price.
+    #[inline]
+    pub fn bids(&self) -> impl Iterator<Item = &L2Level> {
+        self.levels
+            .iter()
+            .rev()
+            .take_while(|(price, _)| price.is_bid())
+            .map(|(_, level)| level)
+    }
+
+    /// Iterates over the ask levels, from the best to the worst price.
+    #[inline]
+    pub fn asks(&self) -> impl Iterator<Item = &L2Level> {
+        self.levels
+            .iter()
+            .rev()
+            .skip_while(|(price, _)| price.is_bid())
+            .map(|(_, level)| level)
+    }
+
+    /// Returns the level at the given unsigned price on one side, if any.
+    #[inline]
+    pub fn level_at(&self, side: Side, price: u32) -> Option<&L2Level> {
+        self.levels.get(&Price::from_u32(price, side.is_bid()))
+    }
+
+    /// Returns a snapshot of the book.
+    pub fn snapshot(&self) -> L2Snapshot {
+        L2Snapshot {
+            book_id: self.book_id,
+            seq: self.seq,
+            bids: self.bids().copied().collect(),
+            asks: self.asks().copied().collect(),
+        }
+    }
+
+    /// Applies a message of the feed. A snapshot replaces the levels of the book. An update
+    /// that is already included in the book is skipped. Messages of other books are ignored.
+    ///
+    /// # Returns
+    /// Returns `FeedError::SequenceGap` if an update was missed. The book is unchanged, and
+    /// has to be rebuilt from the next snapshot.
+    pub fn apply(&mut self, message: &L2Message) -> Result<(), FeedError> {
+        if message.book_id() != self.book_id {
+            return Ok(());
+        }
+        match message {
+            L2Message::Snapshot(snapshot) => {
+                self.levels.clear();
+                for (side, levels) in [(Side::Bid, &snapshot.bids), (Side::Ask, &snapshot.asks)] {
+                    for level in levels {
+                        self.levels
+                            .insert(Price::from_u32(level.price, side.is_bid()), *level);
+                    }
+                }
+                self.seq = snapshot.seq;
+            }
+            L2Message::Update(update) => {
+                if update.seq <= self.seq {
+                    return Ok(());
+                }
+                if update.seq != self.seq + 1 {
+                    return Err(FeedError::SequenceGap {
+                        expected: self.seq + 1,
+                        received: update.seq,
+                    });
+                }
+                self.set_level(update.side, update.level);
+                self.seq = update.seq;
+            }
+        }
+        Ok(())
+    }
+
+    /// Sets the state of a level, removing it if no order rests there.
+    ///
+    /// # Returns
+    /// Returns what the change does to the level.
+    #[inline]
+    fn set_level(&mut self, side: Side, level: L2Level) -> L2Action {
+        let price = Price::from_u32(level.price, side.is_bid());
+        if level.order_count == 0 && level.qty.is_empty() {
+            self.levels.remove(&price);
+            L2Action::Delete
+        } else if self.levels.insert(price, level).is_some() {
+            L2Action::Change
+        } else {
+            L2Action::New
+        }
+    }
+}
+
+/// An event sink that turns the level changes of the books into an incremental L2 feed: an
+/// `L2Update` per level change, sequenced per book, and an `L2Snapshot` of a book after
+/// every `snapshot_interval` of its updates. A consumer rebuilds a book with an `L2Book`.
+///
+/// The publisher keeps the levels of every book it reports. It must be attached to the
+/// manager before the first order is added.
+#[derive(Debug, Default, Clone)]
+pub struct L2Publisher {
+    books: HashMap<BookId, L2Book>, // The levels of every book, as published.
+    messages: Vec<L2Message>,       // Messages not yet drained.
+    snapshot_interval: u64,         // Updates of a book between snapshots. Zero for none.
+}
+
+impl L2Publisher {
+    /// Creates a publisher without periodic snapshots.
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a publisher that adds a snapshot of a book after every `snapshot_interval`
+    /// updates of that book.
+    #[inline]
+    pub fn with_snapshot_interval(snapshot_interval: u64) -> Self {
+        Self {
+            snapshot_interval,
+            ..Self::default()
+        }
+    }
+
+    /// Returns a snapshot of a book, e.g. for a consumer joining the feed. The snapshot of a
+    /// book without any update is empty.
+    #[inline]
+    pub fn snapshot(&self, book_id: BookId) -> L2Snapshot {
+        self.books
+            .get(&book_id)
+            .map_or_else(|| L2Book::new(book_id).snapshot(), L2Book::snapshot)
+    }
+
+    /// Returns the messages not yet drained, in publication order.
+    #[inline]
+    pub fn messages(&self) -> &[L2Message] {
+        &self.messages
+    }
+
+    /// Removes and returns the messages not yet drained, in publication order.
+    #[inline]
+    pub fn drain(&mut self) -> std::vec::Drain<'_, L2Message> {
+        self.messages.drain(..)
+    }
+}
+
+impl EventSink for L2Publisher {
+    fn on_level_changed(
+        &mut self,
+        book_id: BookId,
+        side: Side,
+        price: u32,
+        new_qty: Qty,
+        order_count: u32,
+    ) {
+        let book = self
+            .books
+            .entry(book_id)
+            .or_insert_with(|| L2Book::new(book_id));
+        let level = L2Level {
+            price,
+            qty: new_qty,
+            order_count,
+        };
+        let action = book.set_level(side, level);
+        book.seq += 1;
+        self.messages.push(L2Message::Update(L2Update {
+            book_id,
+            seq: book.seq,
+            action,
+            side,
+            level,
+        }));
+        if self.snapshot_interval > 0 && book.seq.is_multiple_of(self.snapshot_interval) {
+            self.messages.push(L2Message::Snapshot(book.snapshot()));
+        }
+    }
+}
@@ -0,0 +1,855 @@
+// matching.rs
+
+use crate::{
+    allocation::AllocationPolicy,
+    error::SnapshotError,
+    events::EventSink,
+    l3_orderbook::L3OrderBook,
+    level::LevelId,
+    oid_map::OrderIdMap,
+    order::{Order, OrderId, OwnerId},
+    orderbook_manager::OrderBookManager,
+    quantity::Qty,
+    queue::OrderAttributes,
+    side::Side,
+    snapshot::{Reader, Snapshot},
+    utils::BookId,
+};
+
+/// How an incoming order treats the quantity it cannot trade immediately.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OrderType {
+    /// Trades up to its limit price, and the remainder rests in the book.
+    Limit,
+    /// Trades at any price until filled or the opposite side is empty; the remainder is cancelled.
+    Market,
+    /// Trades up to its limit price, and the remainder is cancelled.
+    ImmediateOrCancel,
+    /// Trades its whole quantity up to its limit price, or nothing at all.
+    FillOrKill,
+    /// Waits until a trade prints at or through `trigger`, then becomes a market order.
+    Stop { trigger: u32 },
+    /// Waits until a trade prints at or through `trigger`, then becomes a limit order.
+    StopLimit { trigger: u32 },
+}
+
+/// What a post-only order does when it would take liquidity on arrival.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PostOnly {
+    /// The order is rejected.
+    Reject,
+    /// The price of a limit order is moved one Price(4) unit away from the opposite best price.
+    Reprice,
+}
+
+/// What happens when an incoming order would trade against a resting order of the same owner.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SelfTradePrevention {
+    /// The rest of the incoming order is cancelled.
+    #[default]
+    CancelNewest,
+    /// The resting order is cancelled, and the incoming order keeps matching.
+    CancelOldest,
+    /// Both the resting order and the rest of the incoming order are cancelled.
+    CancelBoth,
+    /// Both orders are reduced by the smaller of their quantities, without a trade, which
+    /// cancels the smaller one. The incoming order keeps matching if anything is left.
+    DecrementAndCancel,
+}
+
+/// An order submitted to the matching engine.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OrderRequest {
+    pub order_id: OrderId,           // Unique reference number of the order.
+    pub book_id: BookId,             // The book the order is submitted to.
+    pub side: Side,                  // Buy (bid) or sell (ask).
+    pub qty: Qty,                    // The quantity of the order.
+    pub price: u32,                  // The limit price. Ignored by market orders.
+    pub order_type: OrderType,       // How the order treats its untraded quantity.
+    pub post_only: Option<PostOnly>, // Set if the order must not take liquidity.
+    pub hidden: bool,                // True if the remainder rests as a hidden order.
+    pub owner: OwnerId,              // The firm or account of the order.
+    pub stp: SelfTradePrevention,    // What happens when it would trade with its own owner.
+}
+
+impl OrderRequest {
+    /// Creates a limit order.
+    #[inline]
+    pub fn limit(order_id: OrderId, book_id: BookId, side: Side, qty: Qty, price: u32) -> Self {
+        Self {
+            order_id,
+            book_id,
+            side,
+            qty,
+            price,
+            order_type: OrderType::Limit,
+            post_only: None,
+            hidden: false,
+            owner: OwnerId(0),
+            stp: SelfTradePrevention::CancelNewest,
+        }
+    }
+
+    /// Creates a market order.
+    #[inline]
+    pub fn market(order_id: OrderId, book_id: BookId, side: Side, qty: Qty) -> Self {
+        Self {
+            order_type: OrderType::Market,
+            ..Self::limit(order_id, book_id, side, qty, 0)
+        }
+    }
+
+    /// Creates an immediate-or-cancel order.
+    #[inline]
+    pub fn immediate_or_cancel(
+        order_id: OrderId,
+        book_id: BookId,
+        side: Side,
+        qty: Qty,
+        price: u32,
+    ) -> Self {
+        Self {
+            order_type: OrderType::ImmediateOrCancel,
+            ..Self::limit(order_id, book_id, side, qty, price)
+        }
+    }
+
+    /// Creates a fill-or-kill order.
+    #[inline]
+    pub fn fill_or_kill(
+        order_id: OrderId,
+        book_id: BookId,
+        side: Side,
+        qty: Qty,
+        price: u32,
+    ) -> Self {
+        Self {
+            order_type: OrderType::FillOrKill,
+            ..Self::limit(order_id, book_id, side, qty, price)
+        }
+    }
+
+    /// Creates a stop order, which becomes a market order once a trade prints at or above
+    /// `trigger` for a buy, or at or below `trigger` for a sell.
+    #[inline]
+    pub fn stop(order_id: OrderId, book_id: BookId, side: Side, qty: Qty, trigger: u32) -> Self {
+        Self {
+            order_type: OrderType::Stop { trigger },
+            ..Self::limit(order_id, book_id, side, qty, 0)
+        }
+    }
+
+    /// Creates a stop-limit order, which becomes a limit order at `price` once a trade prints
+    /// at or above `trigger` for a buy, or at or below `trigger` for a sell.
+    #[inline]
+    pub fn stop_limit(
+        order_id: OrderId,
+        book_id: BookId,
+        side: Side,
+        qty: Qty,
+        trigger: u32,
+        price: u32,
+    ) -> Self {
+        Self {
+            order_type: OrderType::StopLimit { trigger },
+            ..Self::limit(order_id, book_id, side, qty, price)
+        }
+    }
+
+    /// Makes the order post-only: it never trades on arrival.
+    #[inline]
+    pub fn with_post_only(self, post_only: PostOnly) -> Self {
+        Self {
+            post_only: Some(post_only),
+            ..self
+        }
+    }
+
+    /// Sets whether the remainder of the order rests as a hidden order, whose quantity is not
+    /// displayed in the level size.
+    #[inline]
+    pub fn with_hidden(self, hidden: bool) -> Self {
+        Self { hidden, ..self }
+    }
+
+    /// Sets the owner of the order, and what happens when it would trade with another order
+    /// of the same owner.
+    #[inline]
+    pub fn with_owner(self, owner: OwnerId, stp: SelfTradePrevention) -> Self {
+        Self { owner, stp, ..self }
+    }
+
+    /// Returns the limit price of the order, or None if it trades at any price.
+    #[inline]
+    pub fn limit_price(&self) -> Option<u32> {
+        match self.order_type {
+            OrderType::Market | OrderType::Stop { .. } => None,
+            _ => Some(self.price),
+        }
+    }
+
+    /// Returns the trigger price of a stop or stop-limit order.
+    #[inline]
+    pub fn trigger_price(&self) -> Option<u32> {
+        match self.order_type {
+            OrderType::Stop { trigger } | OrderType::StopLimit { trigger } => Some(trigger),
+            _ => None,
+        }
+    }
+
+    /// Returns true if a trade at `price` triggers this stop order.
+    #[inline]
+    pub fn is_triggered_by(&self, price: u32) -> bool {
+        match (self.trigger_price(), self.side) {
+            (None, _) => false,
+            (Some(trigger), Side::Bid) => price >= trigger,
+            (Some(trigger), Side::Ask) => price <= trigger,
+        }
+    }
+
+    /// Returns the order a triggered stop order becomes.
+    #[inline]
+    fn released(&self) -> Self {
+        let order_type = match self.order_type {
+            OrderType::Stop { .. } => OrderType::Market,
+            OrderType::StopLimit { .. } => OrderType::Limit,
+            order_type => order_type,
+        };
+        Self {
+            order_type,
+            ..*self
+        }
+    }
+}
+
+impl Snapshot for OrderType {
+    fn encode(&self, out: &mut Vec<u8>) {
+        match *self {
+            OrderType::Limit => 0u8.encode(out),
+            OrderType::Market => 1u8.encode(out),
+            OrderType::ImmediateOrCancel => 2u8.encode(out),
+            OrderType::FillOrKill => 3u8.encode(out),
+            OrderType::Stop { trigger } => {
+                4u8.encode(out);
+                trigger.encode(out);
+            }
+            OrderType::StopLimit { trigger } => {
+                5u8.encode(out);
+                trigger.encode(out);
+            }
+        }
+    }
+
+    fn decode(reader: &mut Reader<'_>) -> Result<Self, SnapshotError> {
+        Ok(match reader.read::<u8>()? {
+            0 => OrderType::Limit,
+            1 => OrderType::Market,
+            2 => OrderType::ImmediateOrCancel,
+            3 => OrderType::FillOrKill,
+            4 => OrderType::Stop {
+                trigger: reader.read()?,
+            },
+            5 => OrderType::StopLimit {
+                trigger: reader.read()?,
+            },
+            _ => return Err(SnapshotError::Invalid("order type")),
+        })
+    }
+}
+
+impl Snapshot for PostOnly {
+    fn encode(&self, out: &mut Vec<u8>) {
+        (*self == PostOnly::Reprice).encode(out);
+    }
+
+    fn decode(reader: &mut Reader<'_>) -> Result<Self, SnapshotError> {
+        Ok(if reader.read()? {
+            PostOnly::Reprice
+        } else {
+            PostOnly::Reject
+        })
+    }
+}
+
+impl Snapshot for SelfTradePrevention {
+    fn encode(&self, out: &mut Vec<u8>) {
+        let tag: u8 = match self {
+            SelfTradePrevention::CancelNewest => 0,
+            SelfTradePrevention::CancelOldest => 1,
+            SelfTradePrevention::CancelBoth => 2,
+            SelfTradePrevention::DecrementAndCancel => 3,
+        };
+        tag.encode(out);
+    }
+
+    fn decode(reader: &mut Reader<'_>) -> Result<Self, SnapshotError> {
+        Ok(match reader.read::<u8>()? {
+            0 => SelfTradePrevention::CancelNewest,
+            1 => SelfTradePrevention::CancelOldest,
+            2 => SelfTradePrevention::CancelBoth,
+            3 => SelfTradePrevention::DecrementAndCancel,
+            _ => return Err(SnapshotError::Invalid("self-trade prevention")),
+        })
+    }
+}
+
+impl Snapshot for OrderRequest {
+    fn encode(&self, out: &mut Vec<u8>) {
+        self.order_id.encode(out);
+        self.book_id.encode(out);
+        self.side.encode(out);
+        self.qty.encode(out);
+        self.price.encode(out);
+        self.order_type.encode(out);
+        self.post_only.encode(out);
+        self.hidden.encode(out);
+        self.owner.encode(out);
+        self.stp.encode(out);
+    }
+
+    fn decode(reader: &mut Reader<'_>) -> Result<Self, SnapshotError> {
+        Ok(Self {
+            order_id: reader.read()?,
+            book_id: reader.read()?,
+            side: reader.read()?,
+            qty: reader.read()?,
+            price: reader.read()?,
+            order_type: reader.read()?,
+            post_only: reader.read()?,
+            hidden: reader.read()?,
+            owner: reader.read()?,
+            stp: reader.read()?,
+        })
+    }
+}
+
+/// Why an order was rejected without any effect on the book.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RejectReason {
+    /// The order has no quantity.
+    ZeroQuantity,
+    /// An order with the same ID is already resting.
+    DuplicateOrderId,
+    /// A fill-or-kill order could not be filled completely.
+    InsufficientLiquidity,
+    /// A post-only order would have traded on arrival.
+    WouldTakeLiquidity,
+}
+
+/// The final state of a submitted order.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExecutionStatus {
+    /// The whole quantity traded.
+    Filled,
+    /// Part of the quantity traded; the rest is resting or cancelled.
+    PartiallyFilled,
+    /// Nothing traded and the whole quantity is resting.
+    Resting,
+    /// Nothing traded and nothing is resting.
+    Cancelled,
+    /// A stop order is waiting for its trigger price.
+    Pending,
+    /// The order was refused before matching.
+    Rejected(RejectReason),
+}
+
+/// A trade between an incoming (taker) order and a resting (maker) order.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Fill {
+    pub book_id: BookId,         // The book the trade happened in.
+    pub taker_order_id: OrderId, // The incoming order.
+    pub maker_order_id: OrderId, // The resting order it traded against.
+    pub aggressor: Side,         // The side of the incoming order.
+    pub price: u32,              // The trade price, always the price of the resting order.
+    pub qty: Qty,                // The traded quantity.
+}
+
+/// A resting order reduced or cancelled by self-trade prevention instead of trading.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SelfTradeCancel {
+    pub order_id: OrderId, // The resting order.
+    pub qty: Qty,          // The quantity removed from it.
+}
+
+/// The outcome of submitting an order to a matching book.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Execution {
+    pub order_id: OrderId,                        // The submitted order.
+    pub status: ExecutionStatus,                  // The final state of the order.
+    pub fills: Vec<Fill>,                         // The trades it generated, in execution order.
+    pub filled: Qty,                              // Total quantity of the order that traded.
+    pub resting: Qty,                             // Quantity of the order left resting in the book.
+    pub cancelled: Qty,                           // Quantity of the order that was cancelled.
+    pub resting_price: Option<u32>,               // Price the remainder rests at, after post-only.
+    pub hidden: bool,                             // True if the remainder rests as a hidden order.
+    pub self_trade_cancels: Vec<SelfTradeCancel>, // Own resting orders reduced or cancelled.
+    pub triggered: Vec<Execution>,                // Stop orders released by the trades, in order.
+}
+
+impl Execution {
+    /// Creates the execution of an order that has not traded yet.
+    #[inline]
+    fn new(order_id: OrderId) -> Self {
+        Self {
+            order_id,
+            status: ExecutionStatus::Resting,
+            fills: Vec::new(),
+            filled: Qty(0),
+            resting: Qty(0),
+            cancelled: Qty(0),
+            resting_price: None,
+            hidden: false,
+            self_trade_cancels: Vec::new(),
+            triggered: Vec::new(),
+        }
+    }
+
+    /// Creates the execution of a rejected order.
+    #[inline]
+    fn rejected(order_id: OrderId, reason: RejectReason) -> Self {
+        Self {
+            status: ExecutionStatus::Rejected(reason),
+            ..Self::new(order_id)
+        }
+    }
+
+    /// Sets the status from the traded, resting and cancelled quantities.
+    #[inline]
+    fn settle(&mut self) {
+        self.status = if self.resting.is_empty() && self.cancelled.is_empty() {
+            ExecutionStatus::Filled
+        } else if !self.filled.is_empty() {
+            ExecutionStatus::PartiallyFilled
+        } else if !self.resting.is_empty() {
+            ExecutionStatus::Resting
+        } else {
+            ExecutionStatus::Cancelled
+        };
+    }
+}
+
+/// Returns true if an incoming order of the given side and limit can trade at `price`.
+/// A `limit` of None trades at any price.
+#[inline]
+fn crosses(side: Side, price: u32, limit: Option<u32>) -> bool {
+    match (limit, side) {
+        (None, _) => true,
+        (Some(limit), Side::Bid) => price <= limit,
+        (Some(limit), Side::Ask) => price >= limit,
+    }
+}
+
+impl<M: OrderIdMap, A: AllocationPolicy, E: EventSink> OrderBookManager<M, L3OrderBook<A>, E> {
+    /// Submits an order to the matching engine.
+    /// The order first trades against the opposite side of the book, best price first and,
+    /// within a price, oldest order first, for as long as the prices cross. What happens to
+    /// the remainder depends on the order type: a limit order rests it in the book, the other
+    /// types cancel it. A fill-or-kill order is checked against the resting quantity before
+    /// anything is changed, and rejected if it cannot be filled completely; resting orders of
+    /// its own owner do not count toward that quantity. A post-only order that would trade on
+    /// arrival is rejected, or repriced just outside the opposite best price. When matching
+    /// reaches resting orders of the same owner, the self-trade prevention policy of the order
+    /// decides what happens to them instead of trading.
+    /// ## Arguments:
+    /// - `request`: The order to submit.
+    ///
+    /// ## Example:
+    /// ```
+    /// let mut orderbook_manager = OrderBookManager::new_l3();
+    ///
+    /// orderbook_manager.add_order(OrderId(0), BookId(0), Qty(100), 600, false);
+    /// let execution = orderbook_manager.submit_order(&OrderRequest::immediate_or_cancel(
+    ///     OrderId(1), // Order ID
+    ///     BookId(0), // Book ID
+    ///     Side::Bid, // Side
+    ///     Qty(150), // Quantity
+    ///     600, // Limit price
+    /// ));
+    /// assert_eq!(execution.status, ExecutionStatus::PartiallyFilled);
+    /// assert_eq!(execution.cancelled, Qty(50));
+    /// ```
+    pub fn submit_order(&mut self, request: &OrderRequest) -> Execution {
+        let mut execution = self.execute_request(request);
+        self.release_stops(request.book_id, &mut execution.triggered);
+        execution
+    }
+
+    /// Cancels a stop order that is still waiting for its trigger price.
+    /// ## Arguments:
+    /// - `book_id`: The identifier for the book the stop order was submitted to.
+    /// - `order_id`: The order ID for the stop order.
+    ///
+    /// # Returns
+    /// Returns the cancelled stop order, or None if no such stop order is waiting.
+    #[inline]
+    pub fn cancel_stop_order(
+        &mut self,
+        book_id: BookId,
+        order_id: OrderId,
+    ) -> Option<OrderRequest> {
+        self.books
+            .get_mut(book_id.value() as usize)?
+            .as_mut()?
+            .stops
+            .remove(order_id)
+    }
+
+    /// Releases the stop orders triggered by the last trade of a book into the book, until
+    /// no more stop order triggers. A released order may trade and trigger further stops.
+    fn release_stops(&mut self, book_id: BookId, triggered: &mut Vec<Execution>) {
+        loop {
+            let Some(book) = self
+                .books
+                .get_mut(book_id.value() as usize)
+                .and_then(|book| book.as_mut())
+            else {
+                return;
+            };
+            let Some(price) = book.last_trade_price() else {
+                return;
+            };
+            let released = book.stops.take_triggered(price);
+            if released.is_empty() {
+                return;
+            }
+            for request in released {
+                triggered.push(self.execute_request(&request.released()));
+            }
+        }
+    }
+
+    /// Validates an order, then either parks it as a stop order or matches it.
+    fn execute_request(&mut self, request: &OrderRequest) -> Execution {
+        if request.qty.is_empty() {
+            return Execution::rejected(request.order_id, RejectReason::ZeroQuantity);
+        }
+        let book_idx = request.book_id.value() as usize;
+        let is_pending_stop = self.books[book_idx]
+            .as_ref()
+            .is_some_and(|book| book.stops.contains(request.order_id));
+        if self.oid_map.get(request.order_id).is_some() || is_pending_stop {
+            return Execution::rejected(request.order_id, RejectReason::DuplicateOrderId);
+        }
+
+        let mut request = *request;
+        if let Some(trigger) = request.trigger_price() {
+            let book = self.books[book_idx].get_or_insert_with(L3OrderBook::default);
+            match book.last_trade_price() {
+                Some(price) if request.is_triggered_by(price) => request = request.released(),
+                _ => {
+                    book.stops.insert(trigger, request);
+                    let mut execution = Execution::new(request.order_id);
+                    execution.status = ExecutionStatus::Pending;
+                    return execution;
+                }
+            }
+        }
+        if let Some(post_only) = request.post_only {
+            let best = self.book(request.book_id).and_then(|book| {
+                let px = book.aggregate.levels(request.side.opposite()).best()?;
+                Some(px.price().to_u32())
+            });
+            if let Some(best) =
+                best.filter(|best| crosses(request.side, *best, request.limit_price()))
+            {
+                let inside = match request.side {
+                    Side::Bid => best.checked_sub(1).filter(|price| *price > 0),
+                    Side::Ask => best
+                        .checked_add(1)
+                        .filter(|price| *price <= i32::MAX as u32),
+                };
+                match (post_only, inside) {
+                    (PostOnly::Reprice, Some(price)) if request.order_type == OrderType::Limit => {
+                        request.price = price;
+                    }
+                    _ => {
+                        return Execution::rejected(
+                            request.order_id,
+                            RejectReason::WouldTakeLiquidity,
+                        )
+                    }
+                }
+            }
+        }
+        let limit = request.limit_price();
+        if request.order_type == OrderType::FillOrKill
+            && self.available_qty(&request, limit) < request.qty.value()
+        {
+            return Execution::rejected(request.order_id, RejectReason::InsufficientLiquidity);
+        }
+
+        let mut execution = Execution::new(request.order_id);
+        let remaining = self.match_order(&request, &mut execution);
+        if !remaining.is_empty() {
+            if request.order_type == OrderType::Limit {
+                let attributes = OrderAttributes {
+                    fully_hidden: request.hidden,
+                    owner: request.owner,
+                    ..OrderAttributes::default()
+                };
+                self.insert_order(
+                    request.order_id,
+                    Order::new(remaining, LevelId(0), request.book_id),
+                    request.price,
+                    request.side.is_bid(),
+                    attributes,
+                );
+                execution.resting = remaining;
+                execution.resting_price = Some(request.price);
+                execution.hidden = request.hidden;
+            } else {
+                execution.cancelled = remaining;
+            }
+        }
+        execution.settle();
+        self.reprice_pegs(request.book_id);
+        execution
+    }
+
+    /// Submits a limit order to the matching engine. See `submit_order`.
+    /// ## Arguments:
+    /// - `order_id`: The order ID for the order. Represented as unique reference number.
+    /// - `book_id`: The identifier for the book where the order will be matched.
+    /// - `qty`: The quantity of the order.
+    /// - `price32`: The limit price of the order as a 32-bit unsigned integer.
+    /// - `is_bid`: A flag indicating whether the order is a bid (true) or ask (false).
+    ///
+    /// ## Example:
+    /// ```
+    /// let mut orderbook_manager = OrderBookManager::new_l3();
+    ///
+    /// orderbook_manager.add_order(OrderId(0), BookId(0), Qty(100), 600, false);
+    /// let execution = orderbook_manager.submit_limit_order(OrderId(1), BookId(0), Qty(150), 600, true);
+    /// assert_eq!(execution.filled, Qty(100));
+    /// assert_eq!(execution.resting, Qty(50));
+    /// ```
+    #[inline]
+    pub fn submit_limit_order(
+        &mut self,
+        order_id: OrderId,
+        book_id: BookId,
+        qty: Qty,
+        price32: u32,
+        is_bid: bool,
+    ) -> Execution {
+        let side = Side::from_is_bid(is_bid);
+        self.submit_order(&OrderRequest::limit(order_id, book_id, side, qty, price32))
+    }
+
+    /// Returns the quantity an incoming order could trade up to its limit, without changing
+    /// the book. Hidden reserves count, since matching replenishes them. Resting orders of the
+    /// same owner never trade with it: they are skipped if self-trade prevention cancels them,
+    /// and otherwise end the count, since they cut the incoming order short. Stops counting
+    /// once the quantity of the order is reached.
+    fn available_qty(&self, request: &OrderRequest, limit: Option<u32>) -> u32 {
+        let Some(book) = self.book(request.book_id) else {
+            return 0;
+        };
+        let (side, needed) = (request.side, request.qty.value());
+        let mut available: u32 = 0;
+        for px in book.aggregate.levels(side.opposite()).iter_best_first() {
+            let price = px.price().to_u32();
+            if !crosses(side, price, limit) || available >= needed {
+                break;
+            }
+            if request.owner == OwnerId(0) {
+                let size = book
+                    .aggregate
+                    .level_pool
+                    .get(px.level_id())
+                    .unwrap()
+                    .total();
+                available = available.saturating_add(size.value());
+                continue;
+            }
+
+            let (mut displayed, mut total) = (0u32, 0u32);
+            for node in book.orders_at(side.opposite(), price) {
+                let attributes = node.attributes();
+                if !request.owner.is_same(attributes.owner) {
+                    displayed = displayed.saturating_add(node.qty().value());
+                    total = total.saturating_add(node.qty().value() + attributes.hidden.value());
+                } else if request.stp != SelfTradePrevention::CancelOldest {
+                    // Under time priority, only the displayed quantity ahead of the own order
+                    // trades first, since replenished reserves queue behind it. Under other
+                    // policies, the own order is reached before the level fills the order.
+                    if book.allocation.is_time_priority() {
+                        available = available.saturating_add(displayed);
+                    }
+                    return available;
+                }
+            }
+            available = available.saturating_add(total);
+        }
+        available
+    }
+
+    /// Matches an incoming order against the opposite side of a book, up to its limit price.
+    /// Each price level is split among its orders by the allocation policy of the book.
+    /// Resting orders of the same owner are handled by the self-trade prevention policy of the
+    /// incoming order instead of trading; what it cancels of the incoming order is added to
+    /// `execution.cancelled`.
+    ///
+    /// # Returns
+    /// Returns the quantity of the order that is left unmatched.
+    pub(crate) fn match_order(&mut self, request: &OrderRequest, execution: &mut Execution) -> Qty {
+        let (book_id, side) = (request.book_id, request.side);
+        let limit = request.limit_price();
+        let mut remaining = request.qty;
+        while !remaining.is_empty() {
+            let Some((price, makers)) = self.allocate_level(book_id, side, limit, remaining) else {
+                break;
+            };
+
+            let (before, self_trade_cancels) = (remaining, execution.self_trade_cancels.len());
+            for (maker_order_id, allocation) in makers {
+                if remaining.is_empty() {
+                    break;
+                }
+                if allocation.is_empty() {
+                    continue;
+                }
+                let maker_owner = self
+                    .book(book_id)
+                    .and_then(|book| book.node(maker_order_id))
+                    .map_or(OwnerId(0), |node| node.attributes().owner);
+                if request.owner.is_same(maker_owner) {
+                    remaining =
+                        self.prevent_self_trade(request.stp, remaining, maker_order_id, execution);
+                    continue;
+                }
+
+                let traded = Qty(remaining.value().min(allocation.value()));
+                self.trade_order(maker_order_id, traded);
+                if let Some(book) = self.books[book_id.value() as usize].as_mut() {
+                    book.set_last_trade_price(price);
+                }
+                remaining -= traded;
+                execution.filled += traded;
+                execution.fills.push(Fill {
+                    book_id,
+                    taker_order_id: request.order_id,
+                    maker_order_id,
+                    aggressor: side,
+                    price,
+                    qty: traded,
+                });
+            }
+            // A level that neither trades nor cancels anything would be allocated again.
+            if remaining == before && execution.self_trade_cancels.len() == self_trade_cancels {
+                break;
+            }
+        }
+        remaining
+    }
+
+    /// Applies a self-trade prevention policy to an incoming order and the resting order of the
+    /// same owner it would trade with first.
+    ///
+    /// # Returns
+    /// Returns the quantity of the incoming order left to match.
+    fn prevent_self_trade(
+        &mut self,
+        stp: SelfTradePrevention,
+        remaining: Qty,
+        maker_order_id: OrderId,
+        execution: &mut Execution,
+    ) -> Qty {
+        let Some(maker) = self
+            .oid_map
+            .get(maker_order_id)
+            .and_then(|order| self.book(order.book_id())?.node(maker_order_id))
+        else {
+            return remaining;
+        };
+        let (maker_qty, maker_total) = (
+            maker.qty(),
+            Qty(maker.qty().value() + maker.attributes().hidden.value()),
+        );
+        let cancel_maker = |this: &mut Self, execution: &mut Execution| {
+            this.discard_order(maker_order_id);
+            execution.self_trade_cancels.push(SelfTradeCancel {
+                order_id: maker_order_id,
+                qty: maker_total,
+            });
+        };
+        match stp {
+            SelfTradePrevention::CancelNewest => {
+                execution.cancelled += remaining;
+                Qty(0)
+            }
+            SelfTradePrevention::CancelOldest => {
+                cancel_maker(self, execution);
+                remaining
+            }
+            SelfTradePrevention::CancelBoth => {
+                cancel_maker(self, execution);
+                execution.cancelled += remaining;
+                Qty(0)
+            }
+            SelfTradePrevention::DecrementAndCancel => {
+                // The resting order is reduced as if the quantity had traded, so an iceberg
+                // order is replenished.
+                let decrement = Qty(remaining.value().min(maker_qty.value()));
+                self.fill_order(maker_order_id, decrement);
+                execution.self_trade_cancels.push(SelfTradeCancel {
+                    order_id: maker_order_id,
+                    qty: decrement,
+                });
+                execution.cancelled += decrement;
+                let mut remaining = remaining;
+                remaining -= decrement;
+                remaining
+            }
+        }
+    }
+
+    /// Returns the best price of the opposite side for an incoming order of the given side,
+    /// and the orders resting there in time priority, each with its share of `qty` under the
+    /// allocation policy of the book. Orders with no quantity left are skipped, and so are
+    /// levels holding only such orders. Returns None if no such price crosses `limit`.
+    fn allocate_level(
+        &self,
+        book_id: BookId,
+        side: Side,
+        limit: Option<u32>,
+        qty: Qty,
+    ) -> Option<(u32, Vec<(OrderId, Qty)>)> {
+        let book = self.book(book_id)?;
+        let opposite = side.opposite();
+        let mut makers = Vec::new();
+        let mut price = 0;
+        for px in book.aggregate.levels(opposite).iter_best_first() {
+            price = px.price().to_u32();
+            if !crosses(side, price, limit) {
+                return None;
+            }
+            let mut covered: u64 = 0;
+            for node in book.orders_at(opposite, price) {
+                if book.allocation.is_time_priority() && covered >= qty.value() as u64 {
+                    break;
+                }
+                // Orders cancelled down to zero keep their place in the queue, but cannot trade.
+                if node.qty().is_empty() {
+                    continue;
+                }
+                covered += node.qty().value() as u64;
+                makers.push((node.order_id(), node.qty()));
+            }
+            if !makers.is_empty() {
+                break;
+            }
+        }
+        if makers.is_empty() {
+            return None;
+        }
+        let sizes: Vec<Qty> = makers.iter().map(|(_, qty)| *qty).collect();
+        let allocations = book.allocation.allocate(qty, &sizes);
+        let makers = makers
+            .into_iter()
+            .zip(allocations)
+            .map(|((order_id, _), allocation)| (order_id, allocation))
+            .collect();
+        Some((price, makers))
+    }
+}
@@ -0,0 +1,300 @@
+// oid_map.rs
+
+use crate::{
+    order::{OidMap, Order, OrderId},
+    quantity::Qty,
+};
+use std::collections::HashMap;
+
+/// Initial number of slots of a `HashOidMap`. Always a power of two.
+const INITIAL_HASH_CAPACITY: usize = 1 << 16;
+/// Number of order ids covered by a page of a `PagedOidMap`.
+const PAGE_BITS: u32 = 12;
+const PAGE_SIZE: usize = 1 << PAGE_BITS;
+
+/// A map from order IDs to orders, used by the `OrderBookManager` to locate resting orders.
+///
+/// The implementations trade memory for speed differently:
+/// - `OidMap`: a dense array, the fastest for small sequential ids such as ITCH reference numbers.
+/// - `HashOidMap`: an open-addressing hash table, for sparse or random ids.
+/// - `PagedOidMap`: fixed-size pages allocated on demand, for ids that come in clusters.
+pub trait OrderIdMap: Default {
+    /// Reserves space for an OrderId in the map.
+    fn reserve(&mut self, oid: OrderId);
+
+    /// Inserts an Order into the map with a specific OrderId.
+    fn insert(&mut self, oid: OrderId, value: &Order);
+
+    /// Removes an Order from the map by its OrderId.
+    fn remove(&mut self, oid: OrderId);
+
+    /// Gets a reference to an Order by its OrderId.
+    fn get(&self, oid: OrderId) -> Option<&Order>;
+
+    /// Gets a mutable reference to an Order by its OrderId.
+    fn get_mut(&mut self, oid: OrderId) -> Option<&mut Order>;
+
+    /// Updates the quantity of an Order in the map by its OrderId.
+    #[inline]
+    fn update_qty(&mut self, oid: OrderId, qty: Qty) {
+        if let Some(order) = self.get_mut(oid) {
+            let mut remaining = order.qty();
+            remaining -= qty;
+            order.set_qty(remaining);
+        }
+    }
+}
+
+impl OrderIdMap for OidMap {
+    #[inline]
+    fn reserve(&mut self, oid: OrderId) {
+        OidMap::reserve(self, oid)
+    }
+
+    #[inline]
+    fn insert(&mut self, oid: OrderId, value: &Order) {
+        OidMap::insert(self, oid, value)
+    }
+
+    #[inline]
+    fn remove(&mut self, oid: OrderId) {
+        OidMap::remove(self, oid)
+    }
+
+    #[inline]
+    fn get(&self, oid: OrderId) -> Option<&Order> {
+        OidMap::get(self, oid)
+    }
+
+    #[inline]
+    fn get_mut(&mut self, oid: OrderId) -> Option<&mut Order> {
+        OidMap::get_mut(self, oid)
+    }
+
+    #[inline]
+    fn update_qty(&mut self, oid: OrderId, qty: Qty) {
+        OidMap::update_qty(self, oid, qty)
+    }
+}
+
+/// An open-addressing hash table with linear probing, keyed by order ID.
+/// Memory grows with the number of live orders rather than with the largest id.
+pub struct HashOidMap {
+    slots: Vec<Option<(u64, Order)>>,
+    len: usize,
+}
+
+impl Default for HashOidMap {
+    /// Creates a default HashOidMap instance.
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl HashOidMap {
+    /// Creates a new HashOidMap with an initial capacity.
+    #[inline]
+    pub fn new() -> Self {
+        Self::with_capacity(INITIAL_HASH_CAPACITY)
+    }
+
+    /// Creates a new HashOidMap able to hold `capacity` orders before growing.
+    pub fn with_capacity(capacity: usize) -> Self {
+        let slots = (capacity.max(8) * 4 / 3).next_power_of_two();
+        HashOidMap {
+            slots: vec![None; slots],
+            len: 0,
+        }
+    }
+
+    /// Returns the number of orders in the map.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns true if the map holds no order.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns the home slot of an order ID, using Fibonacci hashing.
+    #[inline]
+    fn home(&self, key: u64) -> usize {
+        let bits = self.slots.len().trailing_zeros();
+        (key.wrapping_mul(0x9e37_79b9_7f4a_7c15) >> (64 - bits)) as usize
+    }
+
+    /// Returns the slot holding an order ID, if any.
+    #[inline]
+    fn find(&self, key: u64) -> Option<usize> {
+        let mask = self.slots.len() - 1;
+        let mut idx = self.home(key);
+        loop {
+            match &self.slots[idx] {
+                Some((k, _)) if *k == key => return Some(idx),
+                Some(_) => idx = (idx + 1) & mask,
+                None => return None,
+            }
+        }
+    }
+
+    /// Doubles the number of slots and re-inserts every order.
+    fn grow(&mut self) {
+        let new_len = self.slots.len() * 2;
+        let old = std::mem::replace(&mut self.slots, vec![None; new_len]);
+        for (key, order) in old.into_iter().flatten() {
+            let idx = self.vacant_slot(key);
+            self.slots[idx] = Some((key, order));
+        }
+    }
+
+    /// Returns the first empty slot of the probe sequence of an order ID.
+    #[inline]
+    fn vacant_slot(&self, key: u64) -> usize {
+        let mask = self.slots.len() - 1;
+        let mut idx = self.home(key);
+        while self.slots[idx].is_some() {
+            idx = (idx + 1) & mask;
+        }
+        idx
+    }
+}
+
+impl OrderIdMap for HashOidMap {
+    #[inline]
+    fn reserve(&mut self, _oid: OrderId) {
+        // Keep the load factor below 3/4.
+        if (self.len + 1) * 4 > self.slots.len() * 3 {
+            self.grow();
+        }
+    }
+
+    #[inline]
+    fn insert(&mut self, oid: OrderId, value: &Order) {
+        if let Some(idx) = self.find(oid.0) {
+            self.slots[idx] = Some((oid.0, value.clone()));
+            return;
+        }
+        self.reserve(oid);
+        let idx = self.vacant_slot(oid.0);
+        self.slots[idx] = Some((oid.0, value.clone()));
+        self.len += 1;
+    }
+
+    /// Removes the order with backward-shift deletion, so that no tombstone is left behind.
+    fn remove(&mut self, oid: OrderId) {
+        let Some(mut hole) = self.find(oid.0) else {
+            return;
+        };
+        self.slots[hole] = None;
+        self.len -= 1;
+
+        let mask = self.slots.len() - 1;
+        let mut idx = (hole + 1) & mask;
+        while let Some((key, _)) = &self.slots[idx] {
+            // Move the entry into the hole unless its home lies cyclically in (hole, idx].
+            let home = self.home(*key);
+            if (idx.wrapping_sub(home) & mask) >= (idx.wrapping_sub(hole) & mask) {
+                self.slots[hole] = self.slots[idx].take();
+                hole = idx;
+            }
+            idx = (idx + 1) & mask;
+        }
+    }
+
+    #[inline]
+    fn get(&self, oid: OrderId) -> Option<&Order> {
+        let idx = self.find(oid.0)?;
+        self.slots[idx].as_ref().map(|(_, order)| order)
+    }
+
+    #[inline]
+    fn get_mut(&mut self, oid: OrderId) -> Option<&mut Order> {
+        let idx = self.find(oid.0)?;
+        self.slots[idx].as_mut().map(|(_, order)| order)
+    }
+}
+
+/// A page of consecutive order IDs and the number of orders it holds.
+struct Page {
+    orders: Box<[Option<Order>]>,
+    len: usize,
+}
+
+/// Order IDs split into fixed-size pages that are allocated when their first order arrives
+/// and freed when their last order leaves. Ids that are dense within a few ranges are stored
+/// almost as compactly as in a dense array, without paying for the gaps between the ranges.
+#[derive(Default)]
+pub struct PagedOidMap {
+    pages: HashMap<u64, Page>,
+}
+
+impl PagedOidMap {
+    /// Creates a new, empty PagedOidMap.
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the number of allocated pages.
+    #[inline]
+    pub fn page_count(&self) -> usize {
+        self.pages.len()
+    }
+
+    /// Splits an order ID into its page number and its index in the page.
+    #[inline]
+    fn split(oid: OrderId) -> (u64, usize) {
+        (oid.0 >> PAGE_BITS, (oid.0 as usize) & (PAGE_SIZE - 1))
+    }
+}
+
+impl OrderIdMap for PagedOidMap {
+    #[inline]
+    fn reserve(&mut self, oid: OrderId) {
+        let (page, _) = Self::split(oid);
+        self.pages.entry(page).or_insert_with(|| Page {
+            orders: vec![None; PAGE_SIZE].into_boxed_slice(),
+            len: 0,
+        });
+    }
+
+    #[inline]
+    fn insert(&mut self, oid: OrderId, value: &Order) {
+        self.reserve(oid);
+        let (page, idx) = Self::split(oid);
+        let page = self.pages.get_mut(&page).unwrap();
+        if page.orders[idx].is_none() {
+            page.len += 1;
+        }
+        page.orders[idx] = Some(value.clone());
+    }
+
+    #[inline]
+    fn remove(&mut self, oid: OrderId) {
+        let (page_no, idx) = Self::split(oid);
+        if let Some(page) = self.pages.get_mut(&page_no) {
+            if page.orders[idx].take().is_some() {
+                page.len -= 1;
+                if page.len == 0 {
+                    self.pages.remove(&page_no);
+                }
+            }
+        }
+    }
+
+    #[inline]
+    fn get(&self, oid: OrderId) -> Option<&Order> {
+        let (page, idx) = Self::split(oid);
+        self.pages.get(&page)?.orders[idx].as_ref()
+    }
+
+    #[inline]
+    fn get_mut(&mut self, oid: OrderId) -> Option<&mut Order> {
+        let (page, idx) = Self::split(oid);
+        self.pages.get_mut(&page)?.orders[idx].as_mut()
+    }
+}
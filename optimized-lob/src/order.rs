@@ -1,15 +1,35 @@
 // order.rs
 
 use crate::{
+    error::SnapshotError,
     level::LevelId,
     quantity::Qty,
-    utils::{BookId, INITIAL_ORDER_COUNT},
+    snapshot::{Reader, Snapshot},
+    utils::{BookId, DENSE_ORDER_LIMIT, INITIAL_ORDER_COUNT},
 };
-use std::fmt::Debug;
+use std::{collections::HashMap, fmt::Debug};
 
-/// Unique identifier for an order.
+/// Unique identifier for an order, such as an ITCH order reference number.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
-pub struct OrderId(pub u32);
+pub struct OrderId(pub u64);
+
+/// Identifier of the firm or account an order belongs to, used for self-trade prevention.
+/// `OwnerId(0)` is anonymous and never matches another owner.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct OwnerId(pub u32);
+
+impl OwnerId {
+    #[inline]
+    pub fn value(&self) -> u32 {
+        self.0
+    }
+
+    /// Returns true if orders of this owner and of `other` must not trade with each other.
+    #[inline]
+    pub fn is_same(&self, other: OwnerId) -> bool {
+        self.0 != 0 && self.0 == other.0
+    }
+}
 
 /// Represents an order in the trading system.
 #[derive(Default, Clone)]
@@ -99,8 +119,12 @@ impl Order {
 }
 
 /// Data structure for mapping OrderIds to Order objects.
+///
+/// Ids below `DENSE_ORDER_LIMIT` index a dense array, which is the fast path for the
+/// sequential ids of a typical session. Larger ids are kept in a hash map instead.
 pub struct OidMap {
     data: Vec<Option<Order>>,
+    sparse: HashMap<u64, Order>,
 }
 
 impl Default for OidMap {
@@ -117,59 +141,160 @@ impl OidMap {
     pub fn new() -> Self {
         OidMap {
             data: vec![None; INITIAL_ORDER_COUNT], // Use a fixed-size array
+            sparse: HashMap::new(),
+        }
+    }
+
+    /// Returns the index of an OrderId in the dense array, if it belongs there.
+    #[inline]
+    fn dense_idx(oid: OrderId) -> Option<usize> {
+        if oid.0 < DENSE_ORDER_LIMIT {
+            Some(oid.0 as usize)
+        } else {
+            None
         }
     }
 
     /// Reserves space for an OrderId in the map.
     #[inline]
     pub fn reserve(&mut self, oid: OrderId) {
-        let idx = oid.0 as usize;
-        if idx >= self.data.len() {
-            self.data.resize(idx + 1, None);
+        match Self::dense_idx(oid) {
+            Some(idx) => {
+                if idx >= self.data.len() {
+                    self.data.resize(idx + 1, None);
+                }
+            }
+            None => self.sparse.reserve(1),
         }
     }
 
     /// Inserts an Order into the map with a specific OrderId.
     #[inline]
     pub fn insert(&mut self, oid: OrderId, value: &Order) {
-        let idx = oid.0 as usize;
-        if idx >= self.data.len() {
-            self.data.resize(idx + 1, None);
+        match Self::dense_idx(oid) {
+            Some(idx) => {
+                if idx >= self.data.len() {
+                    self.data.resize(idx + 1, None);
+                }
+                self.data[idx] = Some(value.clone()); // Clone only when necessary
+            }
+            None => {
+                self.sparse.insert(oid.0, value.clone());
+            }
         }
-        self.data[idx] = Some(value.clone()); // Clone only when necessary
     }
 
     /// Removes an Order from the map by its OrderId.
     #[inline]
     pub fn remove(&mut self, oid: OrderId) {
-        let idx = oid.0 as usize;
-        if idx < self.data.len() {
-            self.data[idx] = None;
+        match Self::dense_idx(oid) {
+            Some(idx) => {
+                if idx < self.data.len() {
+                    self.data[idx] = None;
+                }
+            }
+            None => {
+                self.sparse.remove(&oid.0);
+            }
         }
     }
 
     /// Updates the quantity of an Order in the map by its OrderId.
     #[inline]
     pub fn update_qty(&mut self, oid: OrderId, qty: Qty) {
-        let idx = oid.0 as usize;
-        if idx < self.data.len() {
-            if let Some(order) = &mut self.data[idx] {
-                order.qty -= qty;
-            }
+        if let Some(order) = self.get_mut(oid) {
+            order.qty -= qty;
         }
     }
 
     /// Gets a reference to an Order by its OrderId.
     #[inline]
     pub fn get(&self, oid: OrderId) -> Option<&Order> {
-        let idx = oid.0 as usize;
-        self.data.get(idx)?.as_ref()
+        match Self::dense_idx(oid) {
+            Some(idx) => self.data.get(idx)?.as_ref(),
+            None => self.sparse.get(&oid.0),
+        }
     }
 
     /// Gets a mutable reference to an Order by its OrderId.
     #[inline]
     pub fn get_mut(&mut self, oid: OrderId) -> Option<&mut Order> {
-        let idx = oid.0 as usize;
-        self.data.get_mut(idx)?.as_mut()
+        match Self::dense_idx(oid) {
+            Some(idx) => self.data.get_mut(idx)?.as_mut(),
+            None => self.sparse.get_mut(&oid.0),
+        }
+    }
+}
+
+impl Snapshot for Order {
+    fn encode(&self, out: &mut Vec<u8>) {
+        self.level_id.encode(out);
+        self.book_id.encode(out);
+        self.qty.encode(out);
+    }
+
+    fn decode(reader: &mut Reader<'_>) -> Result<Self, SnapshotError> {
+        Ok(Self {
+            level_id: reader.read()?,
+            book_id: reader.read()?,
+            qty: reader.read()?,
+        })
+    }
+}
+
+/// Only the resting orders are written, each with its id: the dense orders in id order, then
+/// the sparse orders in id order. The dense array is restored as large as inserting the orders
+/// makes it, so a corrupt id cannot allocate more than the order itself would.
+impl Snapshot for OidMap {
+    fn encode(&self, out: &mut Vec<u8>) {
+        let dense: Vec<(usize, &Order)> = self
+            .data
+            .iter()
+            .enumerate()
+            .filter_map(|(idx, order)| Some((idx, order.as_ref()?)))
+            .collect();
+        (dense.len() as u64).encode(out);
+        for (idx, order) in dense {
+            (idx as u64).encode(out);
+            order.encode(out);
+        }
+
+        let mut sparse: Vec<(&u64, &Order)> = self.sparse.iter().collect();
+        sparse.sort_unstable_by_key(|(oid, _)| **oid);
+        (sparse.len() as u64).encode(out);
+        for (oid, order) in sparse {
+            oid.encode(out);
+            order.encode(out);
+        }
+    }
+
+    fn decode(reader: &mut Reader<'_>) -> Result<Self, SnapshotError> {
+        let mut data = vec![None; INITIAL_ORDER_COUNT];
+        let dense: u64 = reader.read()?;
+        for _ in 0..dense {
+            let idx: u64 = reader.read()?;
+            if idx >= DENSE_ORDER_LIMIT {
+                return Err(SnapshotError::Invalid("order id"));
+            }
+            let order: Order = reader.read()?;
+            let idx = idx as usize;
+            if idx >= data.len() {
+                data.try_reserve(idx + 1 - data.len())
+                    .map_err(|_| SnapshotError::Invalid("order map length"))?;
+                data.resize(idx + 1, None);
+            }
+            data[idx] = Some(order);
+        }
+
+        let mut sparse = HashMap::new();
+        let count: u64 = reader.read()?;
+        for _ in 0..count {
+            let oid: u64 = reader.read()?;
+            if oid < DENSE_ORDER_LIMIT {
+                return Err(SnapshotError::Invalid("order id"));
+            }
+            sparse.insert(oid, reader.read()?);
+        }
+        Ok(Self { data, sparse })
     }
 }
@@ -1,11 +1,14 @@
 // orderbook.rs
 
 use crate::{
-    level::{Level, LevelId, PriceLevel, SortedLevels},
+    error::SnapshotError,
+    level::{DepthLevel, Level, LevelId, PriceLevel, SortedLevels},
     order::Order,
     pool::LevelPool,
     price::Price,
     quantity::Qty,
+    side::Side,
+    snapshot::{Reader, Snapshot},
     utils::MAX_LEVELS,
 };
 
@@ -38,6 +41,20 @@ impl OrderBook {
     /// Determines whether the order is a bid or ask and inserts it accordingly.
     #[inline]
     pub fn add_order(&mut self, order: &mut Order, price: Price, qty: Qty) {
+        self.place_order(order, price).add_order(qty);
+    }
+
+    /// Adds a hidden order to the order book. Its quantity is added to the hidden quantity of
+    /// the level instead of its displayed size.
+    #[inline]
+    pub fn add_hidden_order(&mut self, order: &mut Order, price: Price, qty: Qty) {
+        self.place_order(order, price).add_hidden_order(qty);
+    }
+
+    /// Returns the level at the given price, allocating it if no order rests there yet, and
+    /// records it in the order.
+    #[inline]
+    fn place_order(&mut self, order: &mut Order, price: Price) -> &mut Level {
         let levels = if price.is_bid() {
             &mut self.bids
         } else {
@@ -76,7 +93,7 @@ impl OrderBook {
             let px = PriceLevel::new(price, level_ptr);
             levels.insert(insertion_point, px);
         }
-        self.level_pool.get_mut(order.level_id()).unwrap().incr(qty);
+        self.level_pool.get_mut(order.level_id()).unwrap()
     }
 
     /// Reduces the quantity of an existing order in the order book.
@@ -88,13 +105,55 @@ impl OrderBook {
             .decr(qty);
     }
 
-    /// Removes an order from the order book and deallocates the associated level if it becomes empty.
+    /// Adds the hidden reserve of an iceberg order to the level it rests at.
+    #[inline]
+    pub fn add_hidden(&mut self, order: &Order, qty: Qty) {
+        self.level_pool
+            .get_mut(order.level_id())
+            .unwrap()
+            .incr_hidden(qty);
+    }
+
+    /// Reduces the hidden quantity an order holds at its level: the quantity of a hidden order,
+    /// or the reserve of an iceberg order.
+    #[inline]
+    pub fn reduce_hidden(&mut self, order: &Order, qty: Qty) {
+        self.level_pool
+            .get_mut(order.level_id())
+            .unwrap()
+            .decr_hidden(qty);
+    }
+
+    /// Moves quantity of an iceberg order from the hidden reserve to the displayed size of its level.
+    #[inline]
+    pub fn replenish_order(&mut self, order: &Order, qty: Qty) {
+        let lvl = self.level_pool.get_mut(order.level_id()).unwrap();
+        lvl.decr_hidden(qty);
+        lvl.incr(qty);
+    }
+
+    /// Removes an order from the order book and deallocates the associated level once no order
+    /// rests there. A level keeps orders whose quantity was cancelled down to zero.
     #[inline]
     pub fn remove_order(&mut self, order: &mut Order) {
         let lvl = self.level_pool.get_mut(order.level_id()).unwrap();
-        lvl.decr(order.qty());
+        lvl.remove_order(order.qty());
+        self.release_level(order.level_id());
+    }
 
-        if lvl.size().is_empty() {
+    /// Removes a hidden order from the order book, like `remove_order`.
+    #[inline]
+    pub fn remove_hidden_order(&mut self, order: &mut Order) {
+        let lvl = self.level_pool.get_mut(order.level_id()).unwrap();
+        lvl.remove_hidden_order(order.qty());
+        self.release_level(order.level_id());
+    }
+
+    /// Deallocates a level once no order rests there.
+    #[inline]
+    fn release_level(&mut self, level_id: LevelId) {
+        let lvl = self.level_pool.get(level_id).unwrap();
+        if lvl.order_count() == 0 {
             let level_price = lvl.price();
             let levels = if level_price.is_bid() {
                 &mut self.bids
@@ -102,7 +161,119 @@ impl OrderBook {
                 &mut self.asks
             };
             levels.remove(level_price);
-            self.level_pool.free(LevelId(order.level_id().value()));
+            self.level_pool.free(level_id);
+        }
+    }
+
+    /// Returns the sorted levels of one side of the book.
+    #[inline]
+    pub fn levels(&self, side: Side) -> &SortedLevels {
+        match side {
+            Side::Bid => &self.bids,
+            Side::Ask => &self.asks,
+        }
+    }
+
+    /// Returns the aggregate state of a price level.
+    #[inline]
+    fn depth_level(&self, px: &PriceLevel) -> DepthLevel {
+        let level = self.level_pool.get(px.level_id()).unwrap();
+        DepthLevel {
+            price: px.price().to_u32(),
+            qty: level.size(),
+            total_qty: level.total(),
+            order_count: level.order_count(),
+        }
+    }
+
+    /// Returns true if a price level is displayed, i.e. it does not hold hidden orders only.
+    #[inline]
+    fn is_displayed(&self, px: &PriceLevel) -> bool {
+        let level = self.level_pool.get(px.level_id()).unwrap();
+        !level.size().is_empty() || level.hidden().is_empty()
+    }
+
+    /// Returns the highest displayed bid level, if any.
+    #[inline]
+    pub fn best_bid(&self) -> Option<DepthLevel> {
+        self.bids
+            .iter_best_first()
+            .find(|px| self.is_displayed(px))
+            .map(|px| self.depth_level(px))
+    }
+
+    /// Returns the lowest displayed ask level, if any.
+    #[inline]
+    pub fn best_ask(&self) -> Option<DepthLevel> {
+        self.asks
+            .iter_best_first()
+            .find(|px| self.is_displayed(px))
+            .map(|px| self.depth_level(px))
+    }
+
+    /// Returns the difference between the best ask and the best bid prices.
+    /// Returns None if either side is empty or the book is crossed.
+    #[inline]
+    pub fn spread(&self) -> Option<u32> {
+        let bid = self.best_bid()?;
+        let ask = self.best_ask()?;
+        ask.price.checked_sub(bid.price)
+    }
+
+    /// Returns the average of the best bid and ask prices, in Price(4) units.
+    /// Returns None if either side is empty.
+    #[inline]
+    pub fn mid(&self) -> Option<f64> {
+        let bid = self.best_bid()?;
+        let ask = self.best_ask()?;
+        Some((bid.price as f64 + ask.price as f64) / 2.0)
+    }
+
+    /// Returns up to `n` displayed levels of one side, from the best to the worst price.
+    /// Levels holding hidden orders only are skipped.
+    #[inline]
+    pub fn depth(&self, side: Side, n: usize) -> Vec<DepthLevel> {
+        self.levels(side)
+            .iter_best_first()
+            .filter(|px| self.is_displayed(px))
+            .take(n)
+            .map(|px| self.depth_level(px))
+            .collect()
+    }
+
+    /// Returns the level at the given unsigned price on one side, if a displayed order rests there.
+    #[inline]
+    pub fn level_at(&self, side: Side, price: u32) -> Option<DepthLevel> {
+        let price = Price::from_u32(price, side.is_bid());
+        self.levels(side)
+            .find(price)
+            .filter(|px| self.is_displayed(px))
+            .map(|px| self.depth_level(px))
+    }
+}
+
+impl Snapshot for OrderBook {
+    fn encode(&self, out: &mut Vec<u8>) {
+        self.bids.encode(out);
+        self.asks.encode(out);
+        self.level_pool.encode(out);
+    }
+
+    fn decode(reader: &mut Reader<'_>) -> Result<Self, SnapshotError> {
+        let book = Self {
+            bids: reader.read()?,
+            asks: reader.read()?,
+            level_pool: reader.read()?,
+        };
+        let pool_len = book.level_pool.len();
+        if book
+            .bids
+            .iter_best_first()
+            .chain(book.asks.iter_best_first())
+            .any(|px| px.level_id().value() as usize >= pool_len)
+        {
+            return Err(SnapshotError::Invalid("level id"));
         }
+        Ok(book)
     }
 }
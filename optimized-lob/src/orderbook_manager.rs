@@ -1,23 +1,46 @@
 // orderbook_manager.rs
 
 use crate::{
+    allocation::AllocationPolicy,
+    book::Book,
+    error::OrderError,
+    events::{level_state, report_level, EventSink, NoopSink},
+    l3_orderbook::{L3OrderBook, QueuePosition},
     level::LevelId,
+    oid_map::OrderIdMap,
     order::{OidMap, Order, OrderId},
     orderbook::OrderBook,
     price::Price,
     quantity::Qty,
+    queue::OrderAttributes,
+    side::Side,
     utils::{BookId, MAX_BOOKS},
 };
 
 /// Manages multiple order books and orders.
-pub struct OrderBookManager {
-    pub books: Vec<Option<OrderBook>>, // A mapping of book IDs to order books.
-    pub oid_map: OidMap,               // A mapping of order IDs to order objects.
+/// The order ID map and the book type are type parameters: a dense `OidMap` and an aggregate
+/// `OrderBook` by default, or e.g. an `L3OrderBook` to keep every order in a FIFO queue.
+/// The changes made to the books are reported to an `EventSink`, by default the `NoopSink`.
+pub struct OrderBookManager<M: OrderIdMap = OidMap, B: Book = OrderBook, E: EventSink = NoopSink> {
+    pub books: Vec<Option<B>>, // A mapping of book IDs to order books.
+    pub oid_map: M,            // A mapping of order IDs to order objects.
+    pub events: E,             // Receives the changes made to the books.
 }
 
-impl Default for OrderBookManager {
+/// The best bid and ask of a book, each as its price and displayed quantity.
+type Bbo = (Option<(u32, Qty)>, Option<(u32, Qty)>);
+
+/// The state of a level and of the best prices of a book before a change, to report what
+/// the change did to the event sink.
+#[derive(Clone, Copy)]
+struct Observed {
+    level: (Qty, u32),
+    bbo: Bbo,
+}
+
+impl<M: OrderIdMap, B: Book, E: EventSink + Default> Default for OrderBookManager<M, B, E> {
     fn default() -> Self {
-        Self::new()
+        Self::from_oid_map(M::default())
     }
 }
 
@@ -25,12 +48,201 @@ impl OrderBookManager {
     /// Creates a new OrderBookManager with empty books and an OidMap.
     #[inline]
     pub fn new() -> Self {
+        Self::with_oid_map(OidMap::new())
+    }
+}
+
+impl OrderBookManager<OidMap, L3OrderBook> {
+    /// Creates a new OrderBookManager of order-by-order (L3) books and an OidMap.
+    /// ## Example:
+    /// ```
+    /// let mut orderbook_manager = OrderBookManager::new_l3();
+    /// ```
+    #[inline]
+    pub fn new_l3() -> Self {
+        Self::from_oid_map(OidMap::new())
+    }
+}
+
+impl<M: OrderIdMap> OrderBookManager<M> {
+    /// Creates a new OrderBookManager with empty books and the given order ID map.
+    /// ## Arguments:
+    /// - `oid_map`: The map used to locate orders by ID, e.g. a `HashOidMap` for sparse IDs.
+    ///
+    /// ## Example:
+    /// ```
+    /// let mut orderbook_manager = OrderBookManager::with_oid_map(HashOidMap::new());
+    /// ```
+    #[inline]
+    pub fn with_oid_map(oid_map: M) -> Self {
+        Self::from_oid_map(oid_map)
+    }
+}
+
+impl<M: OrderIdMap, A: AllocationPolicy, E: EventSink> OrderBookManager<M, L3OrderBook<A>, E> {
+    /// Returns the position of an order in the FIFO queue of its price level.
+    /// ## Arguments:
+    /// - `order_id`: The order ID for the order. Represented as unique reference number.
+    /// ## Example:
+    /// ```
+    /// let mut orderbook_manager = OrderBookManager::new_l3();
+    ///
+    /// let position = orderbook_manager.queue_position(OrderId(0));
+    /// ```
+    #[inline]
+    pub fn queue_position(&self, order_id: OrderId) -> Option<QueuePosition> {
+        let order = self.oid_map.get(order_id)?;
+        self.book(order.book_id())?.queue_position(order_id)
+    }
+
+    /// Adds a hidden order, which rests and trades like any other order but whose quantity is
+    /// never displayed in the level size.
+    /// ## Arguments:
+    /// - `order_id`: The order ID for the order. Represented as unique reference number.
+    /// - `book_id`: The identifier for the book where the order will be placed. Represents as stock locate.
+    /// - `qty`: The quantity of the order. Represented as shares in the orderbook.
+    /// - `price32`: The price of the order as a 32-bit unsigned integer. Return the Price(4) in the orderbook.
+    /// - `is_bid`: A flag indicating whether the order is a bid (true) or ask (false).
+    ///
+    /// ## Example:
+    /// ```
+    /// let mut orderbook_manager = OrderBookManager::new_l3();
+    ///
+    /// orderbook_manager.add_hidden_order(OrderId(0), BookId(0), Qty(100), 600, true);
+    /// ```
+    #[inline]
+    pub fn add_hidden_order(
+        &mut self,
+        order_id: OrderId,
+        book_id: BookId,
+        qty: Qty,
+        price32: u32,
+        is_bid: bool,
+    ) {
+        let order = Order::new(qty, LevelId(0), book_id);
+        let attributes = OrderAttributes {
+            fully_hidden: true,
+            ..OrderAttributes::default()
+        };
+        self.insert_order(order_id, order, price32, is_bid, attributes);
+    }
+
+    /// Adds an iceberg order, which displays at most `peak` shares at a time and keeps the
+    /// rest of its quantity as a hidden reserve. Once the displayed shares are executed, the
+    /// next `peak` shares are displayed at the back of the queue of the level.
+    /// ## Arguments:
+    /// - `order_id`: The order ID for the order. Represented as unique reference number.
+    /// - `book_id`: The identifier for the book where the order will be placed. Represents as stock locate.
+    /// - `qty`: The total quantity of the order, displayed and hidden.
+    /// - `peak`: The quantity displayed at a time. An order with a `peak` of zero or of at least `qty` is fully displayed.
+    /// - `price32`: The price of the order as a 32-bit unsigned integer. Return the Price(4) in the orderbook.
+    /// - `is_bid`: A flag indicating whether the order is a bid (true) or ask (false).
+    ///
+    /// ## Example:
+    /// ```
+    /// let mut orderbook_manager = OrderBookManager::new_l3();
+    ///
+    /// orderbook_manager.add_iceberg_order(
+    ///     OrderId(0), // Order ID
+    ///     BookId(0), // Book ID
+    ///     Qty(1000), // Total quantity
+    ///     Qty(100), // Displayed quantity
+    ///     600, // Price
+    ///     true, // Is Bid
+    /// );
+    /// ```
+    #[inline]
+    pub fn add_iceberg_order(
+        &mut self,
+        order_id: OrderId,
+        book_id: BookId,
+        qty: Qty,
+        peak: Qty,
+        price32: u32,
+        is_bid: bool,
+    ) {
+        if peak.is_empty() || peak >= qty {
+            self.add_order(order_id, book_id, qty, price32, is_bid);
+            return;
+        }
+        let mut hidden = qty;
+        hidden -= peak;
+        let order = Order::new(peak, LevelId(0), book_id);
+        let attributes = OrderAttributes {
+            peak,
+            hidden,
+            ..OrderAttributes::default()
+        };
+        self.insert_order(order_id, order, price32, is_bid, attributes);
+    }
+}
+
+impl<M: OrderIdMap, B: Book, E: EventSink + Default> OrderBookManager<M, B, E> {
+    /// Creates a new OrderBookManager with empty books of any book type and the given order ID
+    /// map. The book type is usually given by a type annotation.
+    /// ## Arguments:
+    /// - `oid_map`: The map used to locate orders by ID.
+    ///
+    /// ## Example:
+    /// ```
+    /// let mut orderbook_manager: OrderBookManager<HashOidMap, L3OrderBook> =
+    ///     OrderBookManager::from_oid_map(HashOidMap::new());
+    /// ```
+    #[inline]
+    pub fn from_oid_map(oid_map: M) -> Self {
+        Self::with_events(oid_map, E::default())
+    }
+}
+
+impl<M: OrderIdMap, B: Book, E: EventSink> OrderBookManager<M, B, E> {
+    /// Creates a new OrderBookManager with empty books, the given order ID map, and a sink
+    /// that receives every change made to the books.
+    /// ## Arguments:
+    /// - `oid_map`: The map used to locate orders by ID.
+    /// - `events`: The event sink.
+    ///
+    /// ## Example:
+    /// ```
+    /// let mut orderbook_manager: OrderBookManager<OidMap, OrderBook, MySink> =
+    ///     OrderBookManager::with_events(OidMap::new(), MySink::new());
+    /// ```
+    #[inline]
+    pub fn with_events(oid_map: M, events: E) -> Self {
         Self {
             books: vec![None; MAX_BOOKS],
-            oid_map: OidMap::new(),
+            oid_map,
+            events,
         }
     }
 
+    /// Returns the order book of the given book ID, if it has seen any order.
+    /// ## Arguments:
+    /// - `book_id`: The identifier for the book. Represents as stock locate.
+    /// ## Example:
+    /// ```
+    /// let orderbook_manager = OrderBookManager::new();
+    ///
+    /// let best_bid = orderbook_manager.book(BookId(1)).and_then(|book| book.best_bid());
+    /// ```
+    #[inline]
+    pub fn book(&self, book_id: BookId) -> Option<&B> {
+        self.books.get(book_id.value() as usize)?.as_ref()
+    }
+
+    /// Clears every book and order, e.g. at the boundary between two trading sessions.
+    /// No event is reported for the orders cleared.
+    /// ## Example:
+    /// ```
+    /// let mut orderbook_manager = OrderBookManager::new();
+    ///
+    /// orderbook_manager.reset();
+    /// ```
+    #[inline]
+    pub fn reset(&mut self) {
+        self.books.iter_mut().for_each(|book| *book = None);
+        self.oid_map = M::default();
+    }
+
     /// Adds a new order to the order book based on the provided parameters.
     /// ## Arguments:
     /// - `order_id`: The order ID for the order. Represented as unique reference number.
@@ -59,6 +271,21 @@ impl OrderBookManager {
         qty: Qty,
         price32: u32,
         is_bid: bool,
+    ) {
+        let order = Order::new(qty, LevelId(0), book_id);
+        self.insert_order(order_id, order, price32, is_bid, B::Attributes::default());
+    }
+
+    /// Adds a new order to its book, with the attributes the book keeps for it, and to the
+    /// order ID map.
+    #[inline]
+    pub(crate) fn insert_order(
+        &mut self,
+        order_id: OrderId,
+        mut order: Order,
+        price32: u32,
+        is_bid: bool,
+        attributes: B::Attributes,
     ) {
         let price_i32 = if is_bid {
             price32 as i32
@@ -68,19 +295,27 @@ impl OrderBookManager {
 
         // Create a Price(i32) from the adjusted price_i32.
         let price = Price(price_i32);
+        let book_id = order.book_id();
+        let qty = order.qty();
 
         self.oid_map.reserve(order_id);
 
-        let mut order = Order::new(qty, LevelId(0), book_id);
-
         // Check if the book for the given book_id exists; if not, create it.
         if self.books[book_id.value() as usize].is_none() {
-            self.books[book_id.value() as usize] = Some(OrderBook::new());
+            self.books[book_id.value() as usize] = Some(B::default());
         }
+        let before = self.observe(book_id, price);
         if let Some(orderbook) = self.books.get_mut(book_id.value() as usize).unwrap() {
-            orderbook.add_order(&mut order, price, qty);
+            orderbook.add_order(order_id, &mut order, price, qty, attributes);
         }
         self.oid_map.insert(order_id, &order);
+        if let Some(before) = before {
+            let side = Side::from_is_bid(is_bid);
+            self.events
+                .on_order_added(book_id, order_id, side, price32, qty);
+            self.report(book_id, price, before);
+        }
+        self.reprice_pegs(book_id);
     }
 
     /// Removes an order from the order book based on its order ID.
@@ -94,16 +329,31 @@ impl OrderBookManager {
     /// ```
     #[inline]
     pub fn remove_order(&mut self, order_id: OrderId) {
-        if let Some(order) = self.oid_map.get_mut(order_id) {
-            if let Some(orderbook) = self
-                .books
-                .get_mut(order.book_id().value() as usize)
-                .unwrap()
-            {
-                orderbook.remove_order(order);
-            }
+        if let Some((book_id, _)) = self.discard_order(order_id) {
+            self.reprice_pegs(book_id);
         }
+    }
+
+    /// Removes an order like `remove_order`, without repricing the pegged orders of its book.
+    ///
+    /// # Returns
+    /// Returns the book of the order and the attributes it had there, or None if the order is
+    /// unknown.
+    #[inline]
+    pub(crate) fn discard_order(&mut self, order_id: OrderId) -> Option<(BookId, B::Attributes)> {
+        let before = self.observe_order(order_id);
+        let order = self.oid_map.get_mut(order_id)?;
+        let book_id = order.book_id();
+        let attributes = match self.books.get_mut(book_id.value() as usize).unwrap() {
+            Some(orderbook) => orderbook.remove_order(order_id, order),
+            None => B::Attributes::default(),
+        };
         self.oid_map.remove(order_id);
+        if let Some((price, before)) = before {
+            self.events.on_order_removed(book_id, order_id);
+            self.report(book_id, price, before);
+        }
+        Some((book_id, attributes))
     }
 
     /// Cancels an order by reducing its quantity in the order book.
@@ -118,19 +368,24 @@ impl OrderBookManager {
     /// ```
     #[inline]
     pub fn cancel_order(&mut self, order_id: OrderId, qty: Qty) {
+        let before = self.observe_order(order_id);
         if let Some(order) = self.oid_map.get_mut(order_id) {
-            if let Some(orderbook) = self
-                .books
-                .get_mut(order.book_id().value() as usize)
-                .unwrap()
-            {
-                orderbook.reduce_order(order, qty);
+            let book_id = order.book_id();
+            if let Some(orderbook) = self.books.get_mut(book_id.value() as usize).unwrap() {
+                orderbook.reduce_order(order_id, order, qty);
+            }
+            self.oid_map.update_qty(order_id, qty);
+            if let Some((price, before)) = before {
+                self.report_reduced(book_id, order_id, qty);
+                self.report(book_id, price, before);
             }
+            self.reprice_pegs(book_id);
         }
-        self.oid_map.update_qty(order_id, qty);
     }
 
     /// Executes an order by either removing it completely or reducing its quantity.
+    /// An iceberg order whose displayed quantity is executed completely is replenished from its
+    /// hidden reserve instead of being removed, and loses its time priority.
     /// ## Arguments:
     /// - `order_id`: The order ID for the order. Represented as unique reference number.
     /// - `qty`: The quantity of the order to be executed. Represented as shares in the orderbook.
@@ -142,30 +397,231 @@ impl OrderBookManager {
     /// ```
     #[inline]
     pub fn execute_order(&mut self, order_id: OrderId, qty: Qty) {
-        if let Some(order) = self.oid_map.get_mut(order_id) {
-            if order.qty() == qty {
-                if let Some(orderbook) = self
-                    .books
-                    .get_mut(order.book_id().value() as usize)
-                    .unwrap()
-                {
-                    orderbook.remove_order(order);
-                }
-                self.oid_map.remove(order_id);
-            } else {
-                if let Some(orderbook) = self
-                    .books
-                    .get_mut(order.book_id().value() as usize)
-                    .unwrap()
-                {
-                    orderbook.reduce_order(order, qty);
-                }
-                self.oid_map.update_qty(order_id, qty);
+        if let Some(book_id) = self.trade_order(order_id, qty) {
+            self.reprice_pegs(book_id);
+        }
+    }
+
+    /// Executes an order like `fill_order`, and reports the trade to the event sink.
+    ///
+    /// # Returns
+    /// Returns the book of the order, or None if the order is unknown.
+    #[inline]
+    pub(crate) fn trade_order(&mut self, order_id: OrderId, qty: Qty) -> Option<BookId> {
+        if E::ENABLED {
+            let order = self.oid_map.get(order_id)?;
+            let book_id = order.book_id();
+            if let Some(book) = self.book(book_id) {
+                let price = book.order_price(order);
+                let side = Side::from_is_bid(price.is_bid());
+                self.events
+                    .on_trade(book_id, order_id, side, price.to_u32(), qty);
+            }
+        }
+        self.fill_order(order_id, qty)
+    }
+
+    /// Executes an order like `execute_order`, without repricing the pegged orders of its book.
+    /// The matching engine reprices them once the whole incoming order is matched.
+    ///
+    /// # Returns
+    /// Returns the book of the order, or None if the order is unknown.
+    #[inline]
+    pub(crate) fn fill_order(&mut self, order_id: OrderId, qty: Qty) -> Option<BookId> {
+        let before = self.observe_order(order_id);
+        let order = self.oid_map.get_mut(order_id)?;
+        let book_id = order.book_id();
+        let orderbook = self.books.get_mut(book_id.value() as usize).unwrap();
+        let replenished = order.qty() == qty
+            && orderbook
+                .as_mut()
+                .is_some_and(|orderbook| orderbook.replenish_order(order_id, order));
+        if replenished {
+            if let Some((price, _)) = before {
+                let side = Side::from_is_bid(price.is_bid());
+                self.events.on_order_removed(book_id, order_id);
+                self.events
+                    .on_order_added(book_id, order_id, side, price.to_u32(), order.qty());
+            }
+        } else if order.qty() == qty {
+            if let Some(orderbook) = orderbook {
+                orderbook.remove_order(order_id, order);
+            }
+            self.oid_map.remove(order_id);
+            if before.is_some() {
+                self.events.on_order_removed(book_id, order_id);
+            }
+        } else {
+            if let Some(orderbook) = orderbook {
+                orderbook.reduce_order(order_id, order, qty);
+            }
+            self.oid_map.update_qty(order_id, qty);
+            if before.is_some() {
+                self.report_reduced(book_id, order_id, qty);
+            }
+        }
+        if let Some((price, before)) = before {
+            self.report(book_id, price, before);
+        }
+        Some(book_id)
+    }
+
+    /// Reprices the pegged orders of a book after its best prices may have changed.
+    #[inline]
+    pub(crate) fn reprice_pegs(&mut self, book_id: BookId) {
+        let before = E::ENABLED.then(|| self.bbo_state(book_id));
+        if let Some(Some(book)) = self.books.get_mut(book_id.value() as usize) {
+            book.reprice_pegs(book_id, &mut self.oid_map, &mut self.events);
+        }
+        if let Some(before) = before {
+            self.report_bbo(book_id, before);
+        }
+    }
+
+    /// Records the state of a level and of the best prices of a book before a change.
+    /// Returns None if the event sink is disabled.
+    #[inline]
+    fn observe(&self, book_id: BookId, price: Price) -> Option<Observed> {
+        if !E::ENABLED {
+            return None;
+        }
+        let side = Side::from_is_bid(price.is_bid());
+        let level = self
+            .book(book_id)
+            .map_or((Qty(0), 0), |book| level_state(book, side, price.to_u32()));
+        Some(Observed {
+            level,
+            bbo: self.bbo_state(book_id),
+        })
+    }
+
+    /// Records the state of the level of an order and of the best prices of its book before
+    /// a change. Returns None if the event sink is disabled or the order is unknown.
+    #[inline]
+    fn observe_order(&self, order_id: OrderId) -> Option<(Price, Observed)> {
+        if !E::ENABLED {
+            return None;
+        }
+        let order = self.oid_map.get(order_id)?;
+        let price = self.book(order.book_id())?.order_price(order);
+        Some((price, self.observe(order.book_id(), price)?))
+    }
+
+    /// Returns the best bid and ask of a book, each as its price and displayed quantity.
+    #[inline]
+    fn bbo_state(&self, book_id: BookId) -> Bbo {
+        self.book(book_id).map_or((None, None), |book| {
+            (
+                book.best_bid().map(|level| (level.price, level.qty)),
+                book.best_ask().map(|level| (level.price, level.qty)),
+            )
+        })
+    }
+
+    /// Reports a reduction of `qty` of an order to the event sink.
+    #[inline]
+    fn report_reduced(&mut self, book_id: BookId, order_id: OrderId, qty: Qty) {
+        let remaining = self
+            .oid_map
+            .get(order_id)
+            .map_or(Qty(0), |order| order.qty());
+        self.events
+            .on_order_reduced(book_id, order_id, qty, remaining);
+    }
+
+    /// Reports the changes of the level at `price` and of the best prices of a book since
+    /// they were observed.
+    #[inline]
+    fn report(&mut self, book_id: BookId, price: Price, before: Observed) {
+        if let Some(Some(book)) = self.books.get(book_id.value() as usize) {
+            let side = Side::from_is_bid(price.is_bid());
+            report_level(
+                book,
+                book_id,
+                side,
+                price.to_u32(),
+                before.level,
+                &mut self.events,
+            );
+        }
+        self.report_bbo(book_id, before.bbo);
+    }
+
+    /// Reports a change of the best prices of a book since they were observed.
+    #[inline]
+    fn report_bbo(&mut self, book_id: BookId, before: Bbo) {
+        let (bid, ask) = self.bbo_state(book_id);
+        if (bid, ask) != before {
+            self.events.on_bbo_changed(book_id, bid, ask);
+        }
+    }
+
+    /// Modifies the quantity and price of a resting order, keeping its ID, book and side.
+    /// A quantity decrease at the same price keeps the time priority of the order. A price
+    /// change or a quantity increase moves the order to the back of the queue at its new
+    /// price. The hidden reserve of an iceberg order is kept; a pegged order that loses its
+    /// priority becomes a plain limit order.
+    /// ## Arguments:
+    /// - `order_id`: The order ID for the order to be modified. Represented as unique reference number.
+    /// - `new_qty`: The new displayed quantity of the order. Represented as shares in the orderbook.
+    /// - `new_price`: The new price of the order as a 32-bit unsigned integer. Return the Price(4) in the orderbook.
+    ///
+    /// # Returns
+    /// Returns `OrderError::UnknownOrder` if no order with this ID rests in a book, and
+    /// `OrderError::ZeroQuantity` if `new_qty` is zero. The book is unchanged in both cases.
+    ///
+    /// ## Example:
+    /// ```
+    /// let mut orderbook_manager = OrderBookManager::new();
+    ///
+    /// orderbook_manager.add_order(OrderId(0), BookId(0), Qty(200), 500, true);
+    /// orderbook_manager.modify_order(
+    ///     OrderId(0), // Order ID
+    ///     Qty(100), // New quantity
+    ///     500, // New price
+    /// )?;
+    /// ```
+    pub fn modify_order(
+        &mut self,
+        order_id: OrderId,
+        new_qty: Qty,
+        new_price: u32,
+    ) -> Result<(), OrderError> {
+        let order = self
+            .oid_map
+            .get(order_id)
+            .ok_or(OrderError::UnknownOrder(order_id))?;
+        if new_qty.is_empty() {
+            return Err(OrderError::ZeroQuantity);
+        }
+        let book_id = order.book_id();
+        let price = match &self.books[book_id.value() as usize] {
+            Some(book) => book.order_price(order),
+            None => return Err(OrderError::UnknownOrder(order_id)),
+        };
+        let is_bid = price.is_bid();
+
+        if Price::from_u32(new_price, is_bid) == price && new_qty <= order.qty() {
+            let mut cut = order.qty();
+            cut -= new_qty;
+            if !cut.is_empty() {
+                self.cancel_order(order_id, cut);
             }
+            return Ok(());
         }
+
+        let mut order = order.clone();
+        let attributes = self
+            .discard_order(order_id)
+            .map_or_else(B::Attributes::default, |(_, attributes)| attributes);
+        order.set_qty(new_qty);
+        self.insert_order(order_id, order, new_price, is_bid, attributes);
+        Ok(())
     }
 
     /// Replaces an existing order with a new order based on order IDs and new parameters.
+    /// The new order keeps the book and side of the old one, and loses its time priority.
+    /// Nothing is added if the old order is unknown.
     /// ## Arguments:
     /// - `order_id`: The order ID for the order to be replaced. Represented as Original unique reference number.
     /// - `new_order_id`: The new order ID for the order that has to be replaced. Represented as the new unique reference number.
@@ -191,26 +647,15 @@ impl OrderBookManager {
         new_qty: Qty,
         new_price: u32,
     ) {
-        let order = self.oid_map.get_mut(order_id);
-        let mut is_bid = true;
-        let mut book_id = BookId(0);
-        if let Some(order) = order {
-            if let Some(book) = self
-                .books
-                .get_mut(order.book_id().value() as usize)
-                .unwrap()
-            {
-                is_bid = book
-                    .level_pool
-                    .get(order.level_id())
-                    .unwrap()
-                    .price()
-                    .is_bid();
-                book_id = order.book_id();
-                book.remove_order(order);
-            }
-            self.oid_map.remove(order_id);
+        let Some(order) = self.oid_map.get(order_id) else {
+            return;
+        };
+        let Some(book) = self.book(order.book_id()) else {
+            return;
+        };
+        let is_bid = book.order_price(order).is_bid();
+        if let Some((book_id, _)) = self.discard_order(order_id) {
+            self.add_order(new_order_id, book_id, new_qty, new_price, is_bid);
         }
-        self.add_order(new_order_id, book_id, new_qty, new_price, is_bid);
     }
 }
@@ -0,0 +1,325 @@
+// peg.rs
+
+use crate::{
+    allocation::AllocationPolicy,
+    error::SnapshotError,
+    events::EventSink,
+    l3_orderbook::L3OrderBook,
+    oid_map::OrderIdMap,
+    order::OrderId,
+    orderbook_manager::OrderBookManager,
+    price::Price,
+    quantity::Qty,
+    side::Side,
+    snapshot::{Reader, Snapshot},
+    utils::BookId,
+};
+use std::collections::HashMap;
+
+/// The reference price a pegged order follows.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PegType {
+    /// The best price of the order's own side.
+    Primary,
+    /// The average of the best bid and ask prices.
+    Midpoint,
+    /// The best price of the opposite side.
+    Market,
+}
+
+/// How the price of a pegged order is derived from the best bid and ask of its book.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PegSpec {
+    pub peg_type: PegType,  // The reference price to follow.
+    pub offset: i32,        // Added to the reference price, in Price(4) units.
+    pub limit: Option<u32>, // Highest price of a bid, lowest price of an ask.
+}
+
+impl PegSpec {
+    /// Creates a peg to a reference price, without offset or limit.
+    #[inline]
+    pub fn new(peg_type: PegType) -> Self {
+        Self {
+            peg_type,
+            offset: 0,
+            limit: None,
+        }
+    }
+
+    /// Sets the offset added to the reference price.
+    #[inline]
+    pub fn with_offset(self, offset: i32) -> Self {
+        Self { offset, ..self }
+    }
+
+    /// Sets the price cap of the order.
+    #[inline]
+    pub fn with_limit(self, limit: u32) -> Self {
+        Self {
+            limit: Some(limit),
+            ..self
+        }
+    }
+
+    /// Returns the price of a pegged order of the given side, from the best bid and ask of the
+    /// non-pegged orders of its book. Returns None if a reference price is missing.
+    ///
+    /// A midpoint that falls between two Price(4) units is rounded away from the opposite
+    /// side. The price never reaches the opposite best price, so a repriced order never takes
+    /// liquidity.
+    pub fn price(&self, side: Side, bid: Option<u32>, ask: Option<u32>) -> Option<u32> {
+        let reference = match (self.peg_type, side) {
+            (PegType::Primary, Side::Bid) | (PegType::Market, Side::Ask) => bid? as i64,
+            (PegType::Primary, Side::Ask) | (PegType::Market, Side::Bid) => ask? as i64,
+            (PegType::Midpoint, Side::Bid) => (bid? as i64 + ask? as i64) / 2,
+            (PegType::Midpoint, Side::Ask) => (bid? as i64 + ask? as i64 + 1) / 2,
+        };
+        let mut price = reference + self.offset as i64;
+        match side {
+            Side::Bid => {
+                if let Some(limit) = self.limit {
+                    price = price.min(limit as i64);
+                }
+                if let Some(ask) = ask {
+                    price = price.min(ask as i64 - 1);
+                }
+            }
+            Side::Ask => {
+                if let Some(limit) = self.limit {
+                    price = price.max(limit as i64);
+                }
+                if let Some(bid) = bid {
+                    price = price.max(bid as i64 + 1);
+                }
+            }
+        }
+        if price < 1 || price > i32::MAX as i64 {
+            return None;
+        }
+        Some(price as u32)
+    }
+}
+
+/// A pegged order resting in a book.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PeggedOrder {
+    pub order_id: OrderId, // The resting order.
+    pub side: Side,        // The side of the order.
+    pub spec: PegSpec,     // How its price follows the book.
+    pub price: u32,        // The price it currently rests at.
+}
+
+/// The pegged orders of a book, in arrival order, and the number of them at each price.
+#[derive(Debug, Default, Clone)]
+pub struct PegBook {
+    orders: Vec<PeggedOrder>,
+    counts: HashMap<Price, u32>,
+}
+
+impl PegBook {
+    /// Creates a new, empty PegBook.
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the number of pegged orders.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.orders.len()
+    }
+
+    /// Returns true if the book has no pegged order.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.orders.is_empty()
+    }
+
+    /// Iterates over the pegged orders in arrival order.
+    #[inline]
+    pub fn iter(&self) -> impl Iterator<Item = &PeggedOrder> {
+        self.orders.iter()
+    }
+
+    /// Gets a pegged order by its ID.
+    #[inline]
+    pub fn get(&self, order_id: OrderId) -> Option<&PeggedOrder> {
+        self.orders.iter().find(|peg| peg.order_id == order_id)
+    }
+
+    /// Returns the number of pegged orders resting at a signed price.
+    #[inline]
+    pub fn count_at(&self, price: Price) -> u32 {
+        self.counts.get(&price).copied().unwrap_or(0)
+    }
+
+    /// Adds a pegged order behind the others.
+    #[inline]
+    pub fn insert(&mut self, peg: PeggedOrder) {
+        *self.counts.entry(Self::key(&peg)).or_default() += 1;
+        self.orders.push(peg);
+    }
+
+    /// Removes a pegged order by its ID.
+    pub fn remove(&mut self, order_id: OrderId) -> Option<PeggedOrder> {
+        let idx = self
+            .orders
+            .iter()
+            .position(|peg| peg.order_id == order_id)?;
+        let peg = self.orders.remove(idx);
+        self.uncount(&peg);
+        Some(peg)
+    }
+
+    /// Records that the pegged order at `idx` in arrival order now rests at `price`.
+    #[inline]
+    pub(crate) fn set_price(&mut self, idx: usize, price: u32) {
+        let peg = self.orders[idx];
+        self.uncount(&peg);
+        self.orders[idx].price = price;
+        *self.counts.entry(Self::key(&self.orders[idx])).or_default() += 1;
+    }
+
+    /// Returns the pegged order at `idx` in arrival order.
+    #[inline]
+    pub(crate) fn at(&self, idx: usize) -> PeggedOrder {
+        self.orders[idx]
+    }
+
+    #[inline]
+    fn key(peg: &PeggedOrder) -> Price {
+        Price::from_u32(peg.price, peg.side.is_bid())
+    }
+
+    #[inline]
+    fn uncount(&mut self, peg: &PeggedOrder) {
+        let key = Self::key(peg);
+        if let Some(count) = self.counts.get_mut(&key) {
+            *count -= 1;
+            if *count == 0 {
+                self.counts.remove(&key);
+            }
+        }
+    }
+}
+
+impl Snapshot for PegType {
+    fn encode(&self, out: &mut Vec<u8>) {
+        let tag: u8 = match self {
+            PegType::Primary => 0,
+            PegType::Midpoint => 1,
+            PegType::Market => 2,
+        };
+        tag.encode(out);
+    }
+
+    fn decode(reader: &mut Reader<'_>) -> Result<Self, SnapshotError> {
+        Ok(match reader.read::<u8>()? {
+            0 => PegType::Primary,
+            1 => PegType::Midpoint,
+            2 => PegType::Market,
+            _ => return Err(SnapshotError::Invalid("peg type")),
+        })
+    }
+}
+
+impl Snapshot for PegSpec {
+    fn encode(&self, out: &mut Vec<u8>) {
+        self.peg_type.encode(out);
+        self.offset.encode(out);
+        self.limit.encode(out);
+    }
+
+    fn decode(reader: &mut Reader<'_>) -> Result<Self, SnapshotError> {
+        Ok(Self {
+            peg_type: reader.read()?,
+            offset: reader.read()?,
+            limit: reader.read()?,
+        })
+    }
+}
+
+impl Snapshot for PeggedOrder {
+    fn encode(&self, out: &mut Vec<u8>) {
+        self.order_id.encode(out);
+        self.side.encode(out);
+        self.spec.encode(out);
+        self.price.encode(out);
+    }
+
+    fn decode(reader: &mut Reader<'_>) -> Result<Self, SnapshotError> {
+        Ok(Self {
+            order_id: reader.read()?,
+            side: reader.read()?,
+            spec: reader.read()?,
+            price: reader.read()?,
+        })
+    }
+}
+
+/// Only the pegged orders are written. The counts per price are rebuilt from them.
+impl Snapshot for PegBook {
+    fn encode(&self, out: &mut Vec<u8>) {
+        self.orders.encode(out);
+    }
+
+    fn decode(reader: &mut Reader<'_>) -> Result<Self, SnapshotError> {
+        let mut pegs = Self::new();
+        for peg in reader.read::<Vec<PeggedOrder>>()? {
+            pegs.insert(peg);
+        }
+        Ok(pegs)
+    }
+}
+
+impl<M: OrderIdMap, A: AllocationPolicy, E: EventSink> OrderBookManager<M, L3OrderBook<A>, E> {
+    /// Adds a pegged order, whose price follows the best bid and ask of the non-pegged orders
+    /// of its book. Pegged orders are repriced whenever those prices change, and lose their
+    /// time priority when they move to another level.
+    /// ## Arguments:
+    /// - `order_id`: The order ID for the order. Represented as unique reference number.
+    /// - `book_id`: The identifier for the book where the order will be placed.
+    /// - `side`: The side of the order.
+    /// - `qty`: The quantity of the order.
+    /// - `spec`: The reference price, offset and limit of the order.
+    ///
+    /// # Returns
+    /// Returns the price the order rests at, or None if the book has no reference price for
+    /// it. The order is not added in that case.
+    ///
+    /// ## Example:
+    /// ```
+    /// let mut orderbook_manager = OrderBookManager::new_l3();
+    ///
+    /// orderbook_manager.add_order(OrderId(0), BookId(0), Qty(100), 600, true);
+    /// orderbook_manager.add_order(OrderId(1), BookId(0), Qty(100), 610, false);
+    /// let price = orderbook_manager.add_pegged_order(
+    ///     OrderId(2), // Order ID
+    ///     BookId(0), // Book ID
+    ///     Side::Bid, // Side
+    ///     Qty(100), // Quantity
+    ///     PegSpec::new(PegType::Midpoint), // Peg
+    /// );
+    /// assert_eq!(price, Some(605));
+    /// ```
+    pub fn add_pegged_order(
+        &mut self,
+        order_id: OrderId,
+        book_id: BookId,
+        side: Side,
+        qty: Qty,
+        spec: PegSpec,
+    ) -> Option<u32> {
+        let book = self.books[book_id.value() as usize].get_or_insert_with(L3OrderBook::default);
+        let (bid, ask) = book.peg_reference();
+        let price = spec.price(side, bid, ask)?;
+        book.pegs.insert(PeggedOrder {
+            order_id,
+            side,
+            spec,
+            price,
+        });
+        self.add_order(order_id, book_id, qty, price, side.is_bid());
+        Some(price)
+    }
+}
@@ -1,7 +1,11 @@
 // pool.rs
 
 // Import the Level and LevelId structs from the level module.
-use crate::level::{Level, LevelId};
+use crate::{
+    error::SnapshotError,
+    level::{Level, LevelId},
+    snapshot::{Reader, Snapshot},
+};
 
 // Define a struct named LevelPool, which is a pool for managing Level objects.
 #[derive(Default, Clone)]
@@ -75,4 +79,36 @@ impl LevelPool {
         let idx = idx.value() as usize;
         self.allocated[idx] = level
     }
+
+    // Returns the number of levels ever allocated, free or not.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.allocated.len()
+    }
+
+    // Returns true if no level was ever allocated.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.allocated.is_empty()
+    }
+}
+
+// The free list is kept in order, so a restored pool hands out the same LevelIds.
+impl Snapshot for LevelPool {
+    fn encode(&self, out: &mut Vec<u8>) {
+        self.allocated.encode(out);
+        self.free.encode(out);
+    }
+
+    fn decode(reader: &mut Reader<'_>) -> Result<Self, SnapshotError> {
+        let allocated: Vec<Level> = reader.read()?;
+        let free: Vec<LevelId> = reader.read()?;
+        if free
+            .iter()
+            .any(|idx| idx.value() as usize >= allocated.len())
+        {
+            return Err(SnapshotError::Invalid("free level"));
+        }
+        Ok(Self { allocated, free })
+    }
 }
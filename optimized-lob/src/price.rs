@@ -1,6 +1,6 @@
 // price.rs
 
-#[derive(Eq, PartialEq, Ord, PartialOrd, Clone, Copy, Debug, Default)]
+#[derive(Eq, PartialEq, Ord, PartialOrd, Clone, Copy, Debug, Default, Hash)]
 pub struct Price(pub i32);
 
 impl Price {
@@ -22,6 +22,12 @@ impl Price {
         self.0.abs()
     }
 
+    /// Returns the unsigned price, as given to `add_order`.
+    #[inline]
+    pub fn to_u32(&self) -> u32 {
+        self.0.unsigned_abs()
+    }
+
     /// Convert a u32 to a Price.
     #[inline]
     pub fn from_u32(price: u32, is_bid: bool) -> Self {
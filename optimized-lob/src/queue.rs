@@ -0,0 +1,351 @@
+// queue.rs
+
+use crate::{
+    error::SnapshotError,
+    order::{OrderId, OwnerId},
+    quantity::Qty,
+    snapshot::{Reader, Snapshot},
+};
+
+/// Identifier of a node in a `NodePool`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct NodeId(pub u32);
+
+impl NodeId {
+    #[inline]
+    pub fn value(&self) -> u32 {
+        self.0
+    }
+}
+
+/// The state of an order resting in an L3 book that its aggregate level does not need: how
+/// much of the order is displayed, and who it belongs to.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct OrderAttributes {
+    pub peak: Qty, // Displayed size of an iceberg order. Zero for a fully displayed order.
+    pub hidden: Qty, // Reserve quantity of an iceberg order that is not displayed yet.
+    pub fully_hidden: bool, // True for a hidden order, which is never displayed.
+    pub owner: OwnerId, // The firm or account the order belongs to.
+}
+
+impl OrderAttributes {
+    /// Returns true if the order is an iceberg order.
+    #[inline]
+    pub fn is_iceberg(&self) -> bool {
+        !self.peak.is_empty()
+    }
+}
+
+/// An order resting in the FIFO queue of a price level.
+/// It is linked to the orders queued before and after it at the same level.
+#[derive(Debug, Clone, Default)]
+pub struct QueueNode {
+    order_id: OrderId,
+    qty: Qty,
+    seq: u64,
+    attributes: OrderAttributes,
+    prev: Option<NodeId>,
+    next: Option<NodeId>,
+}
+
+impl QueueNode {
+    /// Gets the ID of the queued order.
+    #[inline]
+    pub fn order_id(&self) -> OrderId {
+        self.order_id
+    }
+
+    /// Gets the remaining quantity of the queued order.
+    #[inline]
+    pub fn qty(&self) -> Qty {
+        self.qty
+    }
+
+    /// Gets the arrival sequence number of the order in its book. Lower is older.
+    #[inline]
+    pub fn seq(&self) -> u64 {
+        self.seq
+    }
+
+    /// Gets the attributes of the queued order.
+    #[inline]
+    pub fn attributes(&self) -> OrderAttributes {
+        self.attributes
+    }
+
+    /// Gets the order queued just before this one, if any.
+    #[inline]
+    pub fn prev(&self) -> Option<NodeId> {
+        self.prev
+    }
+
+    /// Gets the order queued just after this one, if any.
+    #[inline]
+    pub fn next(&self) -> Option<NodeId> {
+        self.next
+    }
+
+    /// Sets the remaining quantity of the queued order.
+    #[inline]
+    pub fn set_qty(&mut self, qty: Qty) {
+        self.qty = qty;
+    }
+
+    /// Sets the attributes of the queued order.
+    #[inline]
+    pub fn set_attributes(&mut self, attributes: OrderAttributes) {
+        self.attributes = attributes;
+    }
+}
+
+/// The first and last orders of the FIFO queue of a price level.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct OrderQueue {
+    head: Option<NodeId>,
+    tail: Option<NodeId>,
+}
+
+impl OrderQueue {
+    /// Gets the oldest order of the queue, if any.
+    #[inline]
+    pub fn head(&self) -> Option<NodeId> {
+        self.head
+    }
+
+    /// Gets the newest order of the queue, if any.
+    #[inline]
+    pub fn tail(&self) -> Option<NodeId> {
+        self.tail
+    }
+
+    /// Returns true if no order is queued.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.head.is_none()
+    }
+}
+
+/// Pool of queue nodes, threaded into the intrusive FIFO queues of the price levels.
+#[derive(Default, Clone)]
+pub struct NodePool {
+    allocated: Vec<QueueNode>, // A vector to store allocated nodes.
+    free: Vec<NodeId>,         // A vector to store free NodeId values.
+}
+
+impl NodePool {
+    /// Creates a new, empty NodePool.
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the number of nodes, free or not.
+    #[inline]
+    pub(crate) fn len(&self) -> usize {
+        self.allocated.len()
+    }
+
+    /// Allocates an unlinked node for an order. Reuses a free node if available.
+    #[inline]
+    pub fn alloc(
+        &mut self,
+        order_id: OrderId,
+        qty: Qty,
+        seq: u64,
+        attributes: OrderAttributes,
+    ) -> NodeId {
+        let node = QueueNode {
+            order_id,
+            qty,
+            seq,
+            attributes,
+            prev: None,
+            next: None,
+        };
+        if let Some(idx) = self.free.pop() {
+            self.allocated[idx.value() as usize] = node;
+            idx
+        } else {
+            let idx = NodeId(self.allocated.len() as u32);
+            self.allocated.push(node);
+            idx
+        }
+    }
+
+    /// Frees a node. It must have been unlinked from its queue first.
+    #[inline]
+    pub fn free(&mut self, idx: NodeId) {
+        self.free.push(idx);
+    }
+
+    /// Gets a reference to a node by its NodeId.
+    #[inline]
+    pub fn get(&self, idx: NodeId) -> Option<&QueueNode> {
+        self.allocated.get(idx.value() as usize)
+    }
+
+    /// Gets a mutable reference to a node by its NodeId.
+    #[inline]
+    pub fn get_mut(&mut self, idx: NodeId) -> Option<&mut QueueNode> {
+        self.allocated.get_mut(idx.value() as usize)
+    }
+
+    /// Appends a node at the back of a queue, giving it the lowest time priority.
+    #[inline]
+    pub fn push_back(&mut self, queue: &mut OrderQueue, idx: NodeId) {
+        self.allocated[idx.value() as usize].prev = queue.tail;
+        self.allocated[idx.value() as usize].next = None;
+        match queue.tail {
+            Some(tail) => self.allocated[tail.value() as usize].next = Some(idx),
+            None => queue.head = Some(idx),
+        }
+        queue.tail = Some(idx);
+    }
+
+    /// Unlinks a node from a queue, wherever it is in it.
+    #[inline]
+    pub fn unlink(&mut self, queue: &mut OrderQueue, idx: NodeId) {
+        let node = &mut self.allocated[idx.value() as usize];
+        let (prev, next) = (node.prev.take(), node.next.take());
+        match prev {
+            Some(prev) => self.allocated[prev.value() as usize].next = next,
+            None => queue.head = next,
+        }
+        match next {
+            Some(next) => self.allocated[next.value() as usize].prev = prev,
+            None => queue.tail = prev,
+        }
+    }
+
+    /// Moves a node to the back of its queue with a new arrival sequence number, so that it
+    /// loses its time priority.
+    #[inline]
+    pub fn requeue(&mut self, queue: &mut OrderQueue, idx: NodeId, seq: u64) {
+        self.unlink(queue, idx);
+        self.allocated[idx.value() as usize].seq = seq;
+        self.push_back(queue, idx);
+    }
+
+    /// Iterates over the nodes that are not free, with their ids.
+    pub(crate) fn iter_used(&self) -> impl Iterator<Item = (NodeId, &QueueNode)> {
+        let mut used = vec![true; self.allocated.len()];
+        self.free
+            .iter()
+            .for_each(|idx| used[idx.value() as usize] = false);
+        self.allocated
+            .iter()
+            .enumerate()
+            .filter(move |(idx, _)| used[*idx])
+            .map(|(idx, node)| (NodeId(idx as u32), node))
+    }
+
+    /// Iterates over a queue from its oldest to its newest order.
+    #[inline]
+    pub fn iter<'a>(&'a self, queue: &OrderQueue) -> QueueIter<'a> {
+        QueueIter {
+            pool: self,
+            cur: queue.head,
+        }
+    }
+}
+
+/// Iterator over the orders of a queue in time priority.
+pub struct QueueIter<'a> {
+    pool: &'a NodePool,
+    cur: Option<NodeId>,
+}
+
+impl<'a> Iterator for QueueIter<'a> {
+    type Item = &'a QueueNode;
+
+    #[inline]
+    fn next(&mut self) -> Option<&'a QueueNode> {
+        let node = self.pool.get(self.cur?)?;
+        self.cur = node.next;
+        Some(node)
+    }
+}
+
+impl Snapshot for OrderAttributes {
+    fn encode(&self, out: &mut Vec<u8>) {
+        self.peak.encode(out);
+        self.hidden.encode(out);
+        self.fully_hidden.encode(out);
+        self.owner.encode(out);
+    }
+
+    fn decode(reader: &mut Reader<'_>) -> Result<Self, SnapshotError> {
+        Ok(Self {
+            peak: reader.read()?,
+            hidden: reader.read()?,
+            fully_hidden: reader.read()?,
+            owner: reader.read()?,
+        })
+    }
+}
+
+impl Snapshot for QueueNode {
+    fn encode(&self, out: &mut Vec<u8>) {
+        self.order_id.encode(out);
+        self.qty.encode(out);
+        self.seq.encode(out);
+        self.attributes.encode(out);
+        self.prev.encode(out);
+        self.next.encode(out);
+    }
+
+    fn decode(reader: &mut Reader<'_>) -> Result<Self, SnapshotError> {
+        Ok(Self {
+            order_id: reader.read()?,
+            qty: reader.read()?,
+            seq: reader.read()?,
+            attributes: reader.read()?,
+            prev: reader.read()?,
+            next: reader.read()?,
+        })
+    }
+}
+
+impl Snapshot for OrderQueue {
+    fn encode(&self, out: &mut Vec<u8>) {
+        self.head.encode(out);
+        self.tail.encode(out);
+    }
+
+    fn decode(reader: &mut Reader<'_>) -> Result<Self, SnapshotError> {
+        Ok(Self {
+            head: reader.read()?,
+            tail: reader.read()?,
+        })
+    }
+}
+
+impl Snapshot for NodePool {
+    fn encode(&self, out: &mut Vec<u8>) {
+        self.allocated.encode(out);
+        self.free.encode(out);
+    }
+
+    fn decode(reader: &mut Reader<'_>) -> Result<Self, SnapshotError> {
+        let pool = Self {
+            allocated: reader.read()?,
+            free: reader.read()?,
+        };
+        let len = pool.allocated.len();
+        let links = pool
+            .allocated
+            .iter()
+            .flat_map(|node| [node.prev, node.next])
+            .flatten();
+        if pool
+            .free
+            .iter()
+            .copied()
+            .chain(links)
+            .any(|idx| idx.value() as usize >= len)
+        {
+            return Err(SnapshotError::Invalid("node id"));
+        }
+        Ok(pool)
+    }
+}
@@ -0,0 +1,52 @@
+// side.rs
+
+use crate::{
+    error::SnapshotError,
+    snapshot::{Reader, Snapshot},
+};
+
+/// The side of the book an order rests on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Side {
+    Bid,
+    Ask,
+}
+
+impl Side {
+    /// Converts a Buy/Sell Indicator, as used by `add_order`, to a Side.
+    #[inline]
+    pub fn from_is_bid(is_bid: bool) -> Self {
+        if is_bid {
+            Side::Bid
+        } else {
+            Side::Ask
+        }
+    }
+
+    /// Returns true if the side is the bid side.
+    #[inline]
+    pub fn is_bid(&self) -> bool {
+        *self == Side::Bid
+    }
+
+    /// Returns the other side of the book.
+    #[inline]
+    pub fn opposite(&self) -> Self {
+        match self {
+            Side::Bid => Side::Ask,
+            Side::Ask => Side::Bid,
+        }
+    }
+}
+
+impl Snapshot for Side {
+    #[inline]
+    fn encode(&self, out: &mut Vec<u8>) {
+        self.is_bid().encode(out);
+    }
+
+    #[inline]
+    fn decode(reader: &mut Reader<'_>) -> Result<Self, SnapshotError> {
+        Ok(Side::from_is_bid(reader.read()?))
+    }
+}
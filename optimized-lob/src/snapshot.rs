@@ -0,0 +1,257 @@
+// snapshot.rs
+
+use crate::{
+    book::Book,
+    error::SnapshotError,
+    events::EventSink,
+    level::LevelId,
+    oid_map::OrderIdMap,
+    order::{OrderId, OwnerId},
+    orderbook_manager::OrderBookManager,
+    price::Price,
+    quantity::Qty,
+    queue::NodeId,
+    utils::BookId,
+};
+
+/// Magic bytes at the start of a snapshot of an `OrderBookManager`.
+pub const SNAPSHOT_MAGIC: [u8; 4] = *b"LOBS";
+/// Version of the snapshot format, bumped whenever the encoding of a type changes.
+pub const SNAPSHOT_VERSION: u16 = 1;
+
+/// A type whose complete state can be written to a binary snapshot and read back.
+/// Integers are little-endian, and a sequence is its length as a u32 followed by its items.
+pub trait Snapshot: Sized {
+    /// Appends the state to `out`.
+    fn encode(&self, out: &mut Vec<u8>);
+
+    /// Reads a state written by `encode`.
+    fn decode(reader: &mut Reader<'_>) -> Result<Self, SnapshotError>;
+}
+
+/// Reads the values of a snapshot, in the order they were written.
+pub struct Reader<'a> {
+    bytes: &'a [u8], // The bytes not read yet.
+}
+
+impl<'a> Reader<'a> {
+    /// Creates a reader over the bytes of a snapshot.
+    #[inline]
+    pub fn new(bytes: &'a [u8]) -> Self {
+        Self { bytes }
+    }
+
+    /// Returns the number of bytes not read yet.
+    #[inline]
+    pub fn remaining(&self) -> usize {
+        self.bytes.len()
+    }
+
+    /// Reads the next `len` bytes.
+    #[inline]
+    pub fn read_bytes(&mut self, len: usize) -> Result<&'a [u8], SnapshotError> {
+        if len > self.bytes.len() {
+            return Err(SnapshotError::Truncated);
+        }
+        let (bytes, rest) = self.bytes.split_at(len);
+        self.bytes = rest;
+        Ok(bytes)
+    }
+
+    /// Reads the next value.
+    #[inline]
+    pub fn read<T: Snapshot>(&mut self) -> Result<T, SnapshotError> {
+        T::decode(self)
+    }
+}
+
+macro_rules! snapshot_int {
+    ($($int:ty),*) => {
+        $(
+            impl Snapshot for $int {
+                #[inline]
+                fn encode(&self, out: &mut Vec<u8>) {
+                    out.extend_from_slice(&self.to_le_bytes());
+                }
+
+                #[inline]
+                fn decode(reader: &mut Reader<'_>) -> Result<Self, SnapshotError> {
+                    let bytes = reader.read_bytes(std::mem::size_of::<$int>())?;
+                    Ok(<$int>::from_le_bytes(bytes.try_into().unwrap()))
+                }
+            }
+        )*
+    };
+}
+
+snapshot_int!(u8, u16, u32, u64, i32);
+
+macro_rules! snapshot_newtype {
+    ($($newtype:ident),*) => {
+        $(
+            impl Snapshot for $newtype {
+                #[inline]
+                fn encode(&self, out: &mut Vec<u8>) {
+                    self.0.encode(out);
+                }
+
+                #[inline]
+                fn decode(reader: &mut Reader<'_>) -> Result<Self, SnapshotError> {
+                    Ok(Self(reader.read()?))
+                }
+            }
+        )*
+    };
+}
+
+snapshot_newtype!(Qty, Price, LevelId, NodeId, BookId, OrderId, OwnerId);
+
+impl Snapshot for bool {
+    #[inline]
+    fn encode(&self, out: &mut Vec<u8>) {
+        (*self as u8).encode(out);
+    }
+
+    #[inline]
+    fn decode(reader: &mut Reader<'_>) -> Result<Self, SnapshotError> {
+        match reader.read::<u8>()? {
+            0 => Ok(false),
+            1 => Ok(true),
+            _ => Err(SnapshotError::Invalid("bool")),
+        }
+    }
+}
+
+impl<T: Snapshot> Snapshot for Option<T> {
+    #[inline]
+    fn encode(&self, out: &mut Vec<u8>) {
+        match self {
+            Some(value) => {
+                true.encode(out);
+                value.encode(out);
+            }
+            None => false.encode(out),
+        }
+    }
+
+    #[inline]
+    fn decode(reader: &mut Reader<'_>) -> Result<Self, SnapshotError> {
+        if reader.read()? {
+            Ok(Some(reader.read()?))
+        } else {
+            Ok(None)
+        }
+    }
+}
+
+impl<T: Snapshot> Snapshot for Vec<T> {
+    fn encode(&self, out: &mut Vec<u8>) {
+        (self.len() as u32).encode(out);
+        self.iter().for_each(|item| item.encode(out));
+    }
+
+    fn decode(reader: &mut Reader<'_>) -> Result<Self, SnapshotError> {
+        let len = reader.read::<u32>()? as usize;
+        // Every item takes at least one byte, which bounds what a corrupt length allocates.
+        let mut items = Vec::with_capacity(len.min(reader.remaining()));
+        for _ in 0..len {
+            items.push(reader.read()?);
+        }
+        Ok(items)
+    }
+}
+
+impl<M, B, E> OrderBookManager<M, B, E>
+where
+    M: OrderIdMap + Snapshot,
+    B: Book + Snapshot,
+    E: EventSink,
+{
+    /// Writes the complete state of the books and orders to a versioned binary snapshot.
+    /// The event sink is not part of the snapshot.
+    /// ## Example:
+    /// ```
+    /// let orderbook_manager = OrderBookManager::new();
+    ///
+    /// let snapshot = orderbook_manager.save();
+    /// ```
+    pub fn save(&self) -> Vec<u8> {
+        let mut out = Vec::new();
+        out.extend_from_slice(&SNAPSHOT_MAGIC);
+        SNAPSHOT_VERSION.encode(&mut out);
+        self.oid_map.encode(&mut out);
+
+        let books: Vec<(usize, &B)> = self
+            .books
+            .iter()
+            .enumerate()
+            .filter_map(|(idx, book)| Some((idx, book.as_ref()?)))
+            .collect();
+        (books.len() as u32).encode(&mut out);
+        for (idx, book) in books {
+            BookId(idx as u16).encode(&mut out);
+            book.encode(&mut out);
+        }
+        out
+    }
+
+    /// Restores the books and orders from a snapshot written by `save`, with the given event
+    /// sink. Processing the same messages then gives the same state as it would have without
+    /// the snapshot.
+    /// ## Arguments:
+    /// - `bytes`: The snapshot.
+    /// - `events`: The event sink.
+    ///
+    /// # Returns
+    /// Returns a `SnapshotError` if the bytes are not a complete snapshot of this version.
+    pub fn restore_with_events(bytes: &[u8], events: E) -> Result<Self, SnapshotError> {
+        let mut reader = Reader::new(bytes);
+        if reader.read_bytes(SNAPSHOT_MAGIC.len())? != SNAPSHOT_MAGIC {
+            return Err(SnapshotError::BadMagic);
+        }
+        let version: u16 = reader.read()?;
+        if version != SNAPSHOT_VERSION {
+            return Err(SnapshotError::UnsupportedVersion(version));
+        }
+
+        let mut orderbook_manager = Self::with_events(reader.read()?, events);
+        let book_count: u32 = reader.read()?;
+        for _ in 0..book_count {
+            let book_id: BookId = reader.read()?;
+            let book = orderbook_manager
+                .books
+                .get_mut(book_id.value() as usize)
+                .ok_or(SnapshotError::Invalid("book id"))?;
+            *book = Some(reader.read()?);
+        }
+        if reader.remaining() > 0 {
+            return Err(SnapshotError::Invalid("trailing bytes"));
+        }
+        Ok(orderbook_manager)
+    }
+}
+
+impl<M, B, E> OrderBookManager<M, B, E>
+where
+    M: OrderIdMap + Snapshot,
+    B: Book + Snapshot,
+    E: EventSink + Default,
+{
+    /// Restores the books and orders from a snapshot written by `save`.
+    /// ## Arguments:
+    /// - `bytes`: The snapshot.
+    ///
+    /// # Returns
+    /// Returns a `SnapshotError` if the bytes are not a complete snapshot of this version.
+    ///
+    /// ## Example:
+    /// ```
+    /// let snapshot = OrderBookManager::new().save();
+    ///
+    /// let orderbook_manager: OrderBookManager = OrderBookManager::restore(&snapshot)?;
+    /// ```
+    #[inline]
+    pub fn restore(bytes: &[u8]) -> Result<Self, SnapshotError> {
+        Self::restore_with_events(bytes, E::default())
+    }
+}
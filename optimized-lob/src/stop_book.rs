@@ -0,0 +1,126 @@
+// stop_book.rs
+
+use crate::{
+    error::SnapshotError,
+    matching::OrderRequest,
+    order::OrderId,
+    side::Side,
+    snapshot::{Reader, Snapshot},
+};
+use std::collections::{BTreeMap, VecDeque};
+
+/// Stop orders of a book waiting for their trigger price, keyed by trigger price.
+///
+/// Buy stops trigger once a trade prints at or above their trigger price, sell stops once a
+/// trade prints at or below it. Orders with the same trigger price keep their arrival order.
+#[derive(Debug, Default, Clone)]
+pub struct StopBook {
+    buys: BTreeMap<u32, VecDeque<OrderRequest>>,
+    sells: BTreeMap<u32, VecDeque<OrderRequest>>,
+    len: usize,
+}
+
+impl StopBook {
+    /// Creates a new, empty StopBook.
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the number of waiting stop orders.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns true if no stop order is waiting.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns true if a stop order with the given ID is waiting.
+    pub fn contains(&self, order_id: OrderId) -> bool {
+        self.buys
+            .values()
+            .chain(self.sells.values())
+            .flatten()
+            .any(|request| request.order_id == order_id)
+    }
+
+    /// Adds a stop order behind the orders with the same trigger price.
+    #[inline]
+    pub fn insert(&mut self, trigger: u32, request: OrderRequest) {
+        let stops = match request.side {
+            Side::Bid => &mut self.buys,
+            Side::Ask => &mut self.sells,
+        };
+        stops.entry(trigger).or_default().push_back(request);
+        self.len += 1;
+    }
+
+    /// Removes a waiting stop order by its ID.
+    pub fn remove(&mut self, order_id: OrderId) -> Option<OrderRequest> {
+        for stops in [&mut self.buys, &mut self.sells] {
+            let found = stops.iter_mut().find_map(|(trigger, queue)| {
+                let pos = queue.iter().position(|r| r.order_id == order_id)?;
+                Some((*trigger, pos))
+            });
+            if let Some((trigger, pos)) = found {
+                let queue = stops.get_mut(&trigger).unwrap();
+                let request = queue.remove(pos);
+                if queue.is_empty() {
+                    stops.remove(&trigger);
+                }
+                self.len -= 1;
+                return request;
+            }
+        }
+        None
+    }
+
+    /// Removes and returns the stop orders triggered by a trade at `price`.
+    ///
+    /// Buy stops come first, from the lowest to the highest trigger price, then sell stops,
+    /// from the highest to the lowest trigger price. Within a trigger price the oldest order
+    /// comes first.
+    pub fn take_triggered(&mut self, price: u32) -> Vec<OrderRequest> {
+        let mut triggered = Vec::new();
+        let untriggered_buys = match price.checked_add(1) {
+            Some(above) => self.buys.split_off(&above),
+            None => BTreeMap::new(),
+        };
+        let triggered_buys = std::mem::replace(&mut self.buys, untriggered_buys);
+        triggered.extend(triggered_buys.into_values().flatten());
+
+        let triggered_sells = self.sells.split_off(&price);
+        triggered.extend(triggered_sells.into_values().rev().flatten());
+
+        self.len -= triggered.len();
+        triggered
+    }
+}
+
+/// The waiting orders are written with their trigger price, buys then sells, each side by
+/// trigger price and then in arrival order.
+impl Snapshot for StopBook {
+    fn encode(&self, out: &mut Vec<u8>) {
+        (self.len as u64).encode(out);
+        for (trigger, stops) in self.buys.iter().chain(&self.sells) {
+            for request in stops {
+                trigger.encode(out);
+                request.encode(out);
+            }
+        }
+    }
+
+    fn decode(reader: &mut Reader<'_>) -> Result<Self, SnapshotError> {
+        let mut stops = Self::new();
+        let len: u64 = reader.read()?;
+        for _ in 0..len {
+            let trigger = reader.read()?;
+            stops.insert(trigger, reader.read()?);
+        }
+        Ok(stops)
+    }
+}
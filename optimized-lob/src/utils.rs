@@ -1,6 +1,8 @@
 // utils.rs
 
 pub const INITIAL_ORDER_COUNT: usize = 1 << 20;
+/// Order ids below this limit are stored in a dense array, larger ones in a hash map.
+pub const DENSE_ORDER_LIMIT: u64 = 1 << 32;
 pub const MAX_BOOKS: usize = 1 << 14;
 pub const MAX_LEVELS: usize = 1 << 20;
 
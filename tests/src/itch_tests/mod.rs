@@ -0,0 +1,8 @@
+mod test_batch;
+mod test_export;
+mod test_generator;
+mod test_handler;
+mod test_multi_file_stream;
+mod test_serde;
+mod test_subset;
+pub(crate) mod utils;
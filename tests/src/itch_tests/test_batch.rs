@@ -0,0 +1,62 @@
+#[cfg(test)]
+mod tests {
+    use crate::itch_tests::utils::{
+        add_order, delete_order, execute_order, mwcb_status, system_event,
+    };
+    use itch_parser::{EventCode, MessageBatch, MessageStream};
+
+    fn sample_data() -> Vec<u8> {
+        let mut data = Vec::new();
+        data.extend(system_event(10, b'O'));
+        data.extend(add_order(7, 1, true, 100, 5000));
+        data.extend(add_order(8, 2, false, 50, 5100));
+        data.extend(mwcb_status(0));
+        data.extend(execute_order(7, 1, 40));
+        data.extend(delete_order(8, 2));
+        data
+    }
+
+    #[test]
+    fn test_next_batch_splits_into_families() {
+        let data = sample_data();
+        let mut stream = MessageStream::from_reader(&data[..]);
+
+        let batch = stream.next_batch(100).unwrap();
+
+        assert_eq!(batch.len(), 6);
+        assert_eq!(batch.add_orders.tag, vec![b'A', b'A']);
+        assert_eq!(batch.add_orders.stock_locate, vec![7, 8]);
+        assert_eq!(batch.add_orders.order_id, vec![1, 2]);
+        assert_eq!(batch.add_orders.is_bid, vec![true, false]);
+        assert_eq!(batch.add_orders.shares, vec![100, 50]);
+        assert_eq!(batch.add_orders.price, vec![5000, 5100]);
+        assert_eq!(batch.orders_executed.order_id, vec![1]);
+        assert_eq!(batch.orders_executed.shares, vec![40]);
+        assert_eq!(batch.delete_orders.order_id, vec![2]);
+        assert_eq!(batch.system_events.timestamp, vec![10]);
+        assert_eq!(batch.system_events.event, vec![EventCode::StartOfMessages]);
+        assert_eq!(batch.passes.tag, vec![b'W']);
+
+        assert!(stream.next_batch(100).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_next_batch_into_respects_batch_size() {
+        let data = sample_data();
+        let mut stream = MessageStream::from_reader(&data[..]);
+        let mut batch = MessageBatch::new();
+
+        assert_eq!(stream.next_batch_into(4, &mut batch).unwrap(), 4);
+        assert_eq!(batch.len(), 4);
+        assert_eq!(batch.add_orders.len(), 2);
+
+        assert_eq!(stream.next_batch_into(4, &mut batch).unwrap(), 2);
+        assert_eq!(batch.len(), 2);
+        assert!(batch.add_orders.is_empty());
+        assert_eq!(batch.orders_executed.len(), 1);
+        assert_eq!(batch.delete_orders.len(), 1);
+
+        assert_eq!(stream.next_batch_into(4, &mut batch).unwrap(), 0);
+        assert!(batch.is_empty());
+    }
+}
@@ -0,0 +1,142 @@
+#[cfg(test)]
+mod tests {
+    use crate::itch_tests::utils::{
+        add_order, delete_order, execute_order, stock_directory, system_event,
+    };
+    use itch_parser::{
+        export_csv, export_ndjson, export_parquet, ExportFilter, ExportRecord, MessageStream,
+    };
+    use parquet::file::reader::{FileReader, SerializedFileReader};
+    use std::fs::File;
+
+    fn sample_data() -> Vec<u8> {
+        let mut data = Vec::new();
+        data.extend(system_event(10, b'O'));
+        data.extend(stock_directory(7, "AAPL"));
+        data.extend(stock_directory(8, "MSFT"));
+        data.extend(add_order(7, 1, true, 100, 1_234_500));
+        data.extend(add_order(8, 2, false, 50, 5100));
+        data.extend(execute_order(7, 1, 40));
+        data.extend(delete_order(8, 2));
+        data
+    }
+
+    #[test]
+    fn test_export_csv_with_tag_filter() {
+        let data = sample_data();
+        let mut stream = MessageStream::from_reader(&data[..]);
+        let mut out = Vec::new();
+
+        let filter = ExportFilter::new().with_tags(b"AE");
+        let exported = export_csv(&mut stream, &mut out, &filter).unwrap();
+
+        assert_eq!(exported, 3);
+        let out = String::from_utf8(out).unwrap();
+        let lines: Vec<&str> = out.lines().collect();
+        assert_eq!(
+            lines[0],
+            "message_type,tag,stock_locate,tracking_number,timestamp,symbol,order_id,\
+             new_order_id,side,shares,stock,price,match_number,printable,event"
+        );
+        assert_eq!(lines[1], "add_order,A,7,0,0,AAPL,1,,B,100,AAPL,123.4500,,,");
+        assert_eq!(lines[2], "add_order,A,8,0,0,MSFT,2,,S,50,AAPL,0.5100,,,");
+        assert_eq!(lines[3], "order_executed,E,7,0,0,AAPL,1,,,40,,,1,,");
+    }
+
+    #[test]
+    fn test_export_csv_writes_the_header_without_messages() {
+        let data = sample_data();
+        let mut stream = MessageStream::from_reader(&data[..]);
+        let mut out = Vec::new();
+
+        let filter = ExportFilter::new().with_tags(b"U");
+        assert_eq!(export_csv(&mut stream, &mut out, &filter).unwrap(), 0);
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            format!("{}\n", ExportRecord::COLUMNS.join(","))
+        );
+    }
+
+    #[test]
+    fn test_export_ndjson_with_symbol_filter() {
+        let data = sample_data();
+        let mut stream = MessageStream::from_reader(&data[..]);
+        let mut out = Vec::new();
+
+        let filter = ExportFilter::new().with_symbols(&["MSFT"]);
+        let exported = export_ndjson(&mut stream, &mut out, &filter).unwrap();
+
+        assert_eq!(exported, 3);
+        let records: Vec<serde_json::Value> = String::from_utf8(out)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(records.len(), 3);
+        assert_eq!(records[0]["message_type"], "stock_directory");
+        assert_eq!(records[0]["stock"], "MSFT");
+        assert_eq!(records[1]["order_id"], 2);
+        assert_eq!(records[1]["side"], "S");
+        assert_eq!(records[1]["price"], "0.5100");
+        assert_eq!(records[2]["message_type"], "order_delete");
+        assert!(records.iter().all(|r| r["symbol"] == "MSFT"));
+    }
+
+    #[test]
+    fn test_export_parquet_one_file_per_message_type() {
+        let data = sample_data();
+        let mut stream = MessageStream::from_reader(&data[..]);
+        let dir = std::env::temp_dir().join(format!("itch-export-{}", std::process::id()));
+
+        let exported = export_parquet(&mut stream, &dir, &ExportFilter::new()).unwrap();
+        assert_eq!(exported, 7);
+
+        let mut files: Vec<String> = std::fs::read_dir(&dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+            .collect();
+        files.sort();
+        assert_eq!(
+            files,
+            vec![
+                "add_order.parquet",
+                "order_delete.parquet",
+                "order_executed.parquet",
+                "stock_directory.parquet",
+                "system_event.parquet",
+            ]
+        );
+
+        let reader =
+            SerializedFileReader::new(File::open(dir.join("add_order.parquet")).unwrap()).unwrap();
+        let metadata = reader.metadata();
+        assert_eq!(metadata.file_metadata().num_rows(), 2);
+        let rows: Vec<String> = reader
+            .get_row_iter(None)
+            .unwrap()
+            .map(|row| row.unwrap().to_string())
+            .collect();
+        assert!(rows[0].contains("symbol: \"AAPL\""));
+        assert!(rows[0].contains("stock: \"AAPL\""));
+        assert!(rows[0].contains("price: 123.4500"));
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_export_parquet_closes_files_when_the_stream_fails() {
+        let mut data = sample_data();
+        // The day file ends in the middle of a message.
+        data.extend(&add_order(7, 3, true, 10, 1_234_500)[..20]);
+        let mut stream = MessageStream::from_reader(&data[..]);
+        let dir = std::env::temp_dir().join(format!("itch-export-partial-{}", std::process::id()));
+
+        assert!(export_parquet(&mut stream, &dir, &ExportFilter::new()).is_err());
+
+        let reader =
+            SerializedFileReader::new(File::open(dir.join("add_order.parquet")).unwrap()).unwrap();
+        assert_eq!(reader.metadata().file_metadata().num_rows(), 2);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
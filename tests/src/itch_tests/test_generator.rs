@@ -0,0 +1,223 @@
+#[cfg(test)]
+mod tests {
+    use crate::itch_tests::utils::replay;
+    use itch_parser::{
+        Body, EventCode, Generator, GeneratorConfig, Message, MessageStream, MessageWriter,
+    };
+    use optimized_lob::{order::OrderId, quantity::Qty};
+    use std::collections::HashMap;
+
+    fn generate(config: GeneratorConfig) -> Vec<u8> {
+        let mut writer = MessageWriter::from_writer(Vec::new());
+        Generator::new(config).generate(&mut writer).unwrap();
+        writer.into_inner().unwrap()
+    }
+
+    fn parse(data: &[u8]) -> Vec<Message> {
+        MessageStream::from_reader(data)
+            .map(|msg| msg.unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn test_message_writer_round_trip() {
+        let msg = Message {
+            tag: b'C',
+            stock_locate: 3,
+            tracking_number: 9,
+            timestamp: 34_200_000_000_123,
+            body: Body::OrderExecutedWithPrice {
+                order_id: 5,
+                shares: 10,
+                match_number: 77,
+                printable: true,
+                price: 1_000_100,
+            },
+        };
+        let mut writer = MessageWriter::from_writer(Vec::new());
+        writer.write(&msg).unwrap();
+        let pass = Message {
+            tag: b'W',
+            body: Body::Pass(()),
+            ..msg.clone()
+        };
+        assert!(writer.write(&pass).is_err());
+
+        assert_eq!(parse(&writer.into_inner().unwrap()), vec![msg]);
+    }
+
+    #[test]
+    fn test_generator_is_deterministic() {
+        let config = GeneratorConfig {
+            order_messages: 2_000,
+            ..GeneratorConfig::default()
+        };
+
+        let first = generate(config.clone());
+        let second = generate(config.clone());
+        let other = generate(GeneratorConfig { seed: 1, ..config });
+
+        assert_eq!(first, second);
+        assert_ne!(first, other);
+    }
+
+    #[test]
+    fn test_generator_keeps_referential_consistency() {
+        let config = GeneratorConfig {
+            seed: 42,
+            symbols: vec!["AAPL".into(), "MSFT".into(), "QQQ".into(), "SPY".into()],
+            order_messages: 20_000,
+            ..GeneratorConfig::default()
+        };
+        let messages = parse(&generate(config));
+
+        // order id -> (stock locate, remaining shares)
+        let mut live: HashMap<u64, (u16, u32)> = HashMap::new();
+        let mut tags: HashMap<u8, usize> = HashMap::new();
+        for msg in &messages {
+            *tags.entry(msg.tag).or_default() += 1;
+            match msg.body {
+                Body::AddOrder {
+                    order_id, shares, ..
+                } => {
+                    assert!(live.insert(order_id, (msg.stock_locate, shares)).is_none());
+                }
+                Body::OrderExecuted {
+                    order_id, shares, ..
+                }
+                | Body::OrderCancelled { order_id, shares } => {
+                    let order = live.get_mut(&order_id).expect("unknown order");
+                    assert_eq!(order.0, msg.stock_locate);
+                    assert!(shares <= order.1);
+                    order.1 -= shares;
+                    if order.1 == 0 {
+                        live.remove(&order_id);
+                    }
+                }
+                Body::DeleteOrder { order_id } => {
+                    assert!(live.remove(&order_id).is_some(), "unknown order");
+                }
+                Body::ReplaceOrder {
+                    old_order_id,
+                    new_order_id,
+                    shares,
+                    ..
+                } => {
+                    let (stock_locate, _) = live.remove(&old_order_id).expect("unknown order");
+                    assert_eq!(stock_locate, msg.stock_locate);
+                    assert!(live.insert(new_order_id, (stock_locate, shares)).is_none());
+                }
+                _ => {}
+            }
+        }
+
+        for tag in b"SRAFEXDU" {
+            assert!(
+                tags.get(tag).copied().unwrap_or(0) > 0,
+                "no '{}'",
+                *tag as char
+            );
+        }
+        assert_eq!(tags[&b'R'], 4);
+        assert_eq!(
+            messages.first().unwrap().body,
+            Body::SystemEvent {
+                event: EventCode::StartOfMessages
+            }
+        );
+        assert_eq!(
+            messages.last().unwrap().body,
+            Body::SystemEvent {
+                event: EventCode::EndOfMessages
+            }
+        );
+    }
+
+    /// Checks that no generated bid is ever at or above a live ask of the same symbol.
+    fn assert_never_crosses(config: GeneratorConfig) {
+        // order id -> (is bid, price, remaining shares)
+        let mut live: HashMap<u64, (bool, u32, u32)> = HashMap::new();
+        for msg in parse(&generate(config)) {
+            let (order_id, is_bid, price, shares) = match msg.body {
+                Body::AddOrder {
+                    order_id,
+                    is_bid,
+                    shares,
+                    price,
+                    ..
+                } => (order_id, is_bid, price, shares),
+                Body::ReplaceOrder {
+                    old_order_id,
+                    new_order_id,
+                    shares,
+                    price,
+                } => {
+                    let (is_bid, _, _) = live.remove(&old_order_id).unwrap();
+                    (new_order_id, is_bid, price, shares)
+                }
+                Body::OrderExecuted {
+                    order_id, shares, ..
+                }
+                | Body::OrderCancelled { order_id, shares } => {
+                    let order = live.get_mut(&order_id).unwrap();
+                    order.2 -= shares;
+                    if order.2 == 0 {
+                        live.remove(&order_id);
+                    }
+                    continue;
+                }
+                Body::DeleteOrder { order_id } => {
+                    live.remove(&order_id);
+                    continue;
+                }
+                _ => continue,
+            };
+            assert!(price > 0);
+            let crossed = live.values().any(|&(other_is_bid, other_price, _)| {
+                let (bid, ask) = if is_bid {
+                    (price, other_price)
+                } else {
+                    (other_price, price)
+                };
+                other_is_bid != is_bid && bid >= ask
+            });
+            assert!(!crossed, "order {} at {} crosses the book", order_id, price);
+            live.insert(order_id, (is_bid, price, shares));
+        }
+    }
+
+    #[test]
+    fn test_generated_book_never_crosses() {
+        assert_never_crosses(GeneratorConfig {
+            seed: 5,
+            symbols: vec!["AAPL".into()],
+            order_messages: 5_000,
+            ..GeneratorConfig::default()
+        });
+        // Prices stay in range at both ends of the price scale.
+        for initial_price in [0, u32::MAX] {
+            assert_never_crosses(GeneratorConfig {
+                symbols: vec!["AAPL".into()],
+                order_messages: 2_000,
+                initial_price,
+                ..GeneratorConfig::default()
+            });
+        }
+    }
+
+    #[test]
+    fn test_generated_data_replays_through_orderbook() {
+        let data = generate(GeneratorConfig {
+            seed: 7,
+            order_messages: 20_000,
+            ..GeneratorConfig::default()
+        });
+
+        let (orderbook_manager, live) = replay(&parse(&data));
+        assert!(!live.is_empty());
+        for (order_id, shares) in live {
+            let order = orderbook_manager.oid_map.get(OrderId(order_id)).unwrap();
+            assert_eq!(order.qty(), Qty(shares));
+        }
+    }
+}
@@ -0,0 +1,98 @@
+#[cfg(test)]
+mod tests {
+    use crate::itch_tests::utils::{
+        add_order, delete_order, execute_order, mwcb_status, system_event,
+    };
+    use itch_parser::{dispatch_message, EventCode, ItchHandler, Message, MessageStream};
+
+    /// A handler that only implements the order messages it is interested in.
+    #[derive(Default)]
+    struct Recorder {
+        adds: Vec<(u16, u64, bool, u32, u32)>,
+        executes: Vec<(u64, u32)>,
+        deletes: Vec<u64>,
+        events: Vec<EventCode>,
+    }
+
+    impl ItchHandler for Recorder {
+        fn on_add_order(
+            &mut self,
+            msg: &Message,
+            order_id: u64,
+            is_bid: bool,
+            shares: u32,
+            _stock: u64,
+            price: u32,
+        ) {
+            self.adds
+                .push((msg.stock_locate, order_id, is_bid, shares, price));
+        }
+
+        fn on_execute(&mut self, _msg: &Message, order_id: u64, shares: u32, _match_number: u64) {
+            self.executes.push((order_id, shares));
+        }
+
+        fn on_delete_order(&mut self, _msg: &Message, order_id: u64) {
+            self.deletes.push(order_id);
+        }
+
+        fn on_system_event(&mut self, _msg: &Message, event: EventCode) {
+            self.events.push(event);
+        }
+    }
+
+    #[test]
+    fn test_dispatch_calls_matching_methods() {
+        let mut data = Vec::new();
+        data.extend(system_event(10, b'O'));
+        data.extend(add_order(7, 1, true, 100, 5000));
+        data.extend(add_order(7, u64::MAX, false, 50, 5100));
+        data.extend(mwcb_status(0));
+        data.extend(execute_order(7, 1, 40));
+        data.extend(delete_order(7, u64::MAX));
+        data.extend(system_event(20, b'C'));
+
+        let mut stream = MessageStream::from_reader(&data[..]);
+        let mut recorder = Recorder::default();
+        let messages = stream.dispatch(&mut recorder).unwrap();
+
+        assert_eq!(messages, 7);
+        assert_eq!(
+            recorder.adds,
+            vec![(7, 1, true, 100, 5000), (7, u64::MAX, false, 50, 5100)]
+        );
+        assert_eq!(recorder.executes, vec![(1, 40)]);
+        assert_eq!(recorder.deletes, vec![u64::MAX]);
+        assert_eq!(
+            recorder.events,
+            vec![EventCode::StartOfMessages, EventCode::EndOfMessages]
+        );
+    }
+
+    #[test]
+    fn test_dispatch_with_default_handler() {
+        struct Noop;
+        impl ItchHandler for Noop {}
+
+        let mut data = Vec::new();
+        data.extend(add_order(1, 1, true, 100, 5000));
+        data.extend(delete_order(1, 1));
+
+        let mut stream = MessageStream::from_reader(&data[..]);
+        assert_eq!(stream.dispatch(&mut Noop).unwrap(), 2);
+    }
+
+    #[test]
+    fn test_dispatch_message_single() {
+        let data = add_order(3, 42, false, 10, 100);
+        let msg = MessageStream::from_reader(&data[..])
+            .next()
+            .unwrap()
+            .unwrap();
+
+        let mut recorder = Recorder::default();
+        dispatch_message(&mut recorder, &msg);
+
+        assert_eq!(recorder.adds, vec![(3, 42, false, 10, 100)]);
+    }
+}
@@ -0,0 +1,151 @@
+#[cfg(test)]
+mod tests {
+    use crate::itch_tests::utils::{add_order, delete_order, system_event};
+    use crate::lob_tests::utils::get_level_capacity;
+    use itch_parser::{Body, MultiFileStream, SessionDate, SessionEvent};
+    use optimized_lob::{
+        order::OrderId, orderbook_manager::OrderBookManager, quantity::Qty, utils::BookId,
+    };
+    use std::path::PathBuf;
+
+    /// Writes the sessions into a fresh temporary directory and returns their paths.
+    fn write_sessions(test: &str, sessions: &[(&str, Vec<u8>)]) -> (PathBuf, Vec<PathBuf>) {
+        let dir = std::env::temp_dir().join(format!("itch-{}-{}", test, std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let paths = sessions
+            .iter()
+            .map(|(name, data)| {
+                let path = dir.join(name);
+                std::fs::write(&path, data).unwrap();
+                path
+            })
+            .collect();
+        (dir, paths)
+    }
+
+    #[test]
+    fn test_session_date_from_path() {
+        assert_eq!(
+            SessionDate::from_path("/data/12302019.NASDAQ_ITCH50"),
+            Some(SessionDate::new(2019, 12, 30))
+        );
+        assert_eq!(SessionDate::new(2020, 1, 2).to_string(), "2020-01-02");
+        assert_eq!(SessionDate::from_path("NASDAQ_ITCH50"), None);
+        assert_eq!(SessionDate::from_path("13302019.NASDAQ_ITCH50"), None);
+    }
+
+    #[test]
+    fn test_session_date_must_exist() {
+        assert_eq!(SessionDate::from_path("02312020.NASDAQ_ITCH50"), None);
+        assert_eq!(SessionDate::from_path("04312020.NASDAQ_ITCH50"), None);
+        assert_eq!(SessionDate::from_path("02292019.NASDAQ_ITCH50"), None);
+        assert_eq!(SessionDate::from_path("02291900.NASDAQ_ITCH50"), None);
+        assert_eq!(
+            SessionDate::from_path("02292020.NASDAQ_ITCH50"),
+            Some(SessionDate::new(2020, 2, 29))
+        );
+        assert_eq!(
+            SessionDate::from_path("02292000.NASDAQ_ITCH50"),
+            Some(SessionDate::new(2000, 2, 29))
+        );
+        assert_eq!(
+            SessionDate::from_path("01312020.NASDAQ_ITCH50"),
+            Some(SessionDate::new(2020, 1, 31))
+        );
+    }
+
+    #[test]
+    fn test_sessions_are_chained_in_date_order() {
+        let mut day1 = system_event(1, b'O');
+        day1.extend(add_order(1, 1, true, 100, 500));
+        let mut day2 = system_event(2, b'O');
+        day2.extend(add_order(1, 1, false, 10, 600));
+        day2.extend(delete_order(1, 1));
+        let (dir, paths) = write_sessions(
+            "chain",
+            &[
+                ("01022020.NASDAQ_ITCH50", day2),
+                ("12312019.NASDAQ_ITCH50", day1),
+            ],
+        );
+
+        let stream = MultiFileStream::from_files(&paths).unwrap();
+        let events: Vec<SessionEvent> = stream.map(|event| event.unwrap()).collect();
+
+        let day1 = SessionDate::new(2019, 12, 31);
+        let day2 = SessionDate::new(2020, 1, 2);
+        let framing: Vec<String> = events
+            .iter()
+            .map(|event| match event {
+                SessionEvent::SessionStart(date) => format!("start {}", date),
+                SessionEvent::Message(date, msg) => format!("{} {}", date, msg.tag as char),
+                SessionEvent::SessionEnd(date) => format!("end {}", date),
+            })
+            .collect();
+        assert_eq!(
+            framing,
+            vec![
+                format!("start {}", day1),
+                format!("{} S", day1),
+                format!("{} A", day1),
+                format!("end {}", day1),
+                format!("start {}", day2),
+                format!("{} S", day2),
+                format!("{} A", day2),
+                format!("{} D", day2),
+                format!("end {}", day2),
+            ]
+        );
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_orderbook_reset_between_sessions() {
+        let day1 = add_order(1, 1, true, 100, 500);
+        let day2 = add_order(1, 1, true, 30, 500);
+        let (dir, paths) = write_sessions(
+            "reset",
+            &[
+                ("12302019.NASDAQ_ITCH50", day1),
+                ("12312019.NASDAQ_ITCH50", day2),
+            ],
+        );
+
+        let mut orderbook_manager = OrderBookManager::new();
+        let mut sizes = Vec::new();
+        for event in MultiFileStream::from_files(&paths).unwrap() {
+            match event.unwrap() {
+                SessionEvent::Message(_, msg) => {
+                    if let Body::AddOrder {
+                        order_id,
+                        is_bid,
+                        shares,
+                        price,
+                        ..
+                    } = msg.body
+                    {
+                        orderbook_manager.add_order(
+                            OrderId(order_id),
+                            BookId(msg.stock_locate),
+                            Qty(shares),
+                            price,
+                            is_bid,
+                        );
+                    }
+                }
+                SessionEvent::SessionEnd(_) => {
+                    sizes.push(get_level_capacity(&orderbook_manager, 1, 0));
+                    orderbook_manager.reset();
+                }
+                SessionEvent::SessionStart(_) => {}
+            }
+        }
+
+        // Without the reset, the second session would see 130 shares at the level.
+        assert_eq!(sizes, vec![Qty(100), Qty(30)]);
+        assert!(orderbook_manager.books.iter().all(|book| book.is_none()));
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
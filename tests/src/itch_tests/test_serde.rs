@@ -0,0 +1,108 @@
+#[cfg(test)]
+mod tests {
+    use itch_parser::{Body, EventCode, Message};
+
+    fn message(tag: u8, body: Body) -> Message {
+        Message {
+            tag,
+            stock_locate: 7,
+            tracking_number: 2,
+            timestamp: 34_200_000_000_000,
+            body,
+        }
+    }
+
+    #[test]
+    fn test_message_json_round_trip() {
+        let messages = vec![
+            message(
+                b'A',
+                Body::AddOrder {
+                    order_id: u64::MAX,
+                    is_bid: true,
+                    shares: 100,
+                    stock: u64::from_be_bytes(*b"AAPL    "),
+                    price: 1_234_500,
+                },
+            ),
+            message(b'D', Body::DeleteOrder { order_id: 1 }),
+            message(
+                b'X',
+                Body::OrderCancelled {
+                    order_id: 1,
+                    shares: 10,
+                },
+            ),
+            message(
+                b'E',
+                Body::OrderExecuted {
+                    order_id: 1,
+                    shares: 10,
+                    match_number: 99,
+                },
+            ),
+            message(
+                b'C',
+                Body::OrderExecutedWithPrice {
+                    order_id: 1,
+                    shares: 10,
+                    match_number: 99,
+                    printable: false,
+                    price: 5000,
+                },
+            ),
+            message(
+                b'U',
+                Body::ReplaceOrder {
+                    old_order_id: 1,
+                    new_order_id: 2,
+                    shares: 10,
+                    price: 5000,
+                },
+            ),
+            message(
+                b'S',
+                Body::SystemEvent {
+                    event: EventCode::EndOfMarketHours,
+                },
+            ),
+            message(
+                b'R',
+                Body::StockDirectory {
+                    stock: u64::from_be_bytes(*b"MSFT    "),
+                },
+            ),
+            message(b'W', Body::Pass(())),
+        ];
+
+        let json = serde_json::to_string(&messages).unwrap();
+        let decoded: Vec<Message> = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(decoded, messages);
+    }
+
+    #[test]
+    fn test_message_json_golden() {
+        let msg = message(b'D', Body::DeleteOrder { order_id: 42 });
+
+        let json = serde_json::to_string(&msg).unwrap();
+
+        assert_eq!(
+            json,
+            r#"{"tag":68,"stock_locate":7,"tracking_number":2,"timestamp":34200000000000,"body":{"DeleteOrder":{"order_id":42}}}"#
+        );
+        assert_eq!(serde_json::from_str::<Message>(&json).unwrap(), msg);
+    }
+
+    #[test]
+    fn test_event_code_json() {
+        assert_eq!(
+            serde_json::to_string(&EventCode::StartOfMessages).unwrap(),
+            r#""StartOfMessages""#
+        );
+        assert_eq!(
+            serde_json::from_str::<EventCode>(r#""EndOfMessages""#).unwrap(),
+            EventCode::EndOfMessages
+        );
+    }
+}
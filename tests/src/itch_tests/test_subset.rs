@@ -0,0 +1,140 @@
+#[cfg(test)]
+mod tests {
+    use crate::itch_tests::utils::{add_order, replay, stock_directory, system_event};
+    use itch_parser::{
+        extract_subset, parse_time_of_day, Generator, GeneratorConfig, Message, MessageStream,
+        MessageWriter, SubsetFilter,
+    };
+    use optimized_lob::{order::OrderId, quantity::Qty, utils::BookId};
+
+    fn generate() -> Vec<u8> {
+        let mut writer = MessageWriter::from_writer(Vec::new());
+        let config = GeneratorConfig {
+            seed: 3,
+            order_messages: 30_000,
+            ..GeneratorConfig::default()
+        };
+        Generator::new(config).generate(&mut writer).unwrap();
+        writer.into_inner().unwrap()
+    }
+
+    fn subset(data: &[u8], filter: &SubsetFilter) -> Vec<u8> {
+        let mut stream = MessageStream::from_reader(data);
+        let mut writer = MessageWriter::from_writer(Vec::new());
+        extract_subset(&mut stream, &mut writer, filter).unwrap();
+        writer.into_inner().unwrap()
+    }
+
+    fn parse(data: &[u8]) -> Vec<Message> {
+        MessageStream::from_reader(data)
+            .map(|msg| msg.unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn test_parse_time_of_day() {
+        assert_eq!(parse_time_of_day("09:30:00"), Some(34_200_000_000_000));
+        assert_eq!(parse_time_of_day("09:30:00.5"), Some(34_200_500_000_000));
+        assert_eq!(parse_time_of_day("00:00:00.000000001"), Some(1));
+        assert_eq!(parse_time_of_day("24:00:00"), None);
+        assert_eq!(parse_time_of_day("09:30"), None);
+        assert_eq!(parse_time_of_day("09:30:00:00"), None);
+    }
+
+    #[test]
+    fn test_next_with_frame_returns_raw_bytes() {
+        let first = system_event(1, b'O');
+        let second = add_order(2, 5, true, 100, 500);
+        let data = [first.clone(), second.clone()].concat();
+        let mut stream = MessageStream::from_reader(&data[..]);
+
+        let (msg, frame) = stream.next_with_frame().unwrap().unwrap();
+        assert_eq!(msg.tag, b'S');
+        assert_eq!(frame, &first[..]);
+        let (msg, frame) = stream.next_with_frame().unwrap().unwrap();
+        assert_eq!(msg.tag, b'A');
+        assert_eq!(frame, &second[..]);
+        assert!(stream.next_with_frame().is_none());
+    }
+
+    #[test]
+    fn test_symbol_subset_keeps_every_message_of_the_symbol() {
+        let data = generate();
+        let full = parse(&data);
+
+        let messages = parse(&subset(&data, &SubsetFilter::new().with_symbols(&["MSFT"])));
+
+        // MSFT is the second symbol of the generator, so it has stock locate 2.
+        let expected: Vec<Message> = full
+            .into_iter()
+            .filter(|msg| msg.stock_locate == 2 || msg.tag == b'S')
+            .collect();
+        assert_eq!(messages, expected);
+        assert_eq!(messages.iter().filter(|msg| msg.tag == b'R').count(), 1);
+    }
+
+    #[test]
+    fn test_window_subset_replays_cleanly() {
+        let data = generate();
+        let full = parse(&data);
+        let start = full[full.len() / 2].timestamp;
+        let end = full[full.len() * 3 / 4].timestamp;
+
+        let filter = SubsetFilter::new()
+            .with_symbols(&["AAPL"])
+            .with_window(Some(start), Some(end));
+        let messages = parse(&subset(&data, &filter));
+
+        assert!(messages
+            .iter()
+            .all(|msg| msg.tag == b'S' || msg.stock_locate == 1));
+        assert!(messages
+            .iter()
+            .filter(|msg| msg.tag != b'S' && msg.tag != b'R')
+            .all(|msg| msg.timestamp >= start && msg.timestamp <= end));
+        assert!(messages
+            .iter()
+            .filter(|msg| msg.tag != b'R')
+            .collect::<Vec<_>>()
+            .windows(2)
+            .all(|pair| pair[0].timestamp <= pair[1].timestamp));
+
+        // The subset replays without unknown orders and ends in the same state as the AAPL
+        // book of the full replay at the end of the window.
+        let (subset_book, subset_live) = replay(&messages);
+        let until_end: Vec<Message> = full
+            .into_iter()
+            .filter(|msg| msg.timestamp <= end || msg.tag == b'S')
+            .collect();
+        let (full_book, mut full_live) = replay(&until_end);
+        full_live.retain(|order_id, _| {
+            full_book.oid_map.get(OrderId(*order_id)).unwrap().book_id() == BookId(1)
+        });
+        assert!(!subset_live.is_empty());
+        assert_eq!(subset_live, full_live);
+        for (order_id, shares) in subset_live {
+            let order = subset_book.oid_map.get(OrderId(order_id)).unwrap();
+            assert_eq!(order.qty(), Qty(shares));
+        }
+    }
+
+    #[test]
+    fn test_live_orders_are_recreated_when_the_window_opens() {
+        let mut data = system_event(10, b'O');
+        data.extend(stock_directory(1, "AAPL"));
+        data.extend(add_order(1, 5, true, 100, 500));
+        data.extend(system_event(200, b'Q'));
+
+        // Only a system event falls within the window.
+        let filter = SubsetFilter::new().with_window(Some(100), None);
+        let messages = parse(&subset(&data, &filter));
+
+        let tags: Vec<u8> = messages.iter().map(|msg| msg.tag).collect();
+        assert_eq!(tags, b"SRAS");
+        assert_eq!(messages[2].timestamp, 200);
+        assert_eq!(
+            messages[2].body,
+            parse(&add_order(1, 5, true, 100, 500))[0].body
+        );
+    }
+}
@@ -0,0 +1,143 @@
+// A few helper functions for building and replaying raw ITCH messages in the tests
+
+#[cfg(test)]
+use itch_parser::{Body, Message};
+#[cfg(test)]
+use optimized_lob::{
+    order::OrderId, orderbook_manager::OrderBookManager, quantity::Qty, utils::BookId,
+};
+#[cfg(test)]
+use std::collections::HashMap;
+
+/// Frames a message with the 2-byte length prefix and the common header.
+#[cfg(test)]
+pub(crate) fn frame(tag: u8, stock_locate: u16, timestamp: u64, body: &[u8]) -> Vec<u8> {
+    let len = (11 + body.len()) as u16;
+    let mut buf = Vec::with_capacity(2 + len as usize);
+    buf.extend_from_slice(&len.to_be_bytes());
+    buf.push(tag);
+    buf.extend_from_slice(&stock_locate.to_be_bytes());
+    buf.extend_from_slice(&0u16.to_be_bytes());
+    buf.extend_from_slice(&timestamp.to_be_bytes()[2..]);
+    buf.extend_from_slice(body);
+    buf
+}
+
+#[cfg(test)]
+pub(crate) fn system_event(timestamp: u64, event: u8) -> Vec<u8> {
+    frame(b'S', 0, timestamp, &[event])
+}
+
+#[cfg(test)]
+pub(crate) fn stock_directory(stock_locate: u16, symbol: &str) -> Vec<u8> {
+    let mut body = format!("{:<8}", symbol).into_bytes();
+    body.extend_from_slice(&[b' '; 20]);
+    frame(b'R', stock_locate, 0, &body)
+}
+
+#[cfg(test)]
+pub(crate) fn add_order(
+    stock_locate: u16,
+    order_id: u64,
+    is_bid: bool,
+    shares: u32,
+    price: u32,
+) -> Vec<u8> {
+    let mut body = Vec::new();
+    body.extend_from_slice(&order_id.to_be_bytes());
+    body.push(if is_bid { b'B' } else { b'S' });
+    body.extend_from_slice(&shares.to_be_bytes());
+    body.extend_from_slice(b"AAPL    ");
+    body.extend_from_slice(&price.to_be_bytes());
+    frame(b'A', stock_locate, 0, &body)
+}
+
+#[cfg(test)]
+pub(crate) fn execute_order(stock_locate: u16, order_id: u64, shares: u32) -> Vec<u8> {
+    let mut body = Vec::new();
+    body.extend_from_slice(&order_id.to_be_bytes());
+    body.extend_from_slice(&shares.to_be_bytes());
+    body.extend_from_slice(&1u64.to_be_bytes());
+    frame(b'E', stock_locate, 0, &body)
+}
+
+#[cfg(test)]
+pub(crate) fn delete_order(stock_locate: u16, order_id: u64) -> Vec<u8> {
+    frame(b'D', stock_locate, 0, &order_id.to_be_bytes())
+}
+
+#[cfg(test)]
+pub(crate) fn mwcb_status(stock_locate: u16) -> Vec<u8> {
+    frame(b'W', stock_locate, 0, b"1")
+}
+
+/// Replays the messages into a new orderbook, checking that every order message refers to a
+/// live order. Returns the orderbook and the remaining shares of the live orders.
+#[cfg(test)]
+pub(crate) fn replay(messages: &[Message]) -> (OrderBookManager, HashMap<u64, u32>) {
+    let mut orderbook_manager = OrderBookManager::new();
+    let mut live = HashMap::new();
+    replay_into(&mut orderbook_manager, &mut live, messages);
+    (orderbook_manager, live)
+}
+
+/// Replays the messages into an existing orderbook, whose live orders and their remaining
+/// shares are in `live`, checking that every order message refers to a live order.
+#[cfg(test)]
+pub(crate) fn replay_into(
+    orderbook_manager: &mut OrderBookManager,
+    live: &mut HashMap<u64, u32>,
+    messages: &[Message],
+) {
+    for msg in messages {
+        match msg.body {
+            Body::AddOrder {
+                order_id,
+                is_bid,
+                shares,
+                price,
+                ..
+            } => {
+                orderbook_manager.add_order(
+                    OrderId(order_id),
+                    BookId(msg.stock_locate),
+                    Qty(shares),
+                    price,
+                    is_bid,
+                );
+                assert!(live.insert(order_id, shares).is_none());
+            }
+            Body::OrderExecuted {
+                order_id, shares, ..
+            } => {
+                orderbook_manager.execute_order(OrderId(order_id), Qty(shares));
+                *live.get_mut(&order_id).expect("unknown order") -= shares;
+            }
+            Body::OrderCancelled { order_id, shares } => {
+                orderbook_manager.cancel_order(OrderId(order_id), Qty(shares));
+                *live.get_mut(&order_id).expect("unknown order") -= shares;
+            }
+            Body::DeleteOrder { order_id } => {
+                orderbook_manager.remove_order(OrderId(order_id));
+                live.remove(&order_id).expect("unknown order");
+            }
+            Body::ReplaceOrder {
+                old_order_id,
+                new_order_id,
+                shares,
+                price,
+            } => {
+                orderbook_manager.replace_order(
+                    OrderId(old_order_id),
+                    OrderId(new_order_id),
+                    Qty(shares),
+                    price,
+                );
+                live.remove(&old_order_id).expect("unknown order");
+                live.insert(new_order_id, shares);
+            }
+            _ => {}
+        }
+    }
+    live.retain(|_, shares| *shares > 0);
+}
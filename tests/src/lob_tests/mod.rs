@@ -1,4 +1,19 @@
 mod same_book_test;
 mod same_level_and_book_test;
+mod test_allocation;
+mod test_depth;
+mod test_events;
+mod test_iceberg;
+mod test_journal;
+mod test_l3_book;
+mod test_market_data;
+mod test_matching;
+mod test_modify;
+mod test_oid_map;
 mod test_order;
-mod utils;
+mod test_order_flags;
+mod test_pegged_orders;
+mod test_self_trade;
+mod test_snapshot;
+mod test_stop_orders;
+pub(crate) mod utils;
@@ -48,4 +48,25 @@ mod test {
         orderbook_manager.add_order(OrderId(6), BookId(1), Qty(96), 1300, true);
         assert_eq!(Qty(96), get_level_capacity(&orderbook_manager, 1, 3));
     }
+
+    #[test]
+    fn test_for_order_ids_beyond_u32() {
+        let mut orderbook_manager = OrderBookManager::new();
+        let large_id = OrderId(u32::MAX as u64 + 10);
+
+        orderbook_manager.add_order(OrderId(0), BookId(1), Qty(800), 500, true);
+        orderbook_manager.add_order(large_id, BookId(1), Qty(50), 500, true);
+        assert_eq!(Qty(850), get_level_capacity(&orderbook_manager, 1, 0));
+
+        orderbook_manager.execute_order(large_id, Qty(20));
+        assert_eq!(Qty(830), get_level_capacity(&orderbook_manager, 1, 0));
+
+        orderbook_manager.replace_order(large_id, OrderId(u64::MAX), Qty(40), 600);
+        assert_eq!(Qty(800), get_level_capacity(&orderbook_manager, 1, 0));
+        assert_eq!(Qty(40), get_level_capacity(&orderbook_manager, 1, 1));
+
+        orderbook_manager.remove_order(OrderId(u64::MAX));
+        assert_eq!(Qty(0), get_level_capacity(&orderbook_manager, 1, 1));
+        assert!(orderbook_manager.oid_map.get(OrderId(u64::MAX)).is_none());
+    }
 }
@@ -0,0 +1,152 @@
+#[cfg(test)]
+mod tests {
+    use crate::lob_tests::utils::fills;
+    use optimized_lob::allocation::{AllocationPolicy, Fifo, ProRata, TopOrderProRata};
+    use optimized_lob::l3_orderbook::L3OrderBook;
+    use optimized_lob::matching::OrderRequest;
+    use optimized_lob::order::{OidMap, OrderId};
+    use optimized_lob::orderbook_manager::OrderBookManager;
+    use optimized_lob::quantity::Qty;
+    use optimized_lob::side::Side;
+    use optimized_lob::utils::BookId;
+
+    fn allocate<A: AllocationPolicy>(policy: &A, qty: u32, orders: &[u32]) -> Vec<u32> {
+        let orders: Vec<Qty> = orders.iter().map(|qty| Qty(*qty)).collect();
+        policy
+            .allocate(Qty(qty), &orders)
+            .iter()
+            .map(|allocation| allocation.value())
+            .collect()
+    }
+
+    #[test]
+    fn test_fifo() {
+        assert_eq!(allocate(&Fifo, 150, &[100, 30, 50]), vec![100, 30, 20]);
+        assert_eq!(allocate(&Fifo, 500, &[100, 30, 50]), vec![100, 30, 50]);
+        assert!(allocate(&Fifo, 10, &[]).is_empty());
+    }
+
+    #[test]
+    fn test_pro_rata() {
+        let policy = ProRata::default();
+        assert_eq!(allocate(&policy, 300, &[100, 200, 300]), vec![50, 100, 150]);
+        // 16.67, 33.33 and 50 are rounded down, and the remaining lot goes to the oldest order.
+        assert_eq!(allocate(&policy, 100, &[100, 200, 300]), vec![17, 33, 50]);
+        assert_eq!(
+            allocate(&policy, 700, &[100, 200, 300]),
+            vec![100, 200, 300]
+        );
+        assert_eq!(allocate(&policy, 1, &[100, 200, 300]), vec![1, 0, 0]);
+    }
+
+    #[test]
+    fn test_pro_rata_minimum_allocation() {
+        let policy = ProRata { min_qty: Qty(5) };
+        assert_eq!(
+            allocate(&ProRata::default(), 51, &[300, 200, 10]),
+            vec![30, 20, 1]
+        );
+        // The share of 1 of the last order is below the minimum and goes to the oldest order.
+        assert_eq!(allocate(&policy, 51, &[300, 200, 10]), vec![31, 20, 0]);
+        // No share reaches the minimum: everything is allocated in time priority.
+        assert_eq!(allocate(&policy, 8, &[10, 10, 10]), vec![8, 0, 0]);
+    }
+
+    #[test]
+    fn test_top_order_pro_rata() {
+        let policy = TopOrderProRata::default();
+        assert_eq!(allocate(&policy, 150, &[50, 100, 300]), vec![50, 25, 75]);
+        assert_eq!(allocate(&policy, 151, &[50, 100, 300]), vec![50, 26, 75]);
+        assert_eq!(allocate(&policy, 40, &[50, 100, 300]), vec![40, 0, 0]);
+        assert_eq!(allocate(&policy, 40, &[50]), vec![40]);
+        assert!(allocate(&policy, 40, &[]).is_empty());
+
+        // The share of 25 of the last order is below the minimum and goes to the oldest order
+        // after the top order.
+        let policy = TopOrderProRata { min_qty: Qty(30) };
+        assert_eq!(allocate(&policy, 150, &[50, 300, 100]), vec![50, 100, 0]);
+    }
+
+    #[test]
+    fn test_allocations_add_up() {
+        fn check<A: AllocationPolicy>(policy: &A) {
+            let levels: [&[u32]; 4] = [&[1], &[7, 3, 11, 5], &[100, 1, 1, 1, 100], &[13; 9]];
+            for orders in levels {
+                let total: u32 = orders.iter().sum();
+                for qty in 0..=total + 2 {
+                    let allocations = allocate(policy, qty, orders);
+                    assert_eq!(allocations.len(), orders.len());
+                    assert_eq!(allocations.iter().sum::<u32>(), qty.min(total));
+                    assert!(allocations.iter().zip(orders).all(|(a, o)| a <= o));
+                }
+            }
+        }
+        check(&Fifo);
+        check(&ProRata { min_qty: Qty(2) });
+        check(&TopOrderProRata { min_qty: Qty(2) });
+    }
+
+    #[test]
+    fn test_pro_rata_matching() {
+        let mut orderbook_manager: OrderBookManager<OidMap, L3OrderBook<ProRata>> =
+            OrderBookManager::from_oid_map(OidMap::new());
+        orderbook_manager.add_order(OrderId(1), BookId(1), Qty(100), 1000, false);
+        orderbook_manager.add_order(OrderId(2), BookId(1), Qty(300), 1000, false);
+        orderbook_manager.add_order(OrderId(3), BookId(1), Qty(100), 1010, false);
+
+        let request = OrderRequest::market(OrderId(10), BookId(1), Side::Bid, Qty(100));
+        let execution = orderbook_manager.submit_order(&request);
+        assert_eq!(fills(&execution), vec![(1, 25), (2, 75)]);
+
+        // The remainder of the level is allocated before the next level is reached.
+        let request = OrderRequest::market(OrderId(11), BookId(1), Side::Bid, Qty(350));
+        let execution = orderbook_manager.submit_order(&request);
+        assert_eq!(fills(&execution), vec![(1, 75), (2, 225), (3, 50)]);
+    }
+
+    #[test]
+    fn test_policy_per_book() {
+        let mut orderbook_manager: OrderBookManager<OidMap, L3OrderBook<TopOrderProRata>> =
+            OrderBookManager::from_oid_map(OidMap::new());
+        orderbook_manager.set_allocation_policy(BookId(1), TopOrderProRata { min_qty: Qty(30) });
+        for (book_id, first_id) in [(BookId(1), 10), (BookId(2), 20)] {
+            for (id, qty) in [(first_id, 50), (first_id + 1, 300), (first_id + 2, 100)] {
+                orderbook_manager.add_order(OrderId(id), book_id, Qty(qty), 1000, true);
+            }
+        }
+
+        let request = OrderRequest::market(OrderId(100), BookId(1), Side::Ask, Qty(150));
+        let execution = orderbook_manager.submit_order(&request);
+        assert_eq!(fills(&execution), vec![(10, 50), (11, 100)]);
+
+        let request = OrderRequest::market(OrderId(101), BookId(2), Side::Ask, Qty(150));
+        let execution = orderbook_manager.submit_order(&request);
+        assert_eq!(fills(&execution), vec![(20, 50), (21, 75), (22, 25)]);
+    }
+
+    #[test]
+    fn test_orders_without_quantity_are_skipped() {
+        let mut fifo = OrderBookManager::new_l3();
+        let mut pro_rata: OrderBookManager<OidMap, L3OrderBook<ProRata>> =
+            OrderBookManager::from_oid_map(OidMap::new());
+        fifo.add_order(OrderId(1), BookId(1), Qty(100), 600, false);
+        fifo.cancel_order(OrderId(1), Qty(100));
+        let execution = fifo.submit_limit_order(OrderId(2), BookId(1), Qty(10), 600, true);
+        assert!(execution.fills.is_empty());
+        assert_eq!(execution.resting, Qty(10));
+
+        // An order cancelled down to zero does not take a share of the level.
+        fn sample_level<A: AllocationPolicy>(
+            orderbook_manager: &mut OrderBookManager<OidMap, L3OrderBook<A>>,
+        ) {
+            orderbook_manager.add_order(OrderId(3), BookId(1), Qty(100), 610, false);
+            orderbook_manager.cancel_order(OrderId(3), Qty(100));
+            orderbook_manager.add_order(OrderId(4), BookId(1), Qty(50), 610, false);
+        }
+        sample_level(&mut fifo);
+        sample_level(&mut pro_rata);
+        let request = OrderRequest::market(OrderId(5), BookId(1), Side::Bid, Qty(30));
+        assert_eq!(fills(&fifo.submit_order(&request)), vec![(4, 30)]);
+        assert_eq!(fills(&pro_rata.submit_order(&request)), vec![(4, 30)]);
+    }
+}
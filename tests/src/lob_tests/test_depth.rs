@@ -0,0 +1,153 @@
+#[cfg(test)]
+mod tests {
+    use optimized_lob::level::DepthLevel;
+    use optimized_lob::order::OrderId;
+    use optimized_lob::orderbook_manager::OrderBookManager;
+    use optimized_lob::quantity::Qty;
+    use optimized_lob::side::Side;
+    use optimized_lob::utils::BookId;
+
+    fn level(price: u32, qty: u32, order_count: u32) -> DepthLevel {
+        DepthLevel {
+            price,
+            qty: Qty(qty),
+            total_qty: Qty(qty),
+            order_count,
+        }
+    }
+
+    fn sample_book() -> OrderBookManager {
+        let mut orderbook_manager = OrderBookManager::new();
+        orderbook_manager.add_order(OrderId(0), BookId(1), Qty(100), 990, true);
+        orderbook_manager.add_order(OrderId(1), BookId(1), Qty(50), 1000, true);
+        orderbook_manager.add_order(OrderId(2), BookId(1), Qty(25), 1000, true);
+        orderbook_manager.add_order(OrderId(3), BookId(1), Qty(10), 980, true);
+        orderbook_manager.add_order(OrderId(4), BookId(1), Qty(70), 1020, false);
+        orderbook_manager.add_order(OrderId(5), BookId(1), Qty(30), 1010, false);
+        orderbook_manager.add_order(OrderId(6), BookId(1), Qty(40), 1050, false);
+        orderbook_manager
+    }
+
+    #[test]
+    fn test_top_of_book() {
+        let orderbook_manager = sample_book();
+        let book = orderbook_manager.book(BookId(1)).unwrap();
+
+        assert_eq!(book.best_bid(), Some(level(1000, 75, 2)));
+        assert_eq!(book.best_ask(), Some(level(1010, 30, 1)));
+        assert_eq!(book.spread(), Some(10));
+        assert_eq!(book.mid(), Some(1005.0));
+    }
+
+    #[test]
+    fn test_depth() {
+        let orderbook_manager = sample_book();
+        let book = orderbook_manager.book(BookId(1)).unwrap();
+
+        assert_eq!(
+            book.depth(Side::Bid, 10),
+            vec![level(1000, 75, 2), level(990, 100, 1), level(980, 10, 1)]
+        );
+        assert_eq!(
+            book.depth(Side::Ask, 2),
+            vec![level(1010, 30, 1), level(1020, 70, 1)]
+        );
+        assert!(book.depth(Side::Ask, 0).is_empty());
+    }
+
+    #[test]
+    fn test_level_at() {
+        let orderbook_manager = sample_book();
+        let book = orderbook_manager.book(BookId(1)).unwrap();
+
+        assert_eq!(book.level_at(Side::Bid, 990), Some(level(990, 100, 1)));
+        assert_eq!(book.level_at(Side::Ask, 1050), Some(level(1050, 40, 1)));
+        assert_eq!(book.level_at(Side::Ask, 990), None);
+        assert_eq!(book.level_at(Side::Bid, 995), None);
+    }
+
+    #[test]
+    fn test_depth_follows_updates() {
+        let mut orderbook_manager = sample_book();
+
+        orderbook_manager.remove_order(OrderId(5));
+        orderbook_manager.execute_order(OrderId(1), Qty(50));
+        orderbook_manager.cancel_order(OrderId(2), Qty(5));
+
+        let book = orderbook_manager.book(BookId(1)).unwrap();
+        assert_eq!(book.best_bid(), Some(level(1000, 20, 1)));
+        assert_eq!(book.best_ask(), Some(level(1020, 70, 1)));
+        assert_eq!(book.spread(), Some(20));
+
+        orderbook_manager.remove_order(OrderId(2));
+        let book = orderbook_manager.book(BookId(1)).unwrap();
+        assert_eq!(book.best_bid(), Some(level(990, 100, 1)));
+        assert_eq!(book.level_at(Side::Bid, 1000), None);
+    }
+
+    #[test]
+    fn test_empty_book() {
+        let mut orderbook_manager = OrderBookManager::new();
+        assert!(orderbook_manager.book(BookId(1)).is_none());
+        assert!(orderbook_manager.book(BookId(u16::MAX)).is_none());
+
+        orderbook_manager.add_order(OrderId(0), BookId(1), Qty(10), 1000, true);
+        let book = orderbook_manager.book(BookId(1)).unwrap();
+        assert_eq!(book.best_ask(), None);
+        assert_eq!(book.spread(), None);
+        assert_eq!(book.mid(), None);
+        assert!(book.depth(Side::Ask, 5).is_empty());
+    }
+
+    #[test]
+    fn test_order_count() {
+        let mut orderbook_manager = OrderBookManager::new();
+        for id in 0..500 {
+            orderbook_manager.add_order(OrderId(id), BookId(1), Qty(20), 1000, true);
+        }
+        orderbook_manager.add_order(OrderId(500), BookId(1), Qty(10_000), 990, true);
+
+        let book = orderbook_manager.book(BookId(1)).unwrap();
+        assert_eq!(book.best_bid(), Some(level(1000, 10_000, 500)));
+        assert_eq!(book.level_at(Side::Bid, 990), Some(level(990, 10_000, 1)));
+
+        // Partial executions and cancels keep the order in the queue.
+        orderbook_manager.execute_order(OrderId(0), Qty(5));
+        orderbook_manager.cancel_order(OrderId(1), Qty(5));
+        let book = orderbook_manager.book(BookId(1)).unwrap();
+        assert_eq!(book.best_bid(), Some(level(1000, 9_990, 500)));
+
+        // Full fills, deletes and replaces take it out.
+        orderbook_manager.execute_order(OrderId(0), Qty(15));
+        orderbook_manager.remove_order(OrderId(2));
+        orderbook_manager.replace_order(OrderId(3), OrderId(501), Qty(20), 990);
+        let book = orderbook_manager.book(BookId(1)).unwrap();
+        assert_eq!(book.best_bid(), Some(level(1000, 9_935, 497)));
+        assert_eq!(book.level_at(Side::Bid, 990), Some(level(990, 10_020, 2)));
+
+        for id in 4..500 {
+            orderbook_manager.remove_order(OrderId(id));
+        }
+        orderbook_manager.remove_order(OrderId(1));
+        let book = orderbook_manager.book(BookId(1)).unwrap();
+        assert_eq!(book.level_at(Side::Bid, 1000), None);
+        assert_eq!(book.best_bid(), Some(level(990, 10_020, 2)));
+    }
+
+    #[test]
+    fn test_level_with_cancelled_order_is_kept() {
+        let mut orderbook_manager = OrderBookManager::new();
+        orderbook_manager.add_order(OrderId(1), BookId(1), Qty(100), 600, false);
+        orderbook_manager.add_order(OrderId(2), BookId(1), Qty(50), 600, false);
+
+        // Order 1 is cancelled down to zero but not deleted, so it still counts.
+        orderbook_manager.cancel_order(OrderId(1), Qty(100));
+        orderbook_manager.remove_order(OrderId(2));
+        let book = orderbook_manager.book(BookId(1)).unwrap();
+        assert_eq!(book.level_at(Side::Ask, 600), Some(level(600, 0, 1)));
+
+        orderbook_manager.remove_order(OrderId(1));
+        let book = orderbook_manager.book(BookId(1)).unwrap();
+        assert_eq!(book.level_at(Side::Ask, 600), None);
+    }
+}
@@ -0,0 +1,210 @@
+#[cfg(test)]
+mod tests {
+    use optimized_lob::events::{EventSink, NoopSink};
+    use optimized_lob::l3_orderbook::L3OrderBook;
+    use optimized_lob::matching::OrderRequest;
+    use optimized_lob::order::{OidMap, OrderId};
+    use optimized_lob::orderbook::OrderBook;
+    use optimized_lob::orderbook_manager::OrderBookManager;
+    use optimized_lob::peg::{PegSpec, PegType};
+    use optimized_lob::quantity::Qty;
+    use optimized_lob::side::Side;
+    use optimized_lob::utils::BookId;
+
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    enum Event {
+        Added(u64, Side, u32, u32),
+        Reduced(u64, u32, u32),
+        Removed(u64),
+        Trade(u64, Side, u32, u32),
+        Level(Side, u32, u32, u32),
+        Bbo(Option<(u32, Qty)>, Option<(u32, Qty)>),
+    }
+
+    /// Records every event of book 1.
+    #[derive(Debug, Default)]
+    struct Recorder {
+        events: Vec<Event>,
+    }
+
+    impl Recorder {
+        fn take(&mut self) -> Vec<Event> {
+            std::mem::take(&mut self.events)
+        }
+    }
+
+    impl EventSink for Recorder {
+        fn on_order_added(
+            &mut self,
+            book_id: BookId,
+            order_id: OrderId,
+            side: Side,
+            price: u32,
+            qty: Qty,
+        ) {
+            assert_eq!(book_id, BookId(1));
+            self.events
+                .push(Event::Added(order_id.0, side, price, qty.value()));
+        }
+
+        fn on_order_reduced(&mut self, _: BookId, order_id: OrderId, qty: Qty, remaining: Qty) {
+            self.events
+                .push(Event::Reduced(order_id.0, qty.value(), remaining.value()));
+        }
+
+        fn on_order_removed(&mut self, _: BookId, order_id: OrderId) {
+            self.events.push(Event::Removed(order_id.0));
+        }
+
+        fn on_trade(&mut self, _: BookId, order_id: OrderId, side: Side, price: u32, qty: Qty) {
+            self.events
+                .push(Event::Trade(order_id.0, side, price, qty.value()));
+        }
+
+        fn on_level_changed(
+            &mut self,
+            _: BookId,
+            side: Side,
+            price: u32,
+            new_qty: Qty,
+            order_count: u32,
+        ) {
+            self.events
+                .push(Event::Level(side, price, new_qty.value(), order_count));
+        }
+
+        fn on_bbo_changed(&mut self, _: BookId, bid: Option<(u32, Qty)>, ask: Option<(u32, Qty)>) {
+            self.events.push(Event::Bbo(bid, ask));
+        }
+    }
+
+    fn l3_manager() -> OrderBookManager<OidMap, L3OrderBook, Recorder> {
+        OrderBookManager::with_events(OidMap::new(), Recorder::default())
+    }
+
+    #[test]
+    fn test_add_reduce_and_execute_events() {
+        let mut orderbook_manager: OrderBookManager<OidMap, OrderBook, Recorder> =
+            OrderBookManager::from_oid_map(OidMap::new());
+        orderbook_manager.add_order(OrderId(1), BookId(1), Qty(100), 500, true);
+        orderbook_manager.add_order(OrderId(2), BookId(1), Qty(50), 490, true);
+        assert_eq!(
+            orderbook_manager.events.take(),
+            vec![
+                Event::Added(1, Side::Bid, 500, 100),
+                Event::Level(Side::Bid, 500, 100, 1),
+                Event::Bbo(Some((500, Qty(100))), None),
+                Event::Added(2, Side::Bid, 490, 50),
+                Event::Level(Side::Bid, 490, 50, 1),
+            ]
+        );
+
+        orderbook_manager.cancel_order(OrderId(1), Qty(30));
+        assert_eq!(
+            orderbook_manager.events.take(),
+            vec![
+                Event::Reduced(1, 30, 70),
+                Event::Level(Side::Bid, 500, 70, 1),
+                Event::Bbo(Some((500, Qty(70))), None),
+            ]
+        );
+
+        orderbook_manager.execute_order(OrderId(1), Qty(70));
+        assert_eq!(
+            orderbook_manager.events.take(),
+            vec![
+                Event::Trade(1, Side::Bid, 500, 70),
+                Event::Removed(1),
+                Event::Level(Side::Bid, 500, 0, 0),
+                Event::Bbo(Some((490, Qty(50))), None),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_matching_reports_every_trade() {
+        let mut orderbook_manager = l3_manager();
+        orderbook_manager.add_order(OrderId(1), BookId(1), Qty(100), 1000, false);
+        orderbook_manager.add_order(OrderId(2), BookId(1), Qty(100), 1000, false);
+        orderbook_manager.events.take();
+
+        let request = OrderRequest::limit(OrderId(3), BookId(1), Side::Bid, Qty(150), 1000);
+        orderbook_manager.submit_order(&request);
+        assert_eq!(
+            orderbook_manager.events.take(),
+            vec![
+                Event::Trade(1, Side::Ask, 1000, 100),
+                Event::Removed(1),
+                Event::Level(Side::Ask, 1000, 100, 1),
+                Event::Bbo(None, Some((1000, Qty(100)))),
+                Event::Trade(2, Side::Ask, 1000, 50),
+                Event::Reduced(2, 50, 50),
+                Event::Level(Side::Ask, 1000, 50, 1),
+                Event::Bbo(None, Some((1000, Qty(50)))),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_iceberg_replenish_is_removed_and_added() {
+        let mut orderbook_manager = l3_manager();
+        orderbook_manager.add_iceberg_order(OrderId(1), BookId(1), Qty(300), Qty(100), 1000, false);
+        orderbook_manager.events.take();
+
+        orderbook_manager.execute_order(OrderId(1), Qty(100));
+        assert_eq!(
+            orderbook_manager.events.take(),
+            vec![
+                Event::Trade(1, Side::Ask, 1000, 100),
+                Event::Removed(1),
+                Event::Added(1, Side::Ask, 1000, 100),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_hidden_orders_leave_levels_unchanged() {
+        let mut orderbook_manager = l3_manager();
+        orderbook_manager.add_order(OrderId(1), BookId(1), Qty(100), 1000, false);
+        orderbook_manager.events.take();
+
+        orderbook_manager.add_hidden_order(OrderId(2), BookId(1), Qty(100), 990, false);
+        assert_eq!(
+            orderbook_manager.events.take(),
+            vec![Event::Added(2, Side::Ask, 990, 100)]
+        );
+    }
+
+    #[test]
+    fn test_repriced_peg_is_removed_and_added() {
+        let mut orderbook_manager = l3_manager();
+        orderbook_manager.add_order(OrderId(1), BookId(1), Qty(100), 990, true);
+        orderbook_manager.add_order(OrderId(2), BookId(1), Qty(100), 1010, false);
+        let spec = PegSpec::new(PegType::Primary);
+        orderbook_manager.add_pegged_order(OrderId(3), BookId(1), Side::Bid, Qty(50), spec);
+        orderbook_manager.events.take();
+
+        orderbook_manager.add_order(OrderId(4), BookId(1), Qty(100), 995, true);
+        assert_eq!(
+            orderbook_manager.events.take(),
+            vec![
+                Event::Added(4, Side::Bid, 995, 100),
+                Event::Level(Side::Bid, 995, 100, 1),
+                Event::Bbo(Some((995, Qty(100))), Some((1010, Qty(100)))),
+                Event::Removed(3),
+                Event::Level(Side::Bid, 990, 100, 1),
+                Event::Added(3, Side::Bid, 995, 50),
+                Event::Level(Side::Bid, 995, 150, 2),
+                Event::Bbo(Some((995, Qty(150))), Some((1010, Qty(100)))),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_noop_sink_is_disabled() {
+        const { assert!(!NoopSink::ENABLED) };
+        let mut orderbook_manager = OrderBookManager::new();
+        orderbook_manager.add_order(OrderId(1), BookId(1), Qty(100), 500, true);
+        assert_eq!(orderbook_manager.events, NoopSink);
+    }
+}
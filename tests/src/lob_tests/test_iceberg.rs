@@ -0,0 +1,167 @@
+#[cfg(test)]
+mod tests {
+    use crate::lob_tests::utils::fills;
+    use optimized_lob::l3_orderbook::L3OrderBook;
+    use optimized_lob::matching::{ExecutionStatus, OrderRequest};
+    use optimized_lob::order::{OidMap, OrderId};
+    use optimized_lob::orderbook_manager::OrderBookManager;
+    use optimized_lob::quantity::Qty;
+    use optimized_lob::side::Side;
+    use optimized_lob::utils::BookId;
+
+    /// An iceberg ask of 300 showing 100 @ 1000, followed by a plain ask of 50 @ 1000.
+    fn sample_book() -> OrderBookManager<OidMap, L3OrderBook> {
+        let mut orderbook_manager = OrderBookManager::new_l3();
+        orderbook_manager.add_iceberg_order(OrderId(1), BookId(1), Qty(300), Qty(100), 1000, false);
+        orderbook_manager.add_order(OrderId(2), BookId(1), Qty(50), 1000, false);
+        orderbook_manager
+    }
+
+    fn buy(request_id: u64, qty: u32) -> OrderRequest {
+        OrderRequest::market(OrderId(request_id), BookId(1), Side::Bid, Qty(qty))
+    }
+
+    #[test]
+    fn test_displayed_and_hidden_quantity() {
+        let mut orderbook_manager = OrderBookManager::new_l3();
+        orderbook_manager.add_iceberg_order(
+            OrderId(1),
+            BookId(1),
+            Qty(1000),
+            Qty(100),
+            1000,
+            false,
+        );
+        orderbook_manager.add_order(OrderId(2), BookId(1), Qty(50), 1000, false);
+
+        let book = orderbook_manager.book(BookId(1)).unwrap();
+        let level = book.best_ask().unwrap();
+        assert_eq!(level.qty, Qty(150));
+        assert_eq!(level.total_qty, Qty(1050));
+        assert_eq!(level.order_count, 2);
+
+        // Executing the displayed peak displays the next one.
+        orderbook_manager.execute_order(OrderId(1), Qty(100));
+        let order = orderbook_manager.oid_map.get(OrderId(1)).unwrap();
+        assert_eq!(order.qty(), Qty(100));
+        let node = orderbook_manager
+            .book(BookId(1))
+            .unwrap()
+            .node(OrderId(1))
+            .unwrap();
+        assert_eq!(node.qty(), Qty(100));
+        assert_eq!(node.attributes().hidden, Qty(800));
+        let level = orderbook_manager
+            .book(BookId(1))
+            .unwrap()
+            .best_ask()
+            .unwrap();
+        assert_eq!(level.qty, Qty(150));
+        assert_eq!(level.total_qty, Qty(950));
+
+        // A partial execution only reduces the displayed quantity.
+        orderbook_manager.execute_order(OrderId(1), Qty(40));
+        let level = orderbook_manager
+            .book(BookId(1))
+            .unwrap()
+            .best_ask()
+            .unwrap();
+        assert_eq!(level.qty, Qty(110));
+        assert_eq!(level.total_qty, Qty(910));
+
+        // Removing the order removes its reserve as well.
+        orderbook_manager.remove_order(OrderId(1));
+        let level = orderbook_manager
+            .book(BookId(1))
+            .unwrap()
+            .best_ask()
+            .unwrap();
+        assert_eq!(level.qty, Qty(50));
+        assert_eq!(level.total_qty, Qty(50));
+        orderbook_manager.remove_order(OrderId(2));
+        assert!(orderbook_manager
+            .book(BookId(1))
+            .unwrap()
+            .best_ask()
+            .is_none());
+    }
+
+    #[test]
+    fn test_small_iceberg_is_fully_displayed() {
+        let mut orderbook_manager = OrderBookManager::new_l3();
+        orderbook_manager.add_iceberg_order(OrderId(1), BookId(1), Qty(80), Qty(100), 1000, true);
+        let node = orderbook_manager
+            .book(BookId(1))
+            .unwrap()
+            .node(OrderId(1))
+            .unwrap();
+        assert!(!node.attributes().is_iceberg());
+        assert_eq!(node.qty(), Qty(80));
+        assert_eq!(node.attributes().hidden, Qty(0));
+    }
+
+    #[test]
+    fn test_replenished_peak_loses_priority() {
+        let mut orderbook_manager = sample_book();
+
+        let execution = orderbook_manager.submit_order(&buy(10, 150));
+        assert_eq!(fills(&execution), vec![(1, 100), (2, 50)]);
+
+        let book = orderbook_manager.book(BookId(1)).unwrap();
+        let front = book.front(Side::Ask).unwrap();
+        assert_eq!(front.order_id(), OrderId(1));
+        assert_eq!(front.qty(), Qty(100));
+        let level = book.best_ask().unwrap();
+        assert_eq!(level.qty, Qty(100));
+        assert_eq!(level.total_qty, Qty(200));
+    }
+
+    #[test]
+    fn test_matching_consumes_hidden_quantity() {
+        let mut orderbook_manager = sample_book();
+
+        let execution = orderbook_manager.submit_order(&buy(10, 320));
+        assert_eq!(execution.status, ExecutionStatus::Filled);
+        assert_eq!(
+            fills(&execution),
+            vec![(1, 100), (2, 50), (1, 100), (1, 70)]
+        );
+
+        let order = orderbook_manager.oid_map.get(OrderId(1)).unwrap();
+        assert_eq!(order.qty(), Qty(30));
+        let node = orderbook_manager
+            .book(BookId(1))
+            .unwrap()
+            .node(OrderId(1))
+            .unwrap();
+        assert_eq!(node.attributes().hidden, Qty(0));
+
+        orderbook_manager.submit_order(&buy(11, 30));
+        assert!(orderbook_manager.oid_map.get(OrderId(1)).is_none());
+        assert!(orderbook_manager
+            .book(BookId(1))
+            .unwrap()
+            .best_ask()
+            .is_none());
+    }
+
+    #[test]
+    fn test_fill_or_kill_counts_hidden_quantity() {
+        let mut orderbook_manager = sample_book();
+
+        let request = OrderRequest::fill_or_kill(OrderId(10), BookId(1), Side::Bid, Qty(351), 1000);
+        assert!(matches!(
+            orderbook_manager.submit_order(&request).status,
+            ExecutionStatus::Rejected(_)
+        ));
+
+        let request = OrderRequest::fill_or_kill(OrderId(11), BookId(1), Side::Bid, Qty(350), 1000);
+        let execution = orderbook_manager.submit_order(&request);
+        assert_eq!(execution.status, ExecutionStatus::Filled);
+        assert!(orderbook_manager
+            .book(BookId(1))
+            .unwrap()
+            .best_ask()
+            .is_none());
+    }
+}
@@ -0,0 +1,267 @@
+#[cfg(test)]
+mod tests {
+    use optimized_lob::error::JournalError;
+    use optimized_lob::journal::{Command, Journal, JournalRecord};
+    use optimized_lob::l3_orderbook::L3OrderBook;
+    use optimized_lob::order::{OidMap, OrderId};
+    use optimized_lob::orderbook_manager::OrderBookManager;
+    use optimized_lob::quantity::Qty;
+    use optimized_lob::utils::BookId;
+    use std::fs::{self, OpenOptions};
+    use std::path::PathBuf;
+
+    /// Returns a fresh journal path in the temporary directory, unique to the test.
+    fn journal_path(name: &str) -> PathBuf {
+        let path = std::env::temp_dir().join(format!(
+            "lob-journal-{}-{}.journal",
+            std::process::id(),
+            name
+        ));
+        let _ = fs::remove_file(&path);
+        path
+    }
+
+    fn add(order_id: u64, qty: u32, price: u32, is_bid: bool) -> Command {
+        Command::Add {
+            order_id: OrderId(order_id),
+            book_id: BookId(1),
+            qty: Qty(qty),
+            price,
+            is_bid,
+        }
+    }
+
+    fn commands() -> Vec<Command> {
+        vec![
+            add(1, 100, 500, true),
+            add(2, 200, 510, false),
+            add(3, 50, 500, true),
+            Command::Cancel {
+                order_id: OrderId(1),
+                qty: Qty(30),
+            },
+            Command::Execute {
+                order_id: OrderId(2),
+                qty: Qty(200),
+            },
+            Command::Replace {
+                order_id: OrderId(3),
+                new_order_id: OrderId(4),
+                qty: Qty(80),
+                price: 505,
+            },
+            add(5, 10, 520, false),
+            Command::Delete {
+                order_id: OrderId(5),
+            },
+        ]
+    }
+
+    #[test]
+    fn test_records_are_read_back_in_order() {
+        let path = journal_path("read-back");
+        let (mut journal, records) = Journal::open(&path).unwrap();
+        assert!(records.is_empty());
+        for (idx, command) in commands().iter().enumerate() {
+            assert_eq!(journal.append(command).unwrap(), idx as u64 + 1);
+        }
+        drop(journal);
+
+        let (mut journal, records) = Journal::open(&path).unwrap();
+        let expected: Vec<JournalRecord> = commands()
+            .into_iter()
+            .enumerate()
+            .map(|(idx, command)| JournalRecord {
+                seq: idx as u64 + 1,
+                command,
+            })
+            .collect();
+        assert_eq!(records, expected);
+        assert_eq!(journal.truncated(), 0);
+        assert_eq!(journal.append(&add(6, 1, 500, true)).unwrap(), 9);
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_torn_last_record_is_truncated() {
+        let path = journal_path("torn");
+        let (journal, _) = Journal::open(&path).unwrap();
+        let mut journal = journal.with_sync(false);
+        for command in &commands()[..3] {
+            journal.append(command).unwrap();
+        }
+        drop(journal);
+        let full_len = fs::metadata(&path).unwrap().len();
+
+        // The last record was only partly written when the process died.
+        let file = OpenOptions::new().write(true).open(&path).unwrap();
+        file.set_len(full_len - 5).unwrap();
+        drop(file);
+
+        let (mut journal, records) = Journal::open(&path).unwrap();
+        assert_eq!(records.len(), 2);
+        assert_eq!(journal.last_seq(), 2);
+        assert!(journal.truncated() > 0);
+        let truncated_len = fs::metadata(&path).unwrap().len();
+        assert_eq!(truncated_len, full_len - 5 - journal.truncated());
+
+        // The next record replaces the torn one.
+        assert_eq!(journal.append(&commands()[2]).unwrap(), 3);
+        drop(journal);
+        let (_, records) = Journal::open(&path).unwrap();
+        assert_eq!(records.len(), 3);
+        assert_eq!(records[2].command, commands()[2]);
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_corrupt_record_fails_its_checksum() {
+        let path = journal_path("corrupt");
+        let (mut journal, _) = Journal::open(&path).unwrap();
+        for command in &commands()[..3] {
+            journal.append(command).unwrap();
+        }
+        drop(journal);
+
+        let mut bytes = fs::read(&path).unwrap();
+        let last = bytes.len() - 1;
+        bytes[last] ^= 0xff;
+        fs::write(&path, &bytes).unwrap();
+
+        let (journal, records) = Journal::open(&path).unwrap();
+        assert_eq!(records.len(), 2);
+        assert_eq!(journal.last_seq(), 2);
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_corrupt_record_before_the_end_is_reported() {
+        let path = journal_path("corrupt-middle");
+        let (mut journal, _) = Journal::open(&path).unwrap();
+        journal.append(&commands()[0]).unwrap();
+        let offset = fs::metadata(&path).unwrap().len();
+        for command in &commands()[1..3] {
+            journal.append(command).unwrap();
+        }
+        drop(journal);
+
+        // A byte of the second record is damaged, but the third one is intact.
+        let mut bytes = fs::read(&path).unwrap();
+        bytes[offset as usize + 20] ^= 0xff;
+        fs::write(&path, &bytes).unwrap();
+        assert!(matches!(
+            Journal::open(&path),
+            Err(JournalError::Corrupt { seq: 2, offset: found }) if found == offset
+        ));
+        assert_eq!(fs::read(&path).unwrap(), bytes);
+
+        // So is a damaged length that hides the records after it.
+        bytes[offset as usize + 20] ^= 0xff;
+        bytes[offset as usize..offset as usize + 4].copy_from_slice(&u32::MAX.to_le_bytes());
+        fs::write(&path, &bytes).unwrap();
+        assert!(matches!(
+            Journal::open(&path),
+            Err(JournalError::Corrupt { seq: 2, .. })
+        ));
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_other_files_are_not_truncated() {
+        let path = journal_path("not-a-journal");
+        fs::write(&path, b"order book notes").unwrap();
+        assert!(matches!(Journal::open(&path), Err(JournalError::BadHeader)));
+        assert_eq!(fs::read(&path).unwrap(), b"order book notes");
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_recover_from_snapshot_and_journal_tail() {
+        let path = journal_path("recover");
+        let commands = commands();
+
+        let mut uninterrupted = OrderBookManager::new();
+        commands
+            .iter()
+            .for_each(|command| uninterrupted.apply(command));
+
+        let (mut journal, _) = Journal::open(&path).unwrap();
+        let mut orderbook_manager = OrderBookManager::new();
+        for command in &commands[..4] {
+            orderbook_manager.submit(&mut journal, command).unwrap();
+        }
+        let (snapshot, snapshot_seq) = (orderbook_manager.save(), journal.last_seq());
+        for command in &commands[4..] {
+            orderbook_manager.submit(&mut journal, command).unwrap();
+        }
+        // The process dies here.
+        drop((orderbook_manager, journal));
+
+        let (recovered, journal): (OrderBookManager, Journal) =
+            OrderBookManager::recover(Some((&snapshot, snapshot_seq)), &path).unwrap();
+        assert_eq!(journal.last_seq(), commands.len() as u64);
+        assert_eq!(recovered.save(), uninterrupted.save());
+
+        // Without a snapshot, the whole journal is replayed.
+        drop(journal);
+        let (recovered, _): (OrderBookManager, Journal) =
+            OrderBookManager::recover(None, &path).unwrap();
+        assert_eq!(recovered.save(), uninterrupted.save());
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_recover_l3_books() {
+        type Manager = OrderBookManager<OidMap, L3OrderBook>;
+        let path = journal_path("recover-l3");
+        let commands = commands();
+
+        let mut uninterrupted = OrderBookManager::new_l3();
+        commands
+            .iter()
+            .for_each(|command| uninterrupted.apply(command));
+
+        let (mut journal, _) = Journal::open(&path).unwrap();
+        let mut orderbook_manager = OrderBookManager::new_l3();
+        for command in &commands[..5] {
+            orderbook_manager.submit(&mut journal, command).unwrap();
+        }
+        let (snapshot, snapshot_seq) = (orderbook_manager.save(), journal.last_seq());
+        for command in &commands[5..] {
+            orderbook_manager.submit(&mut journal, command).unwrap();
+        }
+        drop((orderbook_manager, journal));
+
+        let (recovered, _): (Manager, Journal) =
+            OrderBookManager::recover(Some((&snapshot, snapshot_seq)), &path).unwrap();
+        assert_eq!(recovered.save(), uninterrupted.save());
+        assert_eq!(
+            recovered.queue_position(OrderId(4)),
+            uninterrupted.queue_position(OrderId(4))
+        );
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_recover_detects_missing_records() {
+        let path = journal_path("missing");
+        let (mut journal, _) = Journal::open(&path).unwrap();
+        let mut orderbook_manager = OrderBookManager::new();
+        orderbook_manager
+            .submit(&mut journal, &commands()[0])
+            .unwrap();
+        drop(journal);
+
+        let snapshot = orderbook_manager.save();
+        let recovered: Result<(OrderBookManager, Journal), _> =
+            OrderBookManager::recover(Some((&snapshot, 5)), &path);
+        assert!(matches!(
+            recovered,
+            Err(JournalError::MissingRecords {
+                expected: 5,
+                found: 1
+            })
+        ));
+        fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,204 @@
+#[cfg(test)]
+mod tests {
+    use crate::lob_tests::utils::position;
+    use optimized_lob::l3_orderbook::L3OrderBook;
+    use optimized_lob::oid_map::HashOidMap;
+    use optimized_lob::order::{OidMap, OrderId};
+    use optimized_lob::orderbook_manager::OrderBookManager;
+    use optimized_lob::quantity::Qty;
+    use optimized_lob::side::Side;
+    use optimized_lob::utils::BookId;
+
+    fn queue(
+        orderbook_manager: &OrderBookManager<OidMap, L3OrderBook>,
+        side: Side,
+        price: u32,
+    ) -> Vec<(u64, u32)> {
+        orderbook_manager
+            .book(BookId(1))
+            .unwrap()
+            .orders_at(side, price)
+            .map(|node| (node.order_id().0, node.qty().value()))
+            .collect()
+    }
+
+    #[test]
+    fn test_fifo_queue() {
+        let mut orderbook_manager = OrderBookManager::new_l3();
+        orderbook_manager.add_order(OrderId(10), BookId(1), Qty(100), 500, true);
+        orderbook_manager.add_order(OrderId(11), BookId(1), Qty(200), 500, true);
+        orderbook_manager.add_order(OrderId(12), BookId(1), Qty(300), 500, true);
+        orderbook_manager.add_order(OrderId(13), BookId(1), Qty(50), 490, true);
+
+        assert_eq!(
+            queue(&orderbook_manager, Side::Bid, 500),
+            vec![(10, 100), (11, 200), (12, 300)]
+        );
+        assert_eq!(
+            orderbook_manager.queue_position(OrderId(10)),
+            position(0, 0)
+        );
+        assert_eq!(
+            orderbook_manager.queue_position(OrderId(12)),
+            position(2, 300)
+        );
+        assert_eq!(
+            orderbook_manager.queue_position(OrderId(13)),
+            position(0, 0)
+        );
+        assert_eq!(orderbook_manager.queue_position(OrderId(99)), None);
+
+        let book = orderbook_manager.book(BookId(1)).unwrap();
+        assert_eq!(book.front(Side::Bid).unwrap().order_id(), OrderId(10));
+        assert!(book.front(Side::Ask).is_none());
+        assert_eq!(book.best_bid().unwrap().qty, Qty(600));
+        assert_eq!(book.best_bid().unwrap().order_count, 3);
+    }
+
+    #[test]
+    fn test_queue_updates() {
+        let mut orderbook_manager = OrderBookManager::new_l3();
+        for id in 0..4 {
+            orderbook_manager.add_order(OrderId(id), BookId(1), Qty(100), 700, false);
+        }
+
+        // A partial cancel keeps the time priority.
+        orderbook_manager.cancel_order(OrderId(1), Qty(40));
+        assert_eq!(
+            orderbook_manager.queue_position(OrderId(2)),
+            position(2, 160)
+        );
+
+        // A delete from the middle relinks the queue.
+        orderbook_manager.remove_order(OrderId(2));
+        assert_eq!(
+            queue(&orderbook_manager, Side::Ask, 700),
+            vec![(0, 100), (1, 60), (3, 100)]
+        );
+
+        // Executions consume the front of the queue.
+        orderbook_manager.execute_order(OrderId(0), Qty(30));
+        assert_eq!(
+            orderbook_manager.queue_position(OrderId(3)),
+            position(2, 130)
+        );
+        orderbook_manager.execute_order(OrderId(0), Qty(70));
+        assert_eq!(
+            queue(&orderbook_manager, Side::Ask, 700),
+            vec![(1, 60), (3, 100)]
+        );
+
+        // A replace loses the time priority, even at the same price.
+        orderbook_manager.replace_order(OrderId(1), OrderId(4), Qty(60), 700);
+        assert_eq!(
+            queue(&orderbook_manager, Side::Ask, 700),
+            vec![(3, 100), (4, 60)]
+        );
+
+        orderbook_manager.remove_order(OrderId(3));
+        orderbook_manager.remove_order(OrderId(4));
+        assert!(queue(&orderbook_manager, Side::Ask, 700).is_empty());
+        assert!(orderbook_manager
+            .book(BookId(1))
+            .unwrap()
+            .best_ask()
+            .is_none());
+
+        // The freed level and nodes are reused by new orders.
+        orderbook_manager.add_order(OrderId(5), BookId(1), Qty(10), 710, false);
+        orderbook_manager.add_order(OrderId(6), BookId(1), Qty(20), 710, false);
+        assert_eq!(
+            queue(&orderbook_manager, Side::Ask, 710),
+            vec![(5, 10), (6, 20)]
+        );
+    }
+
+    #[test]
+    fn test_level_is_not_reused_while_orders_are_queued() {
+        let mut orderbook_manager = OrderBookManager::new_l3();
+        orderbook_manager.add_order(OrderId(1), BookId(1), Qty(100), 600, false);
+        orderbook_manager.add_order(OrderId(2), BookId(1), Qty(100), 600, false);
+        orderbook_manager.cancel_order(OrderId(1), Qty(100));
+        orderbook_manager.remove_order(OrderId(2));
+
+        // Order 1 still rests at 600 with no quantity, so the level is kept.
+        assert_eq!(queue(&orderbook_manager, Side::Ask, 600), vec![(1, 0)]);
+        orderbook_manager.add_order(OrderId(3), BookId(1), Qty(50), 610, false);
+        assert_eq!(queue(&orderbook_manager, Side::Ask, 610), vec![(3, 50)]);
+        let book = orderbook_manager.book(BookId(1)).unwrap();
+        assert_eq!(book.level_at(Side::Ask, 610).unwrap().order_count, 1);
+
+        orderbook_manager.remove_order(OrderId(1));
+        assert!(queue(&orderbook_manager, Side::Ask, 600).is_empty());
+        assert_eq!(queue(&orderbook_manager, Side::Ask, 610), vec![(3, 50)]);
+        let book = orderbook_manager.book(BookId(1)).unwrap();
+        assert_eq!(book.level_at(Side::Ask, 600), None);
+        assert_eq!(book.best_ask().unwrap().qty, Qty(50));
+    }
+
+    #[test]
+    fn test_l3_book_matches_aggregate_book() {
+        let mut l2: OrderBookManager<HashOidMap> = OrderBookManager::default();
+        let mut l3: OrderBookManager<HashOidMap, L3OrderBook> = OrderBookManager::default();
+        let mut live: Vec<(u64, u32)> = Vec::new();
+        let mut next_id = 0;
+        let mut seed = 11u64;
+
+        for _ in 0..20_000 {
+            seed = seed
+                .wrapping_mul(6_364_136_223_846_793_005)
+                .wrapping_add(1_442_695_040_888_963_407);
+            let r = seed >> 33;
+            if live.is_empty() || r.is_multiple_of(3) {
+                let is_bid = r.is_multiple_of(2);
+                let price = if is_bid { 900 } else { 1_000 } + (r % 50) as u32;
+                let qty = 1 + (r % 500) as u32;
+                l2.add_order(OrderId(next_id), BookId(1), Qty(qty), price, is_bid);
+                l3.add_order(OrderId(next_id), BookId(1), Qty(qty), price, is_bid);
+                live.push((next_id, qty));
+                next_id += 1;
+                continue;
+            }
+            let pos = (r as usize / 3) % live.len();
+            let (id, qty) = live[pos];
+            match r % 4 {
+                0 => {
+                    l2.remove_order(OrderId(id));
+                    l3.remove_order(OrderId(id));
+                    live.swap_remove(pos);
+                }
+                1 if qty > 1 => {
+                    l2.cancel_order(OrderId(id), Qty(1));
+                    l3.cancel_order(OrderId(id), Qty(1));
+                    live[pos].1 -= 1;
+                }
+                2 => {
+                    l2.execute_order(OrderId(id), Qty(qty));
+                    l3.execute_order(OrderId(id), Qty(qty));
+                    live.swap_remove(pos);
+                }
+                _ => {
+                    l2.replace_order(OrderId(id), OrderId(next_id), Qty(qty), 950);
+                    l3.replace_order(OrderId(id), OrderId(next_id), Qty(qty), 950);
+                    live[pos].0 = next_id;
+                    next_id += 1;
+                }
+            }
+        }
+
+        let l2_book = l2.book(BookId(1)).unwrap();
+        let l3_book = l3.book(BookId(1)).unwrap();
+        for side in [Side::Bid, Side::Ask] {
+            let depth = l2_book.depth(side, usize::MAX);
+            assert_eq!(depth, l3_book.depth(side, usize::MAX));
+            for level in depth {
+                let orders: Vec<_> = l3_book.orders_at(side, level.price).collect();
+                assert_eq!(orders.len() as u32, level.order_count);
+                let total: u32 = orders.iter().map(|node| node.qty().value()).sum();
+                assert_eq!(Qty(total), level.qty);
+                // The queue is in time priority.
+                assert!(orders.windows(2).all(|w| w[0].seq() < w[1].seq()));
+            }
+        }
+    }
+}
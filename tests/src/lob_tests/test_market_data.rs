@@ -0,0 +1,216 @@
+#[cfg(test)]
+mod tests {
+    use optimized_lob::error::FeedError;
+    use optimized_lob::market_data::{L2Action, L2Book, L2Level, L2Message, L2Publisher};
+    use optimized_lob::order::{OidMap, OrderId};
+    use optimized_lob::orderbook::OrderBook;
+    use optimized_lob::orderbook_manager::OrderBookManager;
+    use optimized_lob::quantity::Qty;
+    use optimized_lob::side::Side;
+    use optimized_lob::utils::BookId;
+
+    type Manager = OrderBookManager<OidMap, OrderBook, L2Publisher>;
+
+    fn manager(publisher: L2Publisher) -> Manager {
+        OrderBookManager::with_events(OidMap::new(), publisher)
+    }
+
+    fn level(price: u32, qty: u32, order_count: u32) -> L2Level {
+        L2Level {
+            price,
+            qty: Qty(qty),
+            order_count,
+        }
+    }
+
+    /// Returns the displayed levels of one side of a book, best price first.
+    fn depth(orderbook_manager: &Manager, book_id: BookId, side: Side) -> Vec<L2Level> {
+        orderbook_manager.book(book_id).map_or(Vec::new(), |book| {
+            book.depth(side, usize::MAX)
+                .iter()
+                .map(|depth| level(depth.price, depth.qty.value(), depth.order_count))
+                .collect()
+        })
+    }
+
+    #[test]
+    fn test_new_change_and_delete_updates() {
+        let mut orderbook_manager = manager(L2Publisher::new());
+        orderbook_manager.add_order(OrderId(1), BookId(1), Qty(100), 500, true);
+        orderbook_manager.add_order(OrderId(2), BookId(1), Qty(50), 500, true);
+        orderbook_manager.add_order(OrderId(3), BookId(2), Qty(70), 510, false);
+        orderbook_manager.remove_order(OrderId(1));
+        orderbook_manager.execute_order(OrderId(2), Qty(50));
+
+        let updates: Vec<_> = orderbook_manager
+            .events
+            .drain()
+            .map(|message| match message {
+                L2Message::Update(update) => (
+                    update.book_id,
+                    update.seq,
+                    update.action,
+                    update.side,
+                    update.level,
+                ),
+                L2Message::Snapshot(_) => panic!("unexpected snapshot"),
+            })
+            .collect();
+        assert_eq!(
+            updates,
+            vec![
+                (BookId(1), 1, L2Action::New, Side::Bid, level(500, 100, 1)),
+                (
+                    BookId(1),
+                    2,
+                    L2Action::Change,
+                    Side::Bid,
+                    level(500, 150, 2)
+                ),
+                (BookId(2), 1, L2Action::New, Side::Ask, level(510, 70, 1)),
+                (BookId(1), 3, L2Action::Change, Side::Bid, level(500, 50, 1)),
+                (BookId(1), 4, L2Action::Delete, Side::Bid, level(500, 0, 0)),
+            ]
+        );
+        assert!(orderbook_manager.events.messages().is_empty());
+    }
+
+    #[test]
+    fn test_periodic_snapshots() {
+        let mut orderbook_manager = manager(L2Publisher::with_snapshot_interval(2));
+        orderbook_manager.add_order(OrderId(1), BookId(1), Qty(100), 500, true);
+        orderbook_manager.add_order(OrderId(2), BookId(1), Qty(40), 520, false);
+        orderbook_manager.add_order(OrderId(3), BookId(1), Qty(60), 490, true);
+
+        let messages: Vec<_> = orderbook_manager.events.drain().collect();
+        assert_eq!(messages.len(), 4);
+        let L2Message::Snapshot(snapshot) = &messages[2] else {
+            panic!("expected a snapshot after the second update");
+        };
+        assert_eq!(snapshot.seq, 2);
+        assert_eq!(snapshot.bids, vec![level(500, 100, 1)]);
+        assert_eq!(snapshot.asks, vec![level(520, 40, 1)]);
+        assert!(matches!(&messages[3], L2Message::Update(update) if update.seq == 3));
+    }
+
+    #[test]
+    fn test_sequence_gap_is_detected() {
+        let mut orderbook_manager = manager(L2Publisher::new());
+        for order_id in 1..=3 {
+            orderbook_manager.add_order(
+                OrderId(order_id),
+                BookId(1),
+                Qty(100),
+                500 + order_id as u32,
+                true,
+            );
+        }
+        let messages: Vec<_> = orderbook_manager.events.drain().collect();
+
+        let mut l2_book = L2Book::new(BookId(1));
+        assert_eq!(l2_book.apply(&messages[0]), Ok(()));
+        assert_eq!(
+            l2_book.apply(&messages[2]),
+            Err(FeedError::SequenceGap {
+                expected: 2,
+                received: 3
+            })
+        );
+        assert_eq!(l2_book.seq(), 1);
+
+        // A snapshot recovers the book, and the updates it includes are skipped.
+        let snapshot = L2Message::Snapshot(orderbook_manager.events.snapshot(BookId(1)));
+        assert_eq!(l2_book.apply(&snapshot), Ok(()));
+        assert_eq!(l2_book.apply(&messages[2]), Ok(()));
+        assert_eq!(l2_book.seq(), 3);
+        assert_eq!(
+            l2_book.bids().copied().collect::<Vec<_>>(),
+            vec![level(503, 100, 1), level(502, 100, 1), level(501, 100, 1)]
+        );
+    }
+
+    /// A small xorshift generator, so the order flow is the same on every run.
+    struct Rng(u64);
+
+    impl Rng {
+        fn next(&mut self, bound: u64) -> u64 {
+            self.0 ^= self.0 << 13;
+            self.0 ^= self.0 >> 7;
+            self.0 ^= self.0 << 17;
+            self.0 % bound
+        }
+    }
+
+    #[test]
+    fn test_rebuild_from_snapshot_and_deltas() {
+        let books = [BookId(1), BookId(2), BookId(3)];
+        let mut orderbook_manager = manager(L2Publisher::with_snapshot_interval(64));
+        let mut rng = Rng(0x2545_f491_4f6c_dd1d);
+        let mut resting: Vec<u64> = Vec::new();
+        let mut l2_books: Vec<Option<L2Book>> = vec![None; books.len()];
+
+        for step in 0..5_000u64 {
+            match rng.next(10) {
+                0..=4 => {
+                    let book_id = books[rng.next(books.len() as u64) as usize];
+                    let is_bid = rng.next(2) == 0;
+                    let price = if is_bid { 900 } else { 1_000 } + rng.next(50) as u32;
+                    let qty = Qty(1 + rng.next(500) as u32);
+                    orderbook_manager.add_order(OrderId(step), book_id, qty, price, is_bid);
+                    resting.push(step);
+                }
+                _ if resting.is_empty() => {}
+                5 | 6 => {
+                    let order_id = resting.swap_remove(rng.next(resting.len() as u64) as usize);
+                    orderbook_manager.remove_order(OrderId(order_id));
+                }
+                7 => {
+                    let idx = rng.next(resting.len() as u64) as usize;
+                    let order_id = OrderId(resting[idx]);
+                    let qty = orderbook_manager.oid_map.get(order_id).unwrap().qty();
+                    if qty.value() > 1 {
+                        let cancelled = 1 + rng.next(qty.value() as u64 - 1) as u32;
+                        orderbook_manager.cancel_order(order_id, Qty(cancelled));
+                    }
+                }
+                _ => {
+                    let idx = rng.next(resting.len() as u64) as usize;
+                    let order_id = OrderId(resting[idx]);
+                    let qty = orderbook_manager.oid_map.get(order_id).unwrap().qty();
+                    let executed = 1 + rng.next(qty.value() as u64) as u32;
+                    orderbook_manager.execute_order(order_id, Qty(executed));
+                    if executed == qty.value() {
+                        resting.swap_remove(idx);
+                    }
+                }
+            }
+
+            // Consumers join the feed at different times, from a snapshot.
+            if step % 1_000 == 0 && (step / 1_000) < books.len() as u64 {
+                let idx = (step / 1_000) as usize;
+                let snapshot = orderbook_manager.events.snapshot(books[idx]);
+                let mut l2_book = L2Book::new(books[idx]);
+                l2_book.apply(&L2Message::Snapshot(snapshot)).unwrap();
+                l2_books[idx] = Some(l2_book);
+            }
+            for message in orderbook_manager.events.drain() {
+                for l2_book in l2_books.iter_mut().flatten() {
+                    l2_book.apply(&message).unwrap();
+                }
+            }
+        }
+
+        for (book_id, l2_book) in books.iter().zip(&l2_books) {
+            let l2_book = l2_book.as_ref().unwrap();
+            assert!(l2_book.seq() > 0);
+            assert_eq!(
+                l2_book.bids().copied().collect::<Vec<_>>(),
+                depth(&orderbook_manager, *book_id, Side::Bid)
+            );
+            assert_eq!(
+                l2_book.asks().copied().collect::<Vec<_>>(),
+                depth(&orderbook_manager, *book_id, Side::Ask)
+            );
+        }
+    }
+}
@@ -0,0 +1,321 @@
+#[cfg(test)]
+mod tests {
+    use optimized_lob::l3_orderbook::L3OrderBook;
+    use optimized_lob::level::DepthLevel;
+    use optimized_lob::matching::{ExecutionStatus, Fill, OrderRequest, RejectReason};
+    use optimized_lob::order::{OidMap, OrderId};
+    use optimized_lob::orderbook_manager::OrderBookManager;
+    use optimized_lob::quantity::Qty;
+    use optimized_lob::side::Side;
+    use optimized_lob::utils::BookId;
+
+    fn fill(taker: u64, maker: u64, aggressor: Side, price: u32, qty: u32) -> Fill {
+        Fill {
+            book_id: BookId(1),
+            taker_order_id: OrderId(taker),
+            maker_order_id: OrderId(maker),
+            aggressor,
+            price,
+            qty: Qty(qty),
+        }
+    }
+
+    /// Asks of 100 @ 1000 (ids 1 then 2), 200 @ 1010 (id 3) and bids of 100 @ 990 (id 4).
+    fn sample_book() -> OrderBookManager<OidMap, L3OrderBook> {
+        let mut orderbook_manager = OrderBookManager::new_l3();
+        orderbook_manager.add_order(OrderId(1), BookId(1), Qty(60), 1000, false);
+        orderbook_manager.add_order(OrderId(2), BookId(1), Qty(40), 1000, false);
+        orderbook_manager.add_order(OrderId(3), BookId(1), Qty(200), 1010, false);
+        orderbook_manager.add_order(OrderId(4), BookId(1), Qty(100), 990, true);
+        orderbook_manager
+    }
+
+    #[test]
+    fn test_non_crossing_order_rests() {
+        let mut orderbook_manager = sample_book();
+
+        let execution =
+            orderbook_manager.submit_limit_order(OrderId(10), BookId(1), Qty(50), 995, true);
+        assert!(execution.fills.is_empty());
+        assert_eq!(execution.filled, Qty(0));
+        assert_eq!(execution.resting, Qty(50));
+
+        let book = orderbook_manager.book(BookId(1)).unwrap();
+        assert_eq!(book.best_bid().unwrap().price, 995);
+        assert_eq!(book.best_ask().unwrap().price, 1000);
+    }
+
+    #[test]
+    fn test_sweep_with_price_time_priority() {
+        let mut orderbook_manager = sample_book();
+
+        let execution =
+            orderbook_manager.submit_limit_order(OrderId(10), BookId(1), Qty(150), 1010, true);
+        assert_eq!(
+            execution.fills,
+            vec![
+                fill(10, 1, Side::Bid, 1000, 60),
+                fill(10, 2, Side::Bid, 1000, 40),
+                fill(10, 3, Side::Bid, 1010, 50),
+            ]
+        );
+        assert_eq!(execution.filled, Qty(150));
+        assert_eq!(execution.resting, Qty(0));
+
+        // The filled makers are gone, the partially filled one keeps its place.
+        assert!(orderbook_manager.oid_map.get(OrderId(1)).is_none());
+        assert!(orderbook_manager.oid_map.get(OrderId(2)).is_none());
+        assert!(orderbook_manager.oid_map.get(OrderId(10)).is_none());
+        let book = orderbook_manager.book(BookId(1)).unwrap();
+        assert_eq!(
+            book.best_ask(),
+            Some(DepthLevel {
+                price: 1010,
+                qty: Qty(150),
+                total_qty: Qty(150),
+                order_count: 1
+            })
+        );
+        assert_eq!(book.best_bid().unwrap().price, 990);
+    }
+
+    #[test]
+    fn test_remainder_rests_at_limit() {
+        let mut orderbook_manager = sample_book();
+
+        let execution =
+            orderbook_manager.submit_limit_order(OrderId(10), BookId(1), Qty(250), 1005, true);
+        assert_eq!(execution.fills.len(), 2);
+        assert_eq!(execution.filled, Qty(100));
+        assert_eq!(execution.resting, Qty(150));
+
+        let book = orderbook_manager.book(BookId(1)).unwrap();
+        assert_eq!(book.best_bid().unwrap().price, 1005);
+        assert_eq!(book.best_bid().unwrap().qty, Qty(150));
+        assert_eq!(book.best_ask().unwrap().price, 1010);
+        assert_eq!(
+            orderbook_manager.oid_map.get(OrderId(10)).unwrap().qty(),
+            Qty(150)
+        );
+    }
+
+    #[test]
+    fn test_sell_order_matches_bids() {
+        let mut orderbook_manager = sample_book();
+        orderbook_manager.add_order(OrderId(5), BookId(1), Qty(30), 995, true);
+
+        let execution =
+            orderbook_manager.submit_limit_order(OrderId(10), BookId(1), Qty(50), 990, false);
+        assert_eq!(
+            execution.fills,
+            vec![
+                fill(10, 5, Side::Ask, 995, 30),
+                fill(10, 4, Side::Ask, 990, 20)
+            ]
+        );
+        assert_eq!(execution.resting, Qty(0));
+        assert_eq!(
+            orderbook_manager
+                .queue_position(OrderId(4))
+                .unwrap()
+                .orders_ahead,
+            0
+        );
+        assert_eq!(
+            orderbook_manager
+                .book(BookId(1))
+                .unwrap()
+                .best_bid()
+                .unwrap()
+                .qty,
+            Qty(80)
+        );
+    }
+
+    #[test]
+    fn test_matching_in_empty_book() {
+        let mut orderbook_manager = OrderBookManager::new_l3();
+
+        let execution =
+            orderbook_manager.submit_limit_order(OrderId(1), BookId(7), Qty(10), 1000, false);
+        assert!(execution.fills.is_empty());
+        assert_eq!(execution.resting, Qty(10));
+
+        let execution =
+            orderbook_manager.submit_limit_order(OrderId(2), BookId(7), Qty(10), 1000, true);
+        assert_eq!(
+            execution.fills,
+            vec![Fill {
+                book_id: BookId(7),
+                ..fill(2, 1, Side::Bid, 1000, 10)
+            }]
+        );
+        let book = orderbook_manager.book(BookId(7)).unwrap();
+        assert!(book.best_bid().is_none());
+        assert!(book.best_ask().is_none());
+    }
+
+    #[test]
+    fn test_limit_order_status() {
+        let mut orderbook_manager = sample_book();
+
+        let execution =
+            orderbook_manager.submit_limit_order(OrderId(10), BookId(1), Qty(50), 995, true);
+        assert_eq!(execution.status, ExecutionStatus::Resting);
+        let execution =
+            orderbook_manager.submit_limit_order(OrderId(11), BookId(1), Qty(150), 1000, true);
+        assert_eq!(execution.status, ExecutionStatus::PartiallyFilled);
+        let execution =
+            orderbook_manager.submit_limit_order(OrderId(12), BookId(1), Qty(50), 990, false);
+        assert_eq!(execution.status, ExecutionStatus::Filled);
+
+        // Invalid orders are rejected without touching the book.
+        let execution =
+            orderbook_manager.submit_limit_order(OrderId(4), BookId(1), Qty(10), 1200, true);
+        assert_eq!(
+            execution.status,
+            ExecutionStatus::Rejected(RejectReason::DuplicateOrderId)
+        );
+        let execution =
+            orderbook_manager.submit_limit_order(OrderId(13), BookId(1), Qty(0), 1200, true);
+        assert_eq!(
+            execution.status,
+            ExecutionStatus::Rejected(RejectReason::ZeroQuantity)
+        );
+        assert_eq!(
+            orderbook_manager
+                .book(BookId(1))
+                .unwrap()
+                .best_ask()
+                .unwrap()
+                .price,
+            1010
+        );
+    }
+
+    #[test]
+    fn test_market_order() {
+        let mut orderbook_manager = sample_book();
+
+        let execution = orderbook_manager.submit_order(&OrderRequest::market(
+            OrderId(10),
+            BookId(1),
+            Side::Bid,
+            Qty(250),
+        ));
+        assert_eq!(execution.status, ExecutionStatus::Filled);
+        assert_eq!(execution.fills.last().unwrap().price, 1010);
+
+        // The book runs out before the order is filled.
+        let execution = orderbook_manager.submit_order(&OrderRequest::market(
+            OrderId(11),
+            BookId(1),
+            Side::Bid,
+            Qty(100),
+        ));
+        assert_eq!(execution.status, ExecutionStatus::PartiallyFilled);
+        assert_eq!(execution.filled, Qty(50));
+        assert_eq!(execution.cancelled, Qty(50));
+        assert!(orderbook_manager.oid_map.get(OrderId(11)).is_none());
+
+        let execution = orderbook_manager.submit_order(&OrderRequest::market(
+            OrderId(12),
+            BookId(1),
+            Side::Bid,
+            Qty(100),
+        ));
+        assert_eq!(execution.status, ExecutionStatus::Cancelled);
+        assert!(orderbook_manager
+            .book(BookId(1))
+            .unwrap()
+            .best_bid()
+            .is_some());
+    }
+
+    #[test]
+    fn test_immediate_or_cancel_order() {
+        let mut orderbook_manager = sample_book();
+
+        let execution = orderbook_manager.submit_order(&OrderRequest::immediate_or_cancel(
+            OrderId(10),
+            BookId(1),
+            Side::Bid,
+            Qty(150),
+            1000,
+        ));
+        assert_eq!(execution.status, ExecutionStatus::PartiallyFilled);
+        assert_eq!(execution.filled, Qty(100));
+        assert_eq!(execution.cancelled, Qty(50));
+        assert_eq!(execution.resting, Qty(0));
+
+        let book = orderbook_manager.book(BookId(1)).unwrap();
+        assert_eq!(book.best_bid().unwrap().price, 990);
+        assert!(orderbook_manager.oid_map.get(OrderId(10)).is_none());
+
+        let execution = orderbook_manager.submit_order(&OrderRequest::immediate_or_cancel(
+            OrderId(11),
+            BookId(1),
+            Side::Ask,
+            Qty(10),
+            1000,
+        ));
+        assert_eq!(execution.status, ExecutionStatus::Cancelled);
+        assert_eq!(execution.cancelled, Qty(10));
+    }
+
+    #[test]
+    fn test_fill_or_kill_order() {
+        let mut orderbook_manager = sample_book();
+
+        // 100 shares are available up to 1000, 300 up to 1010.
+        let execution = orderbook_manager.submit_order(&OrderRequest::fill_or_kill(
+            OrderId(10),
+            BookId(1),
+            Side::Bid,
+            Qty(101),
+            1000,
+        ));
+        assert_eq!(
+            execution.status,
+            ExecutionStatus::Rejected(RejectReason::InsufficientLiquidity)
+        );
+        assert!(execution.fills.is_empty());
+        let book = orderbook_manager.book(BookId(1)).unwrap();
+        assert_eq!(book.best_ask().unwrap().qty, Qty(100));
+        assert_eq!(
+            orderbook_manager
+                .queue_position(OrderId(2))
+                .unwrap()
+                .orders_ahead,
+            1
+        );
+
+        let execution = orderbook_manager.submit_order(&OrderRequest::fill_or_kill(
+            OrderId(11),
+            BookId(1),
+            Side::Bid,
+            Qty(300),
+            1010,
+        ));
+        assert_eq!(execution.status, ExecutionStatus::Filled);
+        assert_eq!(execution.fills.len(), 3);
+        assert!(orderbook_manager
+            .book(BookId(1))
+            .unwrap()
+            .best_ask()
+            .is_none());
+
+        // No book at all.
+        let execution = orderbook_manager.submit_order(&OrderRequest::fill_or_kill(
+            OrderId(12),
+            BookId(2),
+            Side::Ask,
+            Qty(1),
+            1,
+        ));
+        assert_eq!(
+            execution.status,
+            ExecutionStatus::Rejected(RejectReason::InsufficientLiquidity)
+        );
+    }
+}
@@ -0,0 +1,163 @@
+#[cfg(test)]
+mod tests {
+    use crate::lob_tests::utils::position;
+    use optimized_lob::error::OrderError;
+    use optimized_lob::l3_orderbook::L3OrderBook;
+    use optimized_lob::order::{OidMap, OrderId};
+    use optimized_lob::orderbook_manager::OrderBookManager;
+    use optimized_lob::quantity::Qty;
+    use optimized_lob::side::Side;
+    use optimized_lob::utils::BookId;
+
+    /// Three bids of 100 @ 500, in the order 1, 2, 3.
+    fn sample_book() -> OrderBookManager<OidMap, L3OrderBook> {
+        let mut orderbook_manager = OrderBookManager::new_l3();
+        for order_id in 1..=3 {
+            orderbook_manager.add_order(OrderId(order_id), BookId(1), Qty(100), 500, true);
+        }
+        orderbook_manager
+    }
+
+    #[test]
+    fn test_qty_decrease_keeps_priority() {
+        let mut orderbook_manager = sample_book();
+        assert_eq!(
+            orderbook_manager.modify_order(OrderId(1), Qty(40), 500),
+            Ok(())
+        );
+
+        assert_eq!(orderbook_manager.queue_position(OrderId(1)), position(0, 0));
+        assert_eq!(
+            orderbook_manager.queue_position(OrderId(2)),
+            position(1, 40)
+        );
+        let level = orderbook_manager
+            .book(BookId(1))
+            .unwrap()
+            .best_bid()
+            .unwrap();
+        assert_eq!(level.qty, Qty(240));
+        assert_eq!(level.order_count, 3);
+    }
+
+    #[test]
+    fn test_qty_increase_loses_priority() {
+        let mut orderbook_manager = sample_book();
+        assert_eq!(
+            orderbook_manager.modify_order(OrderId(1), Qty(150), 500),
+            Ok(())
+        );
+
+        assert_eq!(orderbook_manager.queue_position(OrderId(2)), position(0, 0));
+        assert_eq!(
+            orderbook_manager.queue_position(OrderId(1)),
+            position(2, 200)
+        );
+        let order = orderbook_manager.oid_map.get(OrderId(1)).unwrap();
+        assert_eq!(order.qty(), Qty(150));
+        assert_eq!(order.book_id(), BookId(1));
+        let level = orderbook_manager
+            .book(BookId(1))
+            .unwrap()
+            .best_bid()
+            .unwrap();
+        assert_eq!(level.qty, Qty(350));
+        assert_eq!(level.order_count, 3);
+    }
+
+    #[test]
+    fn test_price_change_loses_priority() {
+        let mut orderbook_manager = sample_book();
+        orderbook_manager.add_order(OrderId(4), BookId(1), Qty(100), 490, true);
+
+        // Moving to another level, with a smaller quantity.
+        assert_eq!(
+            orderbook_manager.modify_order(OrderId(1), Qty(50), 490),
+            Ok(())
+        );
+        assert_eq!(
+            orderbook_manager.queue_position(OrderId(1)),
+            position(1, 100)
+        );
+        let book = orderbook_manager.book(BookId(1)).unwrap();
+        assert_eq!(book.best_bid().unwrap().qty, Qty(200));
+
+        // Moving back keeps the side and goes behind the orders already there.
+        assert_eq!(
+            orderbook_manager.modify_order(OrderId(1), Qty(50), 500),
+            Ok(())
+        );
+        assert_eq!(
+            orderbook_manager.queue_position(OrderId(1)),
+            position(2, 200)
+        );
+        let level = orderbook_manager
+            .book(BookId(1))
+            .unwrap()
+            .best_bid()
+            .unwrap();
+        assert_eq!(level.qty, Qty(250));
+    }
+
+    #[test]
+    fn test_same_qty_and_price_is_a_no_op() {
+        let mut orderbook_manager = sample_book();
+        assert_eq!(
+            orderbook_manager.modify_order(OrderId(1), Qty(100), 500),
+            Ok(())
+        );
+        assert_eq!(orderbook_manager.queue_position(OrderId(1)), position(0, 0));
+    }
+
+    #[test]
+    fn test_errors_leave_the_book_unchanged() {
+        let mut orderbook_manager = sample_book();
+        assert_eq!(
+            orderbook_manager.modify_order(OrderId(9), Qty(100), 500),
+            Err(OrderError::UnknownOrder(OrderId(9)))
+        );
+        assert_eq!(
+            orderbook_manager.modify_order(OrderId(1), Qty(0), 500),
+            Err(OrderError::ZeroQuantity)
+        );
+        assert!(orderbook_manager.oid_map.get(OrderId(9)).is_none());
+        assert!(orderbook_manager.book(BookId(0)).is_none());
+        let level = orderbook_manager
+            .book(BookId(1))
+            .unwrap()
+            .best_bid()
+            .unwrap();
+        assert_eq!(level.qty, Qty(300));
+        assert_eq!(level.order_count, 3);
+    }
+
+    #[test]
+    fn test_iceberg_keeps_hidden_reserve() {
+        let mut orderbook_manager = OrderBookManager::new_l3();
+        orderbook_manager.add_iceberg_order(OrderId(1), BookId(1), Qty(500), Qty(100), 1000, false);
+        orderbook_manager.add_order(OrderId(2), BookId(1), Qty(50), 1000, false);
+
+        assert_eq!(
+            orderbook_manager.modify_order(OrderId(1), Qty(100), 1010),
+            Ok(())
+        );
+        let order = orderbook_manager.oid_map.get(OrderId(1)).unwrap();
+        assert_eq!(order.qty(), Qty(100));
+        let book = orderbook_manager.book(BookId(1)).unwrap();
+        assert_eq!(book.node(OrderId(1)).unwrap().attributes().hidden, Qty(400));
+        let level = book.best_ask().unwrap();
+        assert_eq!(level.qty, Qty(50));
+        assert_eq!(level.total_qty, Qty(50));
+        let level = book.level_at(Side::Ask, 1010).unwrap();
+        assert_eq!(level.qty, Qty(100));
+        assert_eq!(level.total_qty, Qty(500));
+    }
+
+    #[test]
+    fn test_replace_unknown_order_adds_nothing() {
+        let mut orderbook_manager = OrderBookManager::new();
+        orderbook_manager.replace_order(OrderId(1), OrderId(2), Qty(100), 500);
+        assert!(orderbook_manager.oid_map.get(OrderId(2)).is_none());
+        assert!(orderbook_manager.book(BookId(0)).is_none());
+    }
+}
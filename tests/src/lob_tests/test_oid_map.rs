@@ -0,0 +1,144 @@
+#[cfg(test)]
+mod tests {
+    use crate::lob_tests::utils::get_level_capacity;
+    use optimized_lob::level::LevelId;
+    use optimized_lob::oid_map::{HashOidMap, OrderIdMap, PagedOidMap};
+    use optimized_lob::order::{OidMap, Order, OrderId};
+    use optimized_lob::orderbook_manager::OrderBookManager;
+    use optimized_lob::quantity::Qty;
+    use optimized_lob::utils::BookId;
+    use std::collections::HashMap;
+
+    fn order(qty: u32) -> Order {
+        Order::new(Qty(qty), LevelId(qty), BookId(1))
+    }
+
+    /// Runs the same sequence of operations against a map and a reference `HashMap`.
+    fn check_against_reference<M: OrderIdMap>(mut map: M) {
+        let mut reference: HashMap<u64, Order> = HashMap::new();
+        let mut seed = 7u64;
+        for step in 0..50_000u32 {
+            seed = seed
+                .wrapping_mul(6_364_136_223_846_793_005)
+                .wrapping_add(1_442_695_040_888_963_407);
+            // Mix sequential, clustered and fully random ids.
+            let oid = match seed % 3 {
+                0 => (seed >> 40) % 4_096,
+                1 => (1 << 40) + (seed >> 32) % 10_000,
+                _ => seed >> 1,
+            };
+            match (seed >> 8) % 4 {
+                0 | 1 => {
+                    map.reserve(OrderId(oid));
+                    map.insert(OrderId(oid), &order(step + 10));
+                    reference.insert(oid, order(step + 10));
+                }
+                2 => {
+                    map.remove(OrderId(oid));
+                    reference.remove(&oid);
+                }
+                _ => {
+                    if let Some(expected) = reference.get_mut(&oid) {
+                        map.update_qty(OrderId(oid), Qty(1));
+                        let mut qty = expected.qty();
+                        qty -= Qty(1);
+                        expected.set_qty(qty);
+                    }
+                }
+            }
+            assert_eq!(map.get(OrderId(oid)), reference.get(&oid));
+        }
+        for (oid, expected) in &reference {
+            assert_eq!(map.get(OrderId(*oid)), Some(expected));
+        }
+    }
+
+    #[test]
+    fn test_dense_oid_map_matches_reference() {
+        check_against_reference(OidMap::new());
+    }
+
+    #[test]
+    fn test_hash_oid_map_matches_reference() {
+        // A small initial capacity exercises the growth path.
+        check_against_reference(HashOidMap::with_capacity(4));
+    }
+
+    #[test]
+    fn test_paged_oid_map_matches_reference() {
+        check_against_reference(PagedOidMap::new());
+    }
+
+    #[test]
+    fn test_hash_oid_map_len() {
+        let mut map = HashOidMap::with_capacity(4);
+        assert!(map.is_empty());
+        for oid in 0..100 {
+            map.insert(OrderId(oid * 1_000_003), &order(1));
+        }
+        map.insert(OrderId(0), &order(2));
+        assert_eq!(map.len(), 100);
+        for oid in 0..50 {
+            map.remove(OrderId(oid * 1_000_003));
+        }
+        assert_eq!(map.len(), 50);
+        assert_eq!(map.get(OrderId(0)), None);
+        assert_eq!(map.get(OrderId(99 * 1_000_003)), Some(&order(1)));
+    }
+
+    #[test]
+    fn test_paged_oid_map_frees_empty_pages() {
+        let mut map = PagedOidMap::new();
+        map.insert(OrderId(1), &order(1));
+        map.insert(OrderId(2), &order(1));
+        map.insert(OrderId(u64::MAX), &order(1));
+        assert_eq!(map.page_count(), 2);
+
+        map.remove(OrderId(1));
+        assert_eq!(map.page_count(), 2);
+        map.remove(OrderId(2));
+        map.remove(OrderId(u64::MAX));
+        assert_eq!(map.page_count(), 0);
+    }
+
+    #[test]
+    fn test_manager_with_hash_oid_map() {
+        let mut orderbook_manager = OrderBookManager::with_oid_map(HashOidMap::new());
+        let sparse_id = OrderId(0xdead_beef_0000_0001);
+
+        orderbook_manager.add_order(OrderId(3), BookId(1), Qty(800), 500, true);
+        orderbook_manager.add_order(sparse_id, BookId(1), Qty(50), 500, true);
+        assert_eq!(Qty(850), get_level_capacity(&orderbook_manager, 1, 0));
+
+        orderbook_manager.cancel_order(sparse_id, Qty(20));
+        orderbook_manager.execute_order(OrderId(3), Qty(800));
+        assert_eq!(Qty(30), get_level_capacity(&orderbook_manager, 1, 0));
+        assert!(orderbook_manager.oid_map.get(OrderId(3)).is_none());
+
+        orderbook_manager.replace_order(sparse_id, OrderId(u64::MAX), Qty(40), 600);
+        assert_eq!(
+            orderbook_manager
+                .oid_map
+                .get(OrderId(u64::MAX))
+                .unwrap()
+                .qty(),
+            Qty(40)
+        );
+
+        orderbook_manager.reset();
+        assert!(orderbook_manager.oid_map.is_empty());
+    }
+
+    #[test]
+    fn test_manager_with_paged_oid_map() {
+        let mut orderbook_manager: OrderBookManager<PagedOidMap> = OrderBookManager::default();
+
+        orderbook_manager.add_order(OrderId(1 << 50), BookId(2), Qty(100), 700, false);
+        orderbook_manager.add_order(OrderId((1 << 50) + 1), BookId(2), Qty(10), 700, false);
+        assert_eq!(Qty(110), get_level_capacity(&orderbook_manager, 2, 0));
+
+        orderbook_manager.remove_order(OrderId(1 << 50));
+        assert_eq!(Qty(10), get_level_capacity(&orderbook_manager, 2, 0));
+        assert_eq!(orderbook_manager.oid_map.page_count(), 1);
+    }
+}
@@ -51,6 +51,13 @@ mod tests {
         assert_eq!(order.level_id(), level_id2);
     }
 
+    #[test]
+    fn test_order_stays_small() {
+        // Every order ID up to the dense limit has an entry in the OidMap, so the state only
+        // L3 books need is kept in their queue nodes instead.
+        assert!(std::mem::size_of::<Order>() <= 12);
+    }
+
     #[test]
     fn test_oid_map_insert_and_get() {
         let mut oid_map = OidMap::new();
@@ -97,4 +104,25 @@ mod tests {
 
         assert_eq!(retrieved_order, None);
     }
+
+    #[test]
+    fn test_oid_map_large_order_ids() {
+        let mut oid_map = OidMap::new();
+        let small = OrderId(7);
+        let large = OrderId(u64::MAX - 1);
+        let small_order = Order::new(Qty(100), LevelId(1), BookId(42));
+        let large_order = Order::new(Qty(300), LevelId(2), BookId(42));
+
+        oid_map.insert(small, &small_order);
+        oid_map.insert(large, &large_order);
+        oid_map.update_qty(large, Qty(100));
+
+        assert_eq!(oid_map.get(small), Some(&small_order));
+        assert_eq!(oid_map.get(large).unwrap().qty(), Qty(200));
+        assert_eq!(oid_map.get(OrderId(u64::MAX)), None);
+
+        oid_map.remove(large);
+        assert_eq!(oid_map.get(large), None);
+        assert_eq!(oid_map.get(small), Some(&small_order));
+    }
 }
@@ -0,0 +1,168 @@
+#[cfg(test)]
+mod tests {
+    use optimized_lob::matching::{ExecutionStatus, OrderRequest, PostOnly, RejectReason};
+    use optimized_lob::order::OrderId;
+    use optimized_lob::orderbook_manager::OrderBookManager;
+    use optimized_lob::quantity::Qty;
+    use optimized_lob::side::Side;
+    use optimized_lob::utils::BookId;
+
+    #[test]
+    fn test_hidden_order_is_not_displayed() {
+        let mut orderbook_manager = OrderBookManager::new_l3();
+        orderbook_manager.add_order(OrderId(1), BookId(1), Qty(100), 1000, true);
+        orderbook_manager.add_hidden_order(OrderId(2), BookId(1), Qty(50), 1000, true);
+        orderbook_manager.add_hidden_order(OrderId(3), BookId(1), Qty(30), 1010, true);
+
+        let book = orderbook_manager.book(BookId(1)).unwrap();
+        let best_bid = book.best_bid().unwrap();
+        assert_eq!(best_bid.price, 1000);
+        assert_eq!(best_bid.qty, Qty(100));
+        assert_eq!(best_bid.total_qty, Qty(150));
+        assert_eq!(best_bid.order_count, 2);
+        // A level of hidden orders only is not displayed at all.
+        assert!(book.level_at(Side::Bid, 1010).is_none());
+        assert_eq!(book.depth(Side::Bid, 5).len(), 1);
+        assert_eq!(book.aggregate.bids.len(), 2);
+
+        orderbook_manager.execute_order(OrderId(2), Qty(20));
+        let best_bid = orderbook_manager
+            .book(BookId(1))
+            .unwrap()
+            .best_bid()
+            .unwrap();
+        assert_eq!((best_bid.qty, best_bid.total_qty), (Qty(100), Qty(130)));
+
+        orderbook_manager.remove_order(OrderId(2));
+        orderbook_manager.remove_order(OrderId(3));
+        let book = orderbook_manager.book(BookId(1)).unwrap();
+        assert_eq!(book.best_bid().unwrap().total_qty, Qty(100));
+        assert_eq!(book.aggregate.bids.len(), 1);
+    }
+
+    #[test]
+    fn test_hidden_order_is_matched() {
+        let mut orderbook_manager = OrderBookManager::new_l3();
+        orderbook_manager.add_hidden_order(OrderId(1), BookId(1), Qty(50), 1000, false);
+        orderbook_manager.add_order(OrderId(2), BookId(1), Qty(100), 1010, false);
+        assert_eq!(
+            orderbook_manager
+                .book(BookId(1))
+                .unwrap()
+                .best_ask()
+                .unwrap()
+                .price,
+            1010
+        );
+
+        let execution =
+            orderbook_manager.submit_limit_order(OrderId(10), BookId(1), Qty(100), 1010, true);
+        let fills: Vec<(u64, u32, u32)> = execution
+            .fills
+            .iter()
+            .map(|fill| (fill.maker_order_id.0, fill.price, fill.qty.value()))
+            .collect();
+        assert_eq!(fills, vec![(1, 1000, 50), (2, 1010, 50)]);
+    }
+
+    #[test]
+    fn test_hidden_remainder_is_acknowledged() {
+        let mut orderbook_manager = OrderBookManager::new_l3();
+        let request =
+            OrderRequest::limit(OrderId(1), BookId(1), Side::Bid, Qty(100), 1000).with_hidden(true);
+        let execution = orderbook_manager.submit_order(&request);
+        assert_eq!(execution.status, ExecutionStatus::Resting);
+        assert!(execution.hidden);
+        assert_eq!(execution.resting_price, Some(1000));
+
+        let book = orderbook_manager.book(BookId(1)).unwrap();
+        assert!(book.best_bid().is_none());
+        assert!(book.node(OrderId(1)).unwrap().attributes().fully_hidden);
+    }
+
+    #[test]
+    fn test_post_only_reject() {
+        let mut orderbook_manager = OrderBookManager::new_l3();
+        orderbook_manager.add_order(OrderId(1), BookId(1), Qty(100), 1000, false);
+
+        let request = OrderRequest::limit(OrderId(10), BookId(1), Side::Bid, Qty(10), 1000)
+            .with_post_only(PostOnly::Reject);
+        let execution = orderbook_manager.submit_order(&request);
+        assert_eq!(
+            execution.status,
+            ExecutionStatus::Rejected(RejectReason::WouldTakeLiquidity)
+        );
+        assert!(orderbook_manager.oid_map.get(OrderId(10)).is_none());
+        let best_ask = orderbook_manager
+            .book(BookId(1))
+            .unwrap()
+            .best_ask()
+            .unwrap();
+        assert_eq!(best_ask.qty, Qty(100));
+
+        let request = OrderRequest::limit(OrderId(11), BookId(1), Side::Bid, Qty(10), 990)
+            .with_post_only(PostOnly::Reject);
+        let execution = orderbook_manager.submit_order(&request);
+        assert_eq!(execution.status, ExecutionStatus::Resting);
+        assert_eq!(execution.resting_price, Some(990));
+
+        // A post-only market order would always take liquidity.
+        let request = OrderRequest::market(OrderId(12), BookId(1), Side::Bid, Qty(10))
+            .with_post_only(PostOnly::Reprice);
+        assert_eq!(
+            orderbook_manager.submit_order(&request).status,
+            ExecutionStatus::Rejected(RejectReason::WouldTakeLiquidity)
+        );
+    }
+
+    #[test]
+    fn test_post_only_reprice() {
+        let mut orderbook_manager = OrderBookManager::new_l3();
+        orderbook_manager.add_order(OrderId(1), BookId(1), Qty(100), 1000, false);
+        orderbook_manager.add_order(OrderId(2), BookId(1), Qty(100), 990, true);
+
+        let request = OrderRequest::limit(OrderId(10), BookId(1), Side::Bid, Qty(10), 1005)
+            .with_post_only(PostOnly::Reprice);
+        let execution = orderbook_manager.submit_order(&request);
+        assert_eq!(execution.status, ExecutionStatus::Resting);
+        assert!(execution.fills.is_empty());
+        assert_eq!(execution.resting_price, Some(999));
+
+        let request = OrderRequest::limit(OrderId(11), BookId(1), Side::Ask, Qty(10), 980)
+            .with_post_only(PostOnly::Reprice);
+        let execution = orderbook_manager.submit_order(&request);
+        assert_eq!(execution.resting_price, Some(1000));
+        let book = orderbook_manager.book(BookId(1)).unwrap();
+        assert_eq!(book.best_bid().unwrap().price, 999);
+        assert_eq!(book.best_ask().unwrap().qty, Qty(110));
+    }
+
+    #[test]
+    fn test_post_only_reprice_outside_the_price_range_is_rejected() {
+        let mut orderbook_manager = OrderBookManager::new_l3();
+        let max_price = i32::MAX as u32;
+        orderbook_manager.add_order(OrderId(1), BookId(1), Qty(100), max_price, true);
+
+        let request = OrderRequest::limit(OrderId(10), BookId(1), Side::Ask, Qty(10), max_price)
+            .with_post_only(PostOnly::Reprice);
+        assert_eq!(
+            orderbook_manager.submit_order(&request).status,
+            ExecutionStatus::Rejected(RejectReason::WouldTakeLiquidity)
+        );
+        let book = orderbook_manager.book(BookId(1)).unwrap();
+        assert!(book.best_ask().is_none());
+    }
+
+    #[test]
+    fn test_post_only_sees_hidden_liquidity() {
+        let mut orderbook_manager = OrderBookManager::new_l3();
+        orderbook_manager.add_hidden_order(OrderId(1), BookId(1), Qty(100), 1000, false);
+
+        let request = OrderRequest::limit(OrderId(10), BookId(1), Side::Bid, Qty(10), 1000)
+            .with_post_only(PostOnly::Reject);
+        assert_eq!(
+            orderbook_manager.submit_order(&request).status,
+            ExecutionStatus::Rejected(RejectReason::WouldTakeLiquidity)
+        );
+    }
+}
@@ -0,0 +1,174 @@
+#[cfg(test)]
+mod tests {
+    use crate::lob_tests::utils::fills;
+    use optimized_lob::l3_orderbook::L3OrderBook;
+    use optimized_lob::matching::OrderRequest;
+    use optimized_lob::order::{OidMap, OrderId};
+    use optimized_lob::orderbook_manager::OrderBookManager;
+    use optimized_lob::peg::{PegSpec, PegType};
+    use optimized_lob::quantity::Qty;
+    use optimized_lob::side::Side;
+    use optimized_lob::utils::BookId;
+
+    /// A bid of 100 @ 990 and an ask of 100 @ 1010.
+    fn sample_book() -> OrderBookManager<OidMap, L3OrderBook> {
+        let mut orderbook_manager = OrderBookManager::new_l3();
+        orderbook_manager.add_order(OrderId(1), BookId(1), Qty(100), 990, true);
+        orderbook_manager.add_order(OrderId(2), BookId(1), Qty(100), 1010, false);
+        orderbook_manager
+    }
+
+    fn peg_price(orderbook_manager: &OrderBookManager<OidMap, L3OrderBook>, id: u64) -> u32 {
+        let book = orderbook_manager.book(BookId(1)).unwrap();
+        book.pegs.get(OrderId(id)).unwrap().price
+    }
+
+    fn queue_at(
+        orderbook_manager: &OrderBookManager<OidMap, L3OrderBook>,
+        side: Side,
+        price: u32,
+    ) -> Vec<u64> {
+        let book = orderbook_manager.book(BookId(1)).unwrap();
+        book.orders_at(side, price)
+            .map(|node| node.order_id().0)
+            .collect()
+    }
+
+    #[test]
+    fn test_primary_peg_follows_best_bid() {
+        let mut orderbook_manager = sample_book();
+        let spec = PegSpec::new(PegType::Primary);
+        let price =
+            orderbook_manager.add_pegged_order(OrderId(10), BookId(1), Side::Bid, Qty(50), spec);
+        assert_eq!(price, Some(990));
+        assert_eq!(queue_at(&orderbook_manager, Side::Bid, 990), vec![1, 10]);
+
+        orderbook_manager.add_order(OrderId(3), BookId(1), Qty(100), 1000, true);
+        assert_eq!(peg_price(&orderbook_manager, 10), 1000);
+        assert_eq!(queue_at(&orderbook_manager, Side::Bid, 1000), vec![3, 10]);
+        assert_eq!(queue_at(&orderbook_manager, Side::Bid, 990), vec![1]);
+
+        // The level of the pegged order alone is not a reference price.
+        orderbook_manager.remove_order(OrderId(3));
+        assert_eq!(peg_price(&orderbook_manager, 10), 990);
+        assert_eq!(queue_at(&orderbook_manager, Side::Bid, 990), vec![1, 10]);
+        assert!(orderbook_manager
+            .book(BookId(1))
+            .unwrap()
+            .level_at(Side::Bid, 1000)
+            .is_none());
+
+        // Without a reference price, the pegged order stays where it is.
+        orderbook_manager.remove_order(OrderId(1));
+        assert_eq!(peg_price(&orderbook_manager, 10), 990);
+        let best_bid = orderbook_manager
+            .book(BookId(1))
+            .unwrap()
+            .best_bid()
+            .unwrap();
+        assert_eq!((best_bid.price, best_bid.qty), (990, Qty(50)));
+    }
+
+    #[test]
+    fn test_midpoint_peg_rests_at_half_tick() {
+        let mut orderbook_manager = sample_book();
+        let spec = PegSpec::new(PegType::Midpoint);
+        orderbook_manager.add_pegged_order(OrderId(10), BookId(1), Side::Bid, Qty(50), spec);
+        orderbook_manager.add_pegged_order(OrderId(11), BookId(1), Side::Ask, Qty(50), spec);
+        assert_eq!(peg_price(&orderbook_manager, 10), 1000);
+        assert_eq!(peg_price(&orderbook_manager, 11), 1000);
+
+        // With a tick of 10, the midpoint of 990 and 1000 is half a tick.
+        orderbook_manager.add_order(OrderId(3), BookId(1), Qty(100), 1000, false);
+        assert_eq!(peg_price(&orderbook_manager, 10), 995);
+        assert_eq!(peg_price(&orderbook_manager, 11), 995);
+
+        // A midpoint between two Price(4) units is rounded away from the opposite side.
+        orderbook_manager.add_order(OrderId(4), BookId(1), Qty(100), 991, true);
+        assert_eq!(peg_price(&orderbook_manager, 10), 995);
+        assert_eq!(peg_price(&orderbook_manager, 11), 996);
+        let book = orderbook_manager.book(BookId(1)).unwrap();
+        assert_eq!(book.best_bid().unwrap().price, 995);
+        assert_eq!(book.best_ask().unwrap().price, 996);
+    }
+
+    #[test]
+    fn test_offset_limit_and_market_peg() {
+        let mut orderbook_manager = sample_book();
+        let capped = PegSpec::new(PegType::Primary)
+            .with_offset(5)
+            .with_limit(1000);
+        let market_ask = PegSpec::new(PegType::Market).with_offset(10);
+        let market_bid = PegSpec::new(PegType::Market);
+        orderbook_manager.add_pegged_order(OrderId(10), BookId(1), Side::Bid, Qty(50), capped);
+        orderbook_manager.add_pegged_order(OrderId(11), BookId(1), Side::Ask, Qty(50), market_ask);
+        orderbook_manager.add_pegged_order(OrderId(12), BookId(1), Side::Bid, Qty(50), market_bid);
+        assert_eq!(peg_price(&orderbook_manager, 10), 995);
+        assert_eq!(peg_price(&orderbook_manager, 11), 1000);
+        // A pegged order never reaches the opposite best price.
+        assert_eq!(peg_price(&orderbook_manager, 12), 1009);
+
+        orderbook_manager.add_order(OrderId(3), BookId(1), Qty(100), 1000, true);
+        assert_eq!(peg_price(&orderbook_manager, 10), 1000);
+        assert_eq!(peg_price(&orderbook_manager, 11), 1010);
+        assert_eq!(peg_price(&orderbook_manager, 12), 1009);
+    }
+
+    #[test]
+    fn test_missing_reference_price() {
+        let mut orderbook_manager = OrderBookManager::new_l3();
+        orderbook_manager.add_order(OrderId(1), BookId(1), Qty(100), 990, true);
+        let spec = PegSpec::new(PegType::Midpoint);
+        let price =
+            orderbook_manager.add_pegged_order(OrderId(10), BookId(1), Side::Bid, Qty(50), spec);
+        assert_eq!(price, None);
+        assert!(orderbook_manager.oid_map.get(OrderId(10)).is_none());
+        assert!(orderbook_manager.book(BookId(1)).unwrap().pegs.is_empty());
+    }
+
+    #[test]
+    fn test_repricing_keeps_arrival_order() {
+        let mut orderbook_manager = sample_book();
+        let spec = PegSpec::new(PegType::Primary);
+        orderbook_manager.add_pegged_order(OrderId(10), BookId(1), Side::Bid, Qty(50), spec);
+        orderbook_manager.add_pegged_order(OrderId(11), BookId(1), Side::Bid, Qty(100), spec);
+
+        orderbook_manager.add_order(OrderId(3), BookId(1), Qty(100), 1000, true);
+        assert_eq!(
+            queue_at(&orderbook_manager, Side::Bid, 1000),
+            vec![3, 10, 11]
+        );
+        let position = orderbook_manager.queue_position(OrderId(11)).unwrap();
+        assert_eq!(position.orders_ahead, 2);
+        assert_eq!(position.qty_ahead, Qty(150));
+    }
+
+    #[test]
+    fn test_pegged_orders_trade_and_reprice() {
+        let mut orderbook_manager = sample_book();
+        let spec = PegSpec::new(PegType::Primary);
+        orderbook_manager.add_order(OrderId(3), BookId(1), Qty(100), 1000, true);
+        orderbook_manager.add_pegged_order(OrderId(10), BookId(1), Side::Bid, Qty(100), spec);
+        orderbook_manager.add_pegged_order(OrderId(11), BookId(1), Side::Bid, Qty(100), spec);
+
+        // Pegged orders are repriced once the incoming order is matched.
+        let request = OrderRequest::limit(OrderId(20), BookId(1), Side::Ask, Qty(150), 1000);
+        let execution = orderbook_manager.submit_order(&request);
+        assert_eq!(fills(&execution), vec![(3, 100), (10, 50)]);
+        assert_eq!(
+            queue_at(&orderbook_manager, Side::Bid, 990),
+            vec![1, 10, 11]
+        );
+        assert_eq!(
+            orderbook_manager.oid_map.get(OrderId(10)).unwrap().qty(),
+            Qty(50)
+        );
+
+        let request = OrderRequest::market(OrderId(21), BookId(1), Side::Ask, Qty(300));
+        let execution = orderbook_manager.submit_order(&request);
+        assert_eq!(execution.filled, Qty(250));
+        let book = orderbook_manager.book(BookId(1)).unwrap();
+        assert!(book.pegs.is_empty());
+        assert!(book.best_bid().is_none());
+    }
+}
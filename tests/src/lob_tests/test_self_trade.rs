@@ -0,0 +1,207 @@
+#[cfg(test)]
+mod tests {
+    use crate::lob_tests::utils::fills;
+    use optimized_lob::l3_orderbook::L3OrderBook;
+    use optimized_lob::matching::{
+        Execution, ExecutionStatus, OrderRequest, RejectReason, SelfTradeCancel,
+        SelfTradePrevention,
+    };
+    use optimized_lob::order::{OidMap, OrderId, OwnerId};
+    use optimized_lob::orderbook_manager::OrderBookManager;
+    use optimized_lob::quantity::Qty;
+    use optimized_lob::side::Side;
+    use optimized_lob::utils::BookId;
+
+    const FIRM_A: OwnerId = OwnerId(1);
+    const FIRM_B: OwnerId = OwnerId(2);
+
+    /// Asks of 60 @ 1000 (firm A), 40 @ 1000 (firm B) and 100 @ 1010 (firm A).
+    fn sample_book() -> OrderBookManager<OidMap, L3OrderBook> {
+        let mut orderbook_manager = OrderBookManager::new_l3();
+        for (id, qty, price, owner) in [
+            (1, 60, 1000, FIRM_A),
+            (2, 40, 1000, FIRM_B),
+            (3, 100, 1010, FIRM_A),
+        ] {
+            let request = OrderRequest::limit(OrderId(id), BookId(1), Side::Ask, Qty(qty), price)
+                .with_owner(owner, SelfTradePrevention::CancelNewest);
+            orderbook_manager.submit_order(&request);
+        }
+        orderbook_manager
+    }
+
+    fn buy(
+        orderbook_manager: &mut OrderBookManager<OidMap, L3OrderBook>,
+        qty: u32,
+        stp: SelfTradePrevention,
+    ) -> Execution {
+        let request = OrderRequest::limit(OrderId(10), BookId(1), Side::Bid, Qty(qty), 1010)
+            .with_owner(FIRM_A, stp);
+        orderbook_manager.submit_order(&request)
+    }
+
+    fn cancel(id: u64, qty: u32) -> SelfTradeCancel {
+        SelfTradeCancel {
+            order_id: OrderId(id),
+            qty: Qty(qty),
+        }
+    }
+
+    #[test]
+    fn test_owner_is_stored() {
+        let orderbook_manager = sample_book();
+        let book = orderbook_manager.book(BookId(1)).unwrap();
+        assert_eq!(book.node(OrderId(2)).unwrap().attributes().owner, FIRM_B);
+        assert!(FIRM_A.is_same(FIRM_A));
+        assert!(!FIRM_A.is_same(FIRM_B));
+        assert!(!OwnerId(0).is_same(OwnerId(0)));
+    }
+
+    #[test]
+    fn test_cancel_newest() {
+        let mut orderbook_manager = sample_book();
+        let execution = buy(
+            &mut orderbook_manager,
+            150,
+            SelfTradePrevention::CancelNewest,
+        );
+        assert_eq!(execution.status, ExecutionStatus::Cancelled);
+        assert!(execution.fills.is_empty());
+        assert_eq!(execution.cancelled, Qty(150));
+        assert!(execution.self_trade_cancels.is_empty());
+        assert!(orderbook_manager.oid_map.get(OrderId(10)).is_none());
+        let best_ask = orderbook_manager
+            .book(BookId(1))
+            .unwrap()
+            .best_ask()
+            .unwrap();
+        assert_eq!(best_ask.qty, Qty(100));
+    }
+
+    #[test]
+    fn test_cancel_oldest() {
+        let mut orderbook_manager = sample_book();
+        let execution = buy(
+            &mut orderbook_manager,
+            150,
+            SelfTradePrevention::CancelOldest,
+        );
+        assert_eq!(execution.status, ExecutionStatus::PartiallyFilled);
+        assert_eq!(fills(&execution), vec![(2, 40)]);
+        assert_eq!(
+            execution.self_trade_cancels,
+            vec![cancel(1, 60), cancel(3, 100)]
+        );
+        assert_eq!(execution.resting, Qty(110));
+
+        let book = orderbook_manager.book(BookId(1)).unwrap();
+        assert!(book.best_ask().is_none());
+        assert_eq!(book.best_bid().unwrap().price, 1010);
+        assert_eq!(book.node(OrderId(10)).unwrap().attributes().owner, FIRM_A);
+    }
+
+    #[test]
+    fn test_cancel_both() {
+        let mut orderbook_manager = sample_book();
+        let execution = buy(&mut orderbook_manager, 150, SelfTradePrevention::CancelBoth);
+        assert_eq!(execution.status, ExecutionStatus::Cancelled);
+        assert_eq!(execution.self_trade_cancels, vec![cancel(1, 60)]);
+        assert_eq!(execution.cancelled, Qty(150));
+
+        let book = orderbook_manager.book(BookId(1)).unwrap();
+        let best_ask = book.best_ask().unwrap();
+        assert_eq!((best_ask.price, best_ask.qty), (1000, Qty(40)));
+    }
+
+    #[test]
+    fn test_decrement_and_cancel() {
+        let mut orderbook_manager = sample_book();
+        let execution = buy(
+            &mut orderbook_manager,
+            100,
+            SelfTradePrevention::DecrementAndCancel,
+        );
+        assert_eq!(execution.status, ExecutionStatus::PartiallyFilled);
+        assert_eq!(fills(&execution), vec![(2, 40)]);
+        assert_eq!(execution.self_trade_cancels, vec![cancel(1, 60)]);
+        assert_eq!(execution.filled, Qty(40));
+        assert_eq!(execution.cancelled, Qty(60));
+        assert!(orderbook_manager.oid_map.get(OrderId(1)).is_none());
+
+        // The smaller incoming order is cancelled and the resting order is reduced.
+        let request = OrderRequest::limit(OrderId(11), BookId(1), Side::Bid, Qty(30), 1010)
+            .with_owner(FIRM_A, SelfTradePrevention::DecrementAndCancel);
+        let execution = orderbook_manager.submit_order(&request);
+        assert_eq!(execution.status, ExecutionStatus::Cancelled);
+        assert_eq!(execution.self_trade_cancels, vec![cancel(3, 30)]);
+        assert_eq!(
+            orderbook_manager.oid_map.get(OrderId(3)).unwrap().qty(),
+            Qty(70)
+        );
+        let book = orderbook_manager.book(BookId(1)).unwrap();
+        assert_eq!(book.last_trade_price(), Some(1000));
+    }
+
+    #[test]
+    fn test_fill_or_kill_ignores_own_liquidity() {
+        let fill_or_kill = |id: u64, qty: u32, stp: SelfTradePrevention| {
+            OrderRequest::fill_or_kill(OrderId(id), BookId(1), Side::Bid, Qty(qty), 1000)
+                .with_owner(FIRM_A, stp)
+        };
+        let mut orderbook_manager = OrderBookManager::new_l3();
+        for (id, qty, owner) in [(1, 60, FIRM_B), (2, 40, FIRM_A)] {
+            let request = OrderRequest::limit(OrderId(id), BookId(1), Side::Ask, Qty(qty), 1000)
+                .with_owner(owner, SelfTradePrevention::CancelNewest);
+            orderbook_manager.submit_order(&request);
+        }
+
+        // Only 60 of the 100 at 1000 can trade with firm A.
+        let execution = orderbook_manager.submit_order(&fill_or_kill(
+            10,
+            100,
+            SelfTradePrevention::CancelNewest,
+        ));
+        assert_eq!(
+            execution.status,
+            ExecutionStatus::Rejected(RejectReason::InsufficientLiquidity)
+        );
+        let execution = orderbook_manager.submit_order(&fill_or_kill(
+            11,
+            100,
+            SelfTradePrevention::CancelOldest,
+        ));
+        assert_eq!(
+            execution.status,
+            ExecutionStatus::Rejected(RejectReason::InsufficientLiquidity)
+        );
+        let book = orderbook_manager.book(BookId(1)).unwrap();
+        assert_eq!(book.best_ask().unwrap().qty, Qty(100));
+
+        // Firm A's order comes first in the queue of `sample_book`, and cancels an incoming
+        // order before it trades, unless the resting order is the one cancelled.
+        let mut orderbook_manager = sample_book();
+        let execution =
+            orderbook_manager.submit_order(&fill_or_kill(10, 40, SelfTradePrevention::CancelBoth));
+        assert_eq!(
+            execution.status,
+            ExecutionStatus::Rejected(RejectReason::InsufficientLiquidity)
+        );
+        let execution = orderbook_manager.submit_order(&fill_or_kill(
+            11,
+            40,
+            SelfTradePrevention::CancelOldest,
+        ));
+        assert_eq!(execution.status, ExecutionStatus::Filled);
+        assert_eq!(fills(&execution), vec![(2, 40)]);
+        assert_eq!(execution.self_trade_cancels, vec![cancel(1, 60)]);
+    }
+
+    #[test]
+    fn test_anonymous_orders_trade() {
+        let mut orderbook_manager = OrderBookManager::new_l3();
+        orderbook_manager.add_order(OrderId(1), BookId(1), Qty(100), 1000, false);
+        let execution =
+            orderbook_manager.submit_limit_order(OrderId(2), BookId(1), Qty(10), 1000, true);
+        assert_eq!(execution.status, ExecutionStatus::Filled);
+    }
+}
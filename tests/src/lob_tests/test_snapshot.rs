@@ -0,0 +1,169 @@
+#[cfg(test)]
+mod tests {
+    use crate::itch_tests::utils::{replay, replay_into};
+    use itch_parser::{Generator, GeneratorConfig, Message, MessageStream, MessageWriter};
+    use optimized_lob::allocation::ProRata;
+    use optimized_lob::error::SnapshotError;
+    use optimized_lob::l3_orderbook::L3OrderBook;
+    use optimized_lob::matching::{OrderRequest, SelfTradePrevention};
+    use optimized_lob::order::{OidMap, OrderId, OwnerId};
+    use optimized_lob::orderbook::OrderBook;
+    use optimized_lob::orderbook_manager::OrderBookManager;
+    use optimized_lob::peg::{PegSpec, PegType};
+    use optimized_lob::quantity::Qty;
+    use optimized_lob::side::Side;
+    use optimized_lob::snapshot::{SNAPSHOT_MAGIC, SNAPSHOT_VERSION};
+    use optimized_lob::utils::BookId;
+
+    fn messages(seed: u64) -> Vec<Message> {
+        let mut writer = MessageWriter::from_writer(Vec::new());
+        Generator::new(GeneratorConfig {
+            seed,
+            order_messages: 20_000,
+            ..GeneratorConfig::default()
+        })
+        .generate(&mut writer)
+        .unwrap();
+        let data = writer.into_inner().unwrap();
+        MessageStream::from_reader(&data[..])
+            .map(|msg| msg.unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn test_restored_replay_is_byte_identical() {
+        let messages = messages(11);
+        let (morning, afternoon) = messages.split_at(messages.len() / 2);
+
+        let (uninterrupted, _) = replay(&messages);
+
+        let (orderbook_manager, mut live) = replay(morning);
+        let snapshot = orderbook_manager.save();
+        drop(orderbook_manager);
+
+        let mut restored: OrderBookManager = OrderBookManager::restore(&snapshot).unwrap();
+        assert_eq!(restored.save(), snapshot);
+        replay_into(&mut restored, &mut live, afternoon);
+        assert_eq!(restored.save(), uninterrupted.save());
+    }
+
+    #[test]
+    fn test_order_attributes_are_restored() {
+        let mut orderbook_manager = OrderBookManager::new_l3();
+        orderbook_manager.add_iceberg_order(OrderId(1), BookId(2), Qty(500), Qty(100), 1000, false);
+        orderbook_manager.add_hidden_order(OrderId(2), BookId(2), Qty(70), 990, true);
+        let request = OrderRequest::limit(OrderId(1 << 40), BookId(3), Side::Bid, Qty(30), 500)
+            .with_owner(OwnerId(9), SelfTradePrevention::CancelNewest);
+        orderbook_manager.submit_order(&request);
+
+        let restored: OrderBookManager<OidMap, L3OrderBook> =
+            OrderBookManager::restore(&orderbook_manager.save()).unwrap();
+        for (book_id, order_id) in [
+            (BookId(2), OrderId(1)),
+            (BookId(2), OrderId(2)),
+            (BookId(3), OrderId(1 << 40)),
+        ] {
+            assert_eq!(
+                restored.oid_map.get(order_id),
+                orderbook_manager.oid_map.get(order_id)
+            );
+            let node = |manager: &OrderBookManager<OidMap, L3OrderBook>| {
+                let node = manager.book(book_id).unwrap().node(order_id).unwrap();
+                (node.qty(), node.attributes())
+            };
+            assert_eq!(node(&restored), node(&orderbook_manager));
+        }
+        let node = restored.book(BookId(3)).unwrap().node(OrderId(1 << 40));
+        assert_eq!(node.unwrap().attributes().owner, OwnerId(9));
+        let book = restored.book(BookId(2)).unwrap();
+        assert_eq!(book.best_ask().unwrap().total_qty, Qty(500));
+        assert_eq!(book.best_bid(), None);
+        assert_eq!(
+            restored.book(BookId(3)).unwrap().best_bid().unwrap().qty,
+            Qty(30)
+        );
+        assert!(restored.book(BookId(1)).is_none());
+    }
+
+    #[test]
+    fn test_l3_books_are_restored() {
+        let mut orderbook_manager: OrderBookManager<OidMap, L3OrderBook<ProRata>> =
+            OrderBookManager::from_oid_map(OidMap::new());
+        orderbook_manager.set_allocation_policy(BookId(1), ProRata { min_qty: Qty(5) });
+        orderbook_manager.add_order(OrderId(1), BookId(1), Qty(100), 990, true);
+        orderbook_manager.add_order(OrderId(2), BookId(1), Qty(200), 1010, false);
+        orderbook_manager.add_order(OrderId(3), BookId(1), Qty(300), 1010, false);
+        orderbook_manager.add_iceberg_order(OrderId(4), BookId(1), Qty(400), Qty(50), 1020, false);
+        orderbook_manager.add_pegged_order(
+            OrderId(5),
+            BookId(1),
+            Side::Bid,
+            Qty(60),
+            PegSpec::new(PegType::Midpoint),
+        );
+        let request = OrderRequest::limit(OrderId(6), BookId(1), Side::Bid, Qty(100), 1010)
+            .with_owner(OwnerId(7), SelfTradePrevention::CancelOldest);
+        orderbook_manager.submit_order(&request);
+        orderbook_manager.submit_order(&OrderRequest::stop(
+            OrderId(7),
+            BookId(1),
+            Side::Bid,
+            Qty(80),
+            1015,
+        ));
+        orderbook_manager.cancel_order(OrderId(1), Qty(100));
+        orderbook_manager.remove_order(OrderId(2));
+
+        let snapshot = orderbook_manager.save();
+        let mut restored: OrderBookManager<OidMap, L3OrderBook<ProRata>> =
+            OrderBookManager::restore(&snapshot).unwrap();
+        assert_eq!(restored.save(), snapshot);
+        let book = restored.book(BookId(1)).unwrap();
+        assert_eq!(book.allocation, ProRata { min_qty: Qty(5) });
+        assert_eq!(book.last_trade_price(), Some(1010));
+        assert_eq!(book.stops.len(), 1);
+        assert!(book.pegs.get(OrderId(5)).is_some());
+
+        // Both books trigger the stop order, fill the iceberg and reprice the peg alike.
+        let request = OrderRequest::market(OrderId(8), BookId(1), Side::Bid, Qty(600));
+        let execution = restored.submit_order(&request);
+        assert_eq!(execution.triggered.len(), 1);
+        assert_eq!(execution, orderbook_manager.submit_order(&request));
+        assert_eq!(restored.save(), orderbook_manager.save());
+    }
+
+    #[test]
+    fn test_corrupt_snapshots_are_rejected() {
+        let mut orderbook_manager = OrderBookManager::new();
+        orderbook_manager.add_order(OrderId(1), BookId(1), Qty(100), 500, true);
+        let snapshot = orderbook_manager.save();
+        let restore = |bytes: &[u8]| OrderBookManager::<OidMap, OrderBook>::restore(bytes).err();
+
+        assert_eq!(restore(b"nope"), Some(SnapshotError::BadMagic));
+        let mut newer = SNAPSHOT_MAGIC.to_vec();
+        newer.extend_from_slice(&(SNAPSHOT_VERSION + 1).to_le_bytes());
+        assert_eq!(
+            restore(&newer),
+            Some(SnapshotError::UnsupportedVersion(SNAPSHOT_VERSION + 1))
+        );
+        for len in [0, 5, snapshot.len() / 2, snapshot.len() - 1] {
+            assert_eq!(restore(&snapshot[..len]), Some(SnapshotError::Truncated));
+        }
+        // The order map is read first: a corrupt order count or id must not allocate for it.
+        let mut huge_count = SNAPSHOT_MAGIC.to_vec();
+        huge_count.extend_from_slice(&SNAPSHOT_VERSION.to_le_bytes());
+        huge_count.extend_from_slice(&u64::MAX.to_le_bytes());
+        assert_eq!(restore(&huge_count), Some(SnapshotError::Truncated));
+        let mut huge_id = SNAPSHOT_MAGIC.to_vec();
+        huge_id.extend_from_slice(&SNAPSHOT_VERSION.to_le_bytes());
+        huge_id.extend_from_slice(&1u64.to_le_bytes());
+        huge_id.extend_from_slice(&u64::MAX.to_le_bytes());
+        assert_eq!(restore(&huge_id), Some(SnapshotError::Invalid("order id")));
+        let mut trailing = snapshot.clone();
+        trailing.push(0);
+        assert_eq!(
+            restore(&trailing),
+            Some(SnapshotError::Invalid("trailing bytes"))
+        );
+    }
+}
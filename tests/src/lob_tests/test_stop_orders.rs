@@ -0,0 +1,192 @@
+#[cfg(test)]
+mod tests {
+    use optimized_lob::l3_orderbook::L3OrderBook;
+    use optimized_lob::matching::{ExecutionStatus, OrderRequest, RejectReason};
+    use optimized_lob::order::{OidMap, OrderId};
+    use optimized_lob::orderbook_manager::OrderBookManager;
+    use optimized_lob::quantity::Qty;
+    use optimized_lob::side::Side;
+    use optimized_lob::stop_book::StopBook;
+    use optimized_lob::utils::BookId;
+
+    /// Asks of 100 @ 1000, 100 @ 1010 and 100 @ 1020, bids of 100 @ 990 and 100 @ 980.
+    fn sample_book() -> OrderBookManager<OidMap, L3OrderBook> {
+        let mut orderbook_manager = OrderBookManager::new_l3();
+        for (id, price) in [(1, 1000), (2, 1010), (3, 1020)] {
+            orderbook_manager.add_order(OrderId(id), BookId(1), Qty(100), price, false);
+        }
+        for (id, price) in [(4, 990), (5, 980)] {
+            orderbook_manager.add_order(OrderId(id), BookId(1), Qty(100), price, true);
+        }
+        orderbook_manager
+    }
+
+    fn buy(orderbook_manager: &mut OrderBookManager<OidMap, L3OrderBook>, id: u64, qty: u32) {
+        let request = OrderRequest::market(OrderId(id), BookId(1), Side::Bid, Qty(qty));
+        orderbook_manager.submit_order(&request);
+    }
+
+    #[test]
+    fn test_stop_book_trigger_order() {
+        let mut stops = StopBook::new();
+        let request = |id, side| OrderRequest::stop(OrderId(id), BookId(1), side, Qty(1), 0);
+        stops.insert(1010, request(1, Side::Bid));
+        stops.insert(1000, request(2, Side::Bid));
+        stops.insert(1000, request(3, Side::Bid));
+        stops.insert(1020, request(4, Side::Bid));
+        stops.insert(990, request(5, Side::Ask));
+        stops.insert(1010, request(6, Side::Ask));
+        stops.insert(1015, request(7, Side::Ask));
+        assert_eq!(stops.len(), 7);
+
+        let triggered: Vec<u64> = stops
+            .take_triggered(1010)
+            .iter()
+            .map(|request| request.order_id.0)
+            .collect();
+        assert_eq!(triggered, vec![2, 3, 1, 7, 6]);
+        assert_eq!(stops.len(), 2);
+        assert!(stops.contains(OrderId(4)));
+        assert!(stops.contains(OrderId(5)));
+        assert!(stops.take_triggered(1000).is_empty());
+
+        assert_eq!(stops.remove(OrderId(5)).unwrap().order_id, OrderId(5));
+        assert!(stops.remove(OrderId(5)).is_none());
+        assert_eq!(stops.len(), 1);
+    }
+
+    #[test]
+    fn test_buy_stop_triggers_on_trade() {
+        let mut orderbook_manager = sample_book();
+
+        let execution = orderbook_manager.submit_order(&OrderRequest::stop(
+            OrderId(10),
+            BookId(1),
+            Side::Bid,
+            Qty(150),
+            1000,
+        ));
+        assert_eq!(execution.status, ExecutionStatus::Pending);
+        assert_eq!(orderbook_manager.book(BookId(1)).unwrap().stops.len(), 1);
+
+        // A trade below the trigger does not release the stop.
+        let request = OrderRequest::market(OrderId(11), BookId(1), Side::Ask, Qty(10));
+        let execution = orderbook_manager.submit_order(&request);
+        assert!(execution.triggered.is_empty());
+
+        // A trade at the trigger releases it as a market order.
+        let request = OrderRequest::market(OrderId(12), BookId(1), Side::Bid, Qty(10));
+        let execution = orderbook_manager.submit_order(&request);
+        assert_eq!(execution.fills[0].price, 1000);
+        assert_eq!(execution.triggered.len(), 1);
+        let stop = &execution.triggered[0];
+        assert_eq!(stop.order_id, OrderId(10));
+        assert_eq!(stop.status, ExecutionStatus::Filled);
+        assert_eq!(stop.fills.len(), 2);
+        assert_eq!(stop.fills[0].qty, Qty(90));
+        assert_eq!(stop.fills[1].price, 1010);
+
+        let book = orderbook_manager.book(BookId(1)).unwrap();
+        assert!(book.stops.is_empty());
+        assert_eq!(book.last_trade_price(), Some(1010));
+        assert_eq!(book.best_ask().unwrap().qty, Qty(40));
+    }
+
+    #[test]
+    fn test_sell_stop_limit_rests_after_trigger() {
+        let mut orderbook_manager = sample_book();
+
+        let execution = orderbook_manager.submit_order(&OrderRequest::stop_limit(
+            OrderId(10),
+            BookId(1),
+            Side::Ask,
+            Qty(150),
+            990,
+            985,
+        ));
+        assert_eq!(execution.status, ExecutionStatus::Pending);
+
+        let request = OrderRequest::market(OrderId(11), BookId(1), Side::Ask, Qty(50));
+        let execution = orderbook_manager.submit_order(&request);
+        let stop = &execution.triggered[0];
+        assert_eq!(stop.status, ExecutionStatus::PartiallyFilled);
+        assert_eq!(stop.filled, Qty(50));
+        assert_eq!(stop.resting, Qty(100));
+
+        let book = orderbook_manager.book(BookId(1)).unwrap();
+        assert_eq!(book.best_ask().unwrap().price, 985);
+        assert_eq!(book.best_bid().unwrap().price, 980);
+    }
+
+    #[test]
+    fn test_stops_cascade() {
+        let mut orderbook_manager = sample_book();
+        let stop = |id, qty, trigger| {
+            OrderRequest::stop(OrderId(id), BookId(1), Side::Bid, Qty(qty), trigger)
+        };
+        orderbook_manager.submit_order(&stop(10, 100, 1000));
+        orderbook_manager.submit_order(&stop(11, 50, 1010));
+        orderbook_manager.submit_order(&stop(12, 50, 1010));
+        orderbook_manager.submit_order(&stop(13, 10, 1030));
+
+        // The trade at 1000 releases stop 10, which trades at 1010 and releases 11 and 12.
+        buy(&mut orderbook_manager, 20, 10);
+        let book = orderbook_manager.book(BookId(1)).unwrap();
+        assert_eq!(book.stops.len(), 1);
+        assert_eq!(book.last_trade_price(), Some(1020));
+        assert_eq!(book.best_ask().unwrap().qty, Qty(90));
+    }
+
+    #[test]
+    fn test_cascade_order_is_reported() {
+        let mut orderbook_manager = sample_book();
+        let stop = |id, qty, trigger| {
+            OrderRequest::stop(OrderId(id), BookId(1), Side::Bid, Qty(qty), trigger)
+        };
+        orderbook_manager.submit_order(&stop(10, 100, 1000));
+        orderbook_manager.submit_order(&stop(11, 50, 1010));
+        orderbook_manager.submit_order(&stop(12, 50, 1010));
+
+        let request = OrderRequest::market(OrderId(20), BookId(1), Side::Bid, Qty(10));
+        let execution = orderbook_manager.submit_order(&request);
+        let released: Vec<(u64, u32)> = execution
+            .triggered
+            .iter()
+            .map(|ex| (ex.order_id.0, ex.fills.last().unwrap().price))
+            .collect();
+        assert_eq!(released, vec![(10, 1010), (11, 1010), (12, 1020)]);
+    }
+
+    #[test]
+    fn test_stop_already_triggered_and_cancel() {
+        let mut orderbook_manager = sample_book();
+        buy(&mut orderbook_manager, 20, 10);
+
+        // The last trade is at 1000, so a buy stop at 995 is released at once.
+        let execution = orderbook_manager.submit_order(&OrderRequest::stop(
+            OrderId(10),
+            BookId(1),
+            Side::Bid,
+            Qty(20),
+            995,
+        ));
+        assert_eq!(execution.status, ExecutionStatus::Filled);
+        assert_eq!(execution.fills[0].price, 1000);
+
+        let request = OrderRequest::stop(OrderId(11), BookId(1), Side::Ask, Qty(20), 900);
+        orderbook_manager.submit_order(&request);
+        assert_eq!(
+            orderbook_manager.submit_order(&request).status,
+            ExecutionStatus::Rejected(RejectReason::DuplicateOrderId)
+        );
+        assert_eq!(
+            orderbook_manager.cancel_stop_order(BookId(1), OrderId(11)),
+            Some(request)
+        );
+        assert_eq!(
+            orderbook_manager.cancel_stop_order(BookId(1), OrderId(11)),
+            None
+        );
+        assert!(orderbook_manager.book(BookId(1)).unwrap().stops.is_empty());
+    }
+}
@@ -1,13 +1,16 @@
 #![allow(unused_imports)]
+use optimized_lob::l3_orderbook::QueuePosition;
 use optimized_lob::level::LevelId;
+use optimized_lob::matching::Execution;
+use optimized_lob::oid_map::OrderIdMap;
 use optimized_lob::orderbook_manager::OrderBookManager;
 use optimized_lob::quantity::Qty;
 
 // A few helper functions for the tests
 
 #[cfg(test)]
-pub(crate) fn get_level_capacity(
-    orderbook_manager: &OrderBookManager,
+pub(crate) fn get_level_capacity<M: OrderIdMap>(
+    orderbook_manager: &OrderBookManager<M>,
     book_id: usize,
     level_id: u32,
 ) -> Qty {
@@ -22,3 +25,22 @@ pub(crate) fn get_level_capacity(
         .unwrap()
         .size()
 }
+
+/// Returns the resting order and quantity of every fill of an execution, in execution order.
+#[cfg(test)]
+pub(crate) fn fills(execution: &Execution) -> Vec<(u64, u32)> {
+    execution
+        .fills
+        .iter()
+        .map(|fill| (fill.maker_order_id.0, fill.qty.value()))
+        .collect()
+}
+
+/// Returns the queue position with the given orders and quantity ahead.
+#[cfg(test)]
+pub(crate) fn position(orders_ahead: u32, qty_ahead: u32) -> Option<QueuePosition> {
+    Some(QueuePosition {
+        orders_ahead,
+        qty_ahead: Qty(qty_ahead),
+    })
+}
@@ -1,4 +1,5 @@
 // main.rs
+mod itch_tests;
 mod lob_tests;
 mod test_itch_parser;
 mod test_lob;
@@ -8,6 +9,19 @@ use std::env;
 fn main() {
     let args: Vec<String> = env::args().collect();
 
+    // Write a synthetic ITCH file that can be used as ITCH_DATA, e.g. in CI
+    if args.len() > 2 && args[1] == "--generate" {
+        let mut config = itch_parser::GeneratorConfig::default();
+        if let Some(order_messages) = args.get(3) {
+            config.order_messages = order_messages
+                .parse()
+                .expect("Invalid number of order messages");
+        }
+        let messages = itch_parser::generate_file(&args[2], config).unwrap();
+        println!("Generated {} messages into {}", messages, args[2]);
+        return;
+    }
+
     // Parse command-line arguments
     let test_to_run = if args.len() > 1 && args[1] == "--itch-parser" {
         // If the "--itch-parser" flag is provided, run the itch parser test
@@ -1,160 +1,113 @@
 extern crate itch_parser;
 extern crate optimized_lob;
 
-use itch_parser::Body::{
-    AddOrder, DeleteOrder, OrderCancelled, OrderExecuted, OrderExecutedWithPrice, ReplaceOrder,
-};
-use itch_parser::MessageStream;
+use itch_parser::{ItchHandler, Message, MessageStream};
 use optimized_lob::{
     order::OrderId, orderbook_manager::OrderBookManager, quantity::Qty, utils::BookId,
 };
 use std::path::Path;
 use std::time::Instant;
 
-pub fn test_lob(file_path: &str) {
-    let path_to_market_data = Path::new(file_path);
-    let stream = MessageStream::from_file(path_to_market_data).unwrap();
+/// Replays the ITCH messages into the orderbook and keeps the statistics.
+struct LobHandler {
+    orderbook: OrderBookManager,
+    add_order_count: u32,
+    execute_orders_count: u32,
+    cancel_order_count: u32,
+    delete_order_count: u32,
+    replace_order_count: u32,
+}
 
-    println!("------------------------------------");
-    println!("LOB Processing...\n");
+impl LobHandler {
+    fn new() -> Self {
+        Self {
+            orderbook: OrderBookManager::new(),
+            add_order_count: 0,
+            execute_orders_count: 0,
+            cancel_order_count: 0,
+            delete_order_count: 0,
+            replace_order_count: 0,
+        }
+    }
+}
 
-    // Counters
-    let mut messages = 0;
-    let mut add_order_count = 0;
-    let mut execute_orders_count = 0;
-    let mut cancel_order_count = 0;
-    let mut delete_order_count = 0;
-    let mut replace_order_count = 0;
+impl ItchHandler for LobHandler {
+    fn on_add_order(
+        &mut self,
+        msg: &Message,
+        order_id: u64,
+        is_bid: bool,
+        shares: u32,
+        _stock: u64,
+        price: u32,
+    ) {
+        self.orderbook.add_order(
+            OrderId(order_id),
+            BookId(msg.stock_locate),
+            Qty(shares),
+            price,
+            is_bid,
+        );
+        self.add_order_count += 1;
+    }
 
-    let start = Instant::now();
-    let mut orderbook = OrderBookManager::new(); // Initialize the orderbook
+    fn on_delete_order(&mut self, _msg: &Message, order_id: u64) {
+        self.orderbook.remove_order(OrderId(order_id));
+        self.delete_order_count += 1;
+    }
 
-    // Process messages
-    for msg in stream {
-        let unwrapped_msg = msg.unwrap();
-        let stock_locate = unwrapped_msg.stock_locate;
+    fn on_cancel(&mut self, _msg: &Message, order_id: u64, shares: u32) {
+        self.orderbook.cancel_order(OrderId(order_id), Qty(shares));
+        self.cancel_order_count += 1;
+    }
 
-        match unwrapped_msg.body {
-            AddOrder {
-                order_id,
-                is_bid,
-                shares,
-                stock: _,
-                price,
-            } => {
-                let oid: Option<u32> = order_id.try_into().ok();
+    fn on_execute(&mut self, _msg: &Message, order_id: u64, shares: u32, _match_number: u64) {
+        self.orderbook.execute_order(OrderId(order_id), Qty(shares));
+        self.execute_orders_count += 1;
+    }
 
-                match oid {
-                    Some(id) => {
-                        orderbook.add_order(
-                            OrderId(id),
-                            BookId(stock_locate),
-                            Qty(shares),
-                            price,
-                            is_bid,
-                        );
-                    }
-                    None => {
-                        // Conversion failed due to overflow, handle the error here
-                        println!("Failed to convert Order ID u32 due to overflow");
-                        break;
-                    }
-                }
-                add_order_count += 1;
-            }
-            OrderExecuted {
-                order_id,
-                shares,
-                match_number: _,
-            } => {
-                let oid: Option<u32> = order_id.try_into().ok();
-                match oid {
-                    Some(id) => {
-                        orderbook.execute_order(OrderId(id), Qty(shares));
-                    }
-                    None => {
-                        // Conversion failed due to overflow, handle the error here
-                        println!("Failed to convert Order ID u32 due to overflow");
-                        break;
-                    }
-                }
-                execute_orders_count += 1;
-            }
-            OrderExecutedWithPrice {
-                order_id,
-                shares,
-                match_number: _,
-                printable: _,
-                price: _,
-            } => {
-                let oid: Option<u32> = order_id.try_into().ok();
-                match oid {
-                    Some(id) => {
-                        orderbook.execute_order(OrderId(id), Qty(shares));
-                    }
-                    None => {
-                        // Conversion failed due to overflow, handle the error here
-                        println!("Failed to convert Order ID u32 due to overflow");
-                        break;
-                    }
-                }
-                execute_orders_count += 1;
-            }
-            OrderCancelled { order_id, shares } => {
-                let oid: Option<u32> = order_id.try_into().ok();
-                match oid {
-                    Some(id) => {
-                        orderbook.cancel_order(OrderId(id), Qty(shares));
-                    }
-                    None => {
-                        // Conversion failed due to overflow, handle the error here
-                        println!("Failed to convert Order ID u32 due to overflow");
-                        break;
-                    }
-                }
-                cancel_order_count += 1;
-            }
-            DeleteOrder { order_id } => {
-                let oid: Option<u32> = order_id.try_into().ok();
-                match oid {
-                    Some(id) => {
-                        orderbook.remove_order(OrderId(id));
-                    }
-                    None => {
-                        // Conversion failed due to overflow, handle the error here
-                        println!("Failed to convert Order ID u32 due to overflow");
-                        break;
-                    }
-                }
-                delete_order_count += 1;
-            }
-            ReplaceOrder {
-                old_order_id,
-                new_order_id,
-                shares,
-                price,
-            } => {
-                let old_oid: Option<u32> = old_order_id.try_into().ok();
-                let new_oid: Option<u32> = new_order_id.try_into().ok();
+    fn on_execute_with_price(
+        &mut self,
+        msg: &Message,
+        order_id: u64,
+        shares: u32,
+        match_number: u64,
+        _printable: bool,
+        _price: u32,
+    ) {
+        self.on_execute(msg, order_id, shares, match_number);
+    }
 
-                match (old_oid, new_oid) {
-                    (Some(id), Some(new_id)) => {
-                        orderbook.replace_order(OrderId(id), OrderId(new_id), Qty(shares), price);
-                    }
-                    _ => {
-                        // Conversion failed due to overflow, handle the error here
-                        println!("Failed to convert Order ID u32 due to overflow");
-                        break;
-                    }
-                }
+    fn on_replace_order(
+        &mut self,
+        _msg: &Message,
+        old_order_id: u64,
+        new_order_id: u64,
+        shares: u32,
+        price: u32,
+    ) {
+        self.orderbook.replace_order(
+            OrderId(old_order_id),
+            OrderId(new_order_id),
+            Qty(shares),
+            price,
+        );
+        self.replace_order_count += 1;
+    }
+}
 
-                replace_order_count += 1;
-            }
-            _ => {}
-        }
+pub fn test_lob(file_path: &str) {
+    let path_to_market_data = Path::new(file_path);
+    let mut stream = MessageStream::from_file(path_to_market_data).unwrap();
 
-        messages += 1;
-    }
+    println!("------------------------------------");
+    println!("LOB Processing...\n");
+
+    let start = Instant::now();
+    let mut handler = LobHandler::new(); // Initialize the orderbook
+
+    // Process messages
+    let messages = stream.dispatch(&mut handler).unwrap();
 
     let duration = Instant::now() - start;
     let speed = messages as f64 / duration.as_secs_f64();
@@ -168,10 +121,10 @@ pub fn test_lob(file_path: &str) {
     println!("Total Time: {:.3} seconds", duration.as_secs_f64());
     println!("Speed: {} msg/second\n", speed as u32);
     println!("Orderbook Statistics:");
-    println!("Total Add Orders: {}", add_order_count);
-    println!("Total Execute Orders: {}", execute_orders_count);
-    println!("Total Cancel Orders: {}", cancel_order_count);
-    println!("Total Delete Orders: {}", delete_order_count);
-    println!("Total Replace Orders: {}", replace_order_count);
+    println!("Total Add Orders: {}", handler.add_order_count);
+    println!("Total Execute Orders: {}", handler.execute_orders_count);
+    println!("Total Cancel Orders: {}", handler.cancel_order_count);
+    println!("Total Delete Orders: {}", handler.delete_order_count);
+    println!("Total Replace Orders: {}", handler.replace_order_count);
     println!("------------------------------------");
 }